@@ -26,10 +26,11 @@ export interface ApiKeys {
   openrouter_api_key: string;
 }
 
-const defaultSettings: Settings = {
+// A function so the port reflects the prefix loaded by initPorts()
+const getDefaultSettings = (): Settings => ({
   serverPort: PORTS.OPENCODE,
   theme: "dark",
-};
+});
 
 const defaultApiKeys: ApiKeys = {
   openrouter_api_key: "",
@@ -37,7 +38,7 @@ const defaultApiKeys: ApiKeys = {
 
 export function useSettings() {
   const platform = usePlatform();
-  const [settings, setSettings] = useState<Settings>(defaultSettings);
+  const [settings, setSettings] = useState<Settings>(getDefaultSettings);
   const [apiKeys, setApiKeys] = useState<ApiKeys>(defaultApiKeys);
   const [loading, setLoading] = useState(true);
 
@@ -49,7 +50,7 @@ export function useSettings() {
         if (platform.storage) {
           const saved = await platform.storage.get<Settings>("settings");
           if (saved) {
-            setSettings({ ...defaultSettings, ...saved });
+            setSettings({ ...getDefaultSettings(), ...saved });
           }
         }
 
@@ -109,6 +110,7 @@ export function useSettings() {
 
   // Reset to defaults
   const resetSettings = useCallback(async () => {
+    const defaultSettings = getDefaultSettings();
     setSettings(defaultSettings);
     try {
       if (platform.storage) {
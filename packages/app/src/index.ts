@@ -48,6 +48,9 @@ export {
 // Hooks
 export * from "./hooks";
 
+// Service ports (call initPorts() before rendering)
+export { initPorts, PORTS } from "./lib/ports";
+
 // Theme utilities (use these - don't duplicate)
 export { getTheme, getThemeList, initTheme, setTheme, THEMES, type Theme } from "./lib/theme";
 
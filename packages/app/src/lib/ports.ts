@@ -11,14 +11,28 @@
  * - ${PREFIX}300: OpenCode AI server
  */
 
-// Read prefix from environment or use default
-const PORT_PREFIX = parseInt(import.meta.env.VITE_HANDS_PORT_PREFIX || "55", 10);
+import { invoke } from "@tauri-apps/api/core";
+
+// Build-time default, replaced by the desktop app's prefix in initPorts()
+let portPrefix = parseInt(import.meta.env.VITE_HANDS_PORT_PREFIX || "55", 10);
+
+/**
+ * Load the port prefix the desktop app is using (configurable in settings).
+ * Call once at startup, before anything reads PORTS.
+ */
+export async function initPorts(): Promise<void> {
+  try {
+    portPrefix = await invoke<number>("get_port_prefix");
+  } catch (err) {
+    console.warn("[ports] Using default port prefix:", err);
+  }
+}
 
 /**
  * Get the base port for a service offset
  */
 function getPort(offset: number): number {
-  return PORT_PREFIX * 1000 + offset;
+  return portPrefix * 1000 + offset;
 }
 
 /**
@@ -26,21 +40,29 @@ function getPort(offset: number): number {
  */
 export const PORTS = {
   /** Runtime API server (default: 55000) */
-  RUNTIME: getPort(0),
+  get RUNTIME() {
+    return getPort(0);
+  },
 
   /** PostgreSQL database (default: 55100) */
-  POSTGRES: getPort(100),
+  get POSTGRES() {
+    return getPort(100);
+  },
 
   /** Worker/Miniflare dev server (default: 55200) */
-  WORKER: getPort(200),
+  get WORKER() {
+    return getPort(200);
+  },
 
   /** OpenCode AI server (default: 55300) */
-  OPENCODE: getPort(300),
-} as const;
+  get OPENCODE() {
+    return getPort(300);
+  },
+};
 
 /**
  * Get the configured port prefix (e.g., 55 for 55xxx ports)
  */
 export function getPortPrefix(): number {
-  return PORT_PREFIX;
+  return portPrefix;
 }
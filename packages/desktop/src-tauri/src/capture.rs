@@ -2,10 +2,18 @@
 //!
 //! Uses the native Cmd+Shift+4 style region selection.
 
-use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_store::StoreExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::fs::File;
 use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::AppState;
 
 #[cfg(target_os = "macos")]
 use objc2_app_kit::NSEvent;
@@ -56,9 +64,195 @@ fn get_png_dimensions(path: &str) -> Option<(u32, u32)> {
     Some((width, height))
 }
 
+/// Read the captured region's top-left position from PNG `tEXt` metadata, if present.
+///
+/// screencapture may record the origin either as `XDGx`/`XDGy` keywords or as a
+/// `Comment` of the form `x=…,y=…`. Chunks are length (4) + type (4) + data + CRC (4),
+/// starting after the 8-byte signature.
+fn read_capture_position_from_png(path: &str) -> Option<(i32, i32)> {
+    let mut data = Vec::new();
+    File::open(path).ok()?.read_to_end(&mut data).ok()?;
+
+    if data.len() < 8 || &data[0..8] != b"\x89PNG\r\n\x1a\n" {
+        return None;
+    }
+
+    let (mut x, mut y) = (None, None);
+    let mut offset = 8;
+
+    while offset + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[offset..offset + 4].try_into().ok()?) as usize;
+        let chunk_type = &data[offset + 4..offset + 8];
+        let data_start = offset + 8;
+        let data_end = data_start.checked_add(length)?;
+        if data_end + 4 > data.len() || chunk_type == b"IEND" {
+            break;
+        }
+
+        if chunk_type == b"tEXt" {
+            let chunk = &data[data_start..data_end];
+            if let Some(sep) = chunk.iter().position(|&b| b == 0) {
+                let keyword = String::from_utf8_lossy(&chunk[..sep]);
+                let text = String::from_utf8_lossy(&chunk[sep + 1..]);
+                match keyword.as_ref() {
+                    "XDGx" => x = text.trim().parse().ok(),
+                    "XDGy" => y = text.trim().parse().ok(),
+                    "Comment" => {
+                        for part in text.split(',') {
+                            match part.trim().split_once('=') {
+                                Some(("x", v)) => x = v.trim().parse().ok(),
+                                Some(("y", v)) => y = v.trim().parse().ok(),
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        offset = data_end + 4; // skip CRC
+    }
+
+    Some((x?, y?))
+}
+
+const CAPTURE_FORMAT_KEY: &str = "capture_format";
+
+/// Image format captures are saved in. screencapture always writes PNG; other
+/// formats are re-encoded afterwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CaptureFormat {
+    #[default]
+    Png,
+    Jpeg { quality: u8 },
+    /// The `image` crate only encodes lossless WebP, so `quality` must be 100
+    Webp { quality: u8 },
+}
+
+impl CaptureFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg { .. } => "jpg",
+            CaptureFormat::Webp { .. } => "webp",
+        }
+    }
+
+    /// Reject settings the encoder can't honour instead of silently ignoring them
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            CaptureFormat::Webp { quality } if *quality != 100 => Err(format!(
+                "WebP captures are lossless only; quality must be 100 (got {})",
+                quality
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Saved capture format preference (`settings.json["capture_format"]`), PNG by default
+pub fn load_capture_format(app: &AppHandle) -> CaptureFormat {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(CAPTURE_FORMAT_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Re-encode a captured PNG into `format` on a blocking thread, replacing the original file.
+/// Returns the path of the encoded image (its extension changes with the format).
+async fn encode_capture(png_path: &Path, format: CaptureFormat) -> Result<PathBuf, String> {
+    if format == CaptureFormat::Png {
+        return Ok(png_path.to_path_buf());
+    }
+
+    let png_path = png_path.to_path_buf();
+    tokio::task::spawn_blocking(move || encode_image(&png_path, format))
+        .await
+        .map_err(|e| format!("Capture encoding task failed: {}", e))?
+}
+
+fn encode_image(png_path: &Path, format: CaptureFormat) -> Result<PathBuf, String> {
+    let image = image::open(png_path)
+        .map_err(|e| format!("Failed to read capture: {}", e))?;
+    let output_path = png_path.with_extension(format.extension());
+    let file = File::create(&output_path)
+        .map_err(|e| format!("Failed to create {}: {}", output_path.display(), e))?;
+    let writer = std::io::BufWriter::new(file);
+
+    let result = match format {
+        CaptureFormat::Jpeg { quality } => {
+            // JPEG has no alpha channel
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(writer, quality.clamp(1, 100));
+            image::DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)
+        }
+        CaptureFormat::Webp { .. } => {
+            image.write_with_encoder(image::codecs::webp::WebPEncoder::new_lossless(writer))
+        }
+        CaptureFormat::Png => unreachable!(),
+    };
+    result.map_err(|e| format!("Failed to encode capture as {}: {}", format.extension(), e))?;
+
+    let _ = std::fs::remove_file(png_path);
+    Ok(output_path)
+}
+
+/// Get the saved capture format
+#[tauri::command]
+pub fn get_capture_format(app: AppHandle) -> CaptureFormat {
+    load_capture_format(&app)
+}
+
+/// Save the capture format used when none is passed explicitly
+#[tauri::command]
+pub fn set_capture_format(app: AppHandle, format: CaptureFormat) -> Result<(), String> {
+    format.validate()?;
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(CAPTURE_FORMAT_KEY, serde_json::json!(format));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
+/// Check whether screencapture failed because Screen Recording permission is missing.
+/// `screencapture` prints e.g. "No screen recording permission." to stderr.
+fn is_permission_denied(output: &std::process::Output) -> bool {
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    stderr.contains("permission") || stderr.contains("not permitted")
+}
+
+/// Check whether the user pressed ESC during an interactive capture:
+/// screencapture exits non-zero without writing anything or printing an error
+fn is_cancelled(output: &std::process::Output, file_exists: bool) -> bool {
+    !output.status.success() && !file_exists && output.stderr.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Active workbook ID from app state, used to route captures to the right workbook
+async fn active_workbook_id(app: &AppHandle) -> Option<String> {
+    let state = app.try_state::<Arc<Mutex<AppState>>>()?;
+    let state = state.lock().await;
+    state.active_workbook_id.clone()
+}
+
 /// Start the screen capture flow using native macOS screencapture
 /// This gives the familiar Cmd+Shift+4 crosshair for region selection
 pub async fn start_capture(app: &AppHandle) -> Result<(), String> {
+    let workbook_id = active_workbook_id(app).await;
+    start_capture_for_workbook(app, workbook_id, None).await
+}
+
+/// `format` defaults to the saved preference
+async fn start_capture_for_workbook(
+    app: &AppHandle,
+    workbook_id: Option<String>,
+    format: Option<CaptureFormat>,
+) -> Result<(), String> {
+    if let Some(format) = &format {
+        format.validate()?;
+    }
+
     // Create temp directory for captures
     let temp_dir = std::env::temp_dir().join("hands-captures");
     std::fs::create_dir_all(&temp_dir)
@@ -80,22 +274,35 @@ pub async fn start_capture(app: &AppHandle) -> Result<(), String> {
         .output()
         .map_err(|e| format!("Failed to run screencapture: {}", e))?;
 
-    // Check if user cancelled (ESC key) - file won't exist
-    if !file_path.exists() {
+    // Missing Screen Recording permission is a user flow, not an error
+    if is_permission_denied(&output) {
+        println!("[capture] Screen recording permission denied");
+        let _ = app.emit("capture:permission-denied", serde_json::json!({}));
+        return Ok(());
+    }
+
+    if is_cancelled(&output, file_path.exists()) {
         println!("[capture] User cancelled screen capture");
         return Ok(());
     }
 
-    if !output.status.success() {
+    if !output.status.success() || !file_path.exists() {
         return Err("Screen capture failed".to_string());
     }
 
     println!("[capture] Screenshot saved to: {}", file_path_str);
+    crate::telemetry::track(app, "capture_used").await;
 
     // Get mouse position and screen scale factor
     let (mouse_x, mouse_y, scale) = get_mouse_position_and_scale();
     println!("[capture] Mouse position: ({}, {}), scale: {}", mouse_x, mouse_y, scale);
 
+    // Prefer the region origin recorded in the PNG - the mouse may have moved since
+    let recorded_position = read_capture_position_from_png(&file_path_str);
+    if let Some((x, y)) = recorded_position {
+        println!("[capture] Region origin from PNG metadata: ({}, {})", x, y);
+    }
+
     // Get image dimensions and convert to logical pixels
     // PNG contains actual pixels, but window positioning uses logical points
     let (panel_x, panel_y, img_width, img_height) = if let Some((px_width, px_height)) = get_png_dimensions(&file_path_str) {
@@ -104,25 +311,138 @@ pub async fn start_capture(app: &AppHandle) -> Result<(), String> {
         let logical_height = (px_height as f64 / scale) as u32;
         println!("[capture] Image: {}x{} px -> {}x{} logical (scale {})", px_width, px_height, logical_width, logical_height, scale);
 
-        // Mouse is at bottom-right, subtract logical dimensions to get top-left
-        let top_left_x = (mouse_x - logical_width as i32).max(0);
-        let top_left_y = (mouse_y - logical_height as i32).max(0);
-        println!("[capture] Calculated top-left: ({}, {})", top_left_x, top_left_y);
-        (top_left_x, top_left_y, logical_width, logical_height)
+        if let Some((x, y)) = recorded_position {
+            (x, y, logical_width, logical_height)
+        } else {
+            // Mouse is at bottom-right, subtract logical dimensions to get top-left
+            let top_left_x = (mouse_x - logical_width as i32).max(0);
+            let top_left_y = (mouse_y - logical_height as i32).max(0);
+            println!("[capture] Calculated top-left: ({}, {})", top_left_x, top_left_y);
+            (top_left_x, top_left_y, logical_width, logical_height)
+        }
     } else {
         println!("[capture] Could not read image dimensions, using mouse position");
-        (mouse_x, mouse_y, 400, 300)
+        let (x, y) = recorded_position.unwrap_or((mouse_x, mouse_y));
+        (x, y, 400, 300)
     };
 
+    // Convert only after reading the PNG header and metadata above
+    let format = format.unwrap_or_else(|| load_capture_format(app));
+    let file_path_str = encode_capture(&file_path, format).await?.to_string_lossy().to_string();
+
     // Open action panel at top-left of capture region, sized to match image
-    open_capture_action_panel(app, panel_x, panel_y, img_width, img_height, Some(file_path_str)).await?;
+    open_capture_action_panel(app, panel_x, panel_y, img_width, img_height, Some(file_path_str), workbook_id).await?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub async fn start_capture_command(app: AppHandle) -> Result<(), String> {
-    start_capture(&app).await
+pub async fn start_capture_command(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    format: Option<CaptureFormat>,
+) -> Result<(), String> {
+    let workbook_id = state.lock().await.active_workbook_id.clone();
+    start_capture_for_workbook(&app, workbook_id, format).await
+}
+
+const COUNTDOWN_LABEL: &str = "capture_countdown";
+const COUNTDOWN_SIZE: f64 = 140.0;
+
+/// Set while a delayed capture is counting down
+static COUNTDOWN_ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Set by `cancel_capture_countdown` to abort the running countdown
+static COUNTDOWN_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Start a capture after a countdown, emitting `capture:countdown { remaining }` each
+/// second and showing the count in a small always-on-top window
+#[tauri::command]
+pub async fn start_capture_delayed(app: AppHandle, delay_secs: u8) -> Result<(), String> {
+    if COUNTDOWN_ACTIVE.swap(true, Ordering::SeqCst) {
+        return Err("A capture countdown is already running".to_string());
+    }
+    COUNTDOWN_CANCELLED.store(false, Ordering::SeqCst);
+
+    if let Err(e) = open_countdown_window(&app, delay_secs) {
+        // The countdown still works without its window
+        eprintln!("[capture] {}", e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        for remaining in (1..=delay_secs).rev() {
+            let _ = app.emit("capture:countdown", serde_json::json!({ "remaining": remaining }));
+
+            // Sleep in small steps so cancellation is picked up quickly
+            for _ in 0..10 {
+                if COUNTDOWN_CANCELLED.load(Ordering::SeqCst) {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+            if COUNTDOWN_CANCELLED.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        // Close the countdown window first so it doesn't end up in the screenshot
+        if let Some(window) = app.get_webview_window(COUNTDOWN_LABEL) {
+            let _ = window.close();
+        }
+        COUNTDOWN_ACTIVE.store(false, Ordering::SeqCst);
+
+        if COUNTDOWN_CANCELLED.load(Ordering::SeqCst) {
+            println!("[capture] Capture countdown cancelled");
+            return;
+        }
+
+        if let Err(e) = start_capture(&app).await {
+            eprintln!("[capture] Failed to start capture: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Abort a running capture countdown
+#[tauri::command]
+pub fn cancel_capture_countdown() -> Result<(), String> {
+    if !COUNTDOWN_ACTIVE.load(Ordering::SeqCst) {
+        return Err("No capture countdown is running".to_string());
+    }
+    COUNTDOWN_CANCELLED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Show the countdown window centered on the monitor under the mouse
+fn open_countdown_window(app: &AppHandle, delay_secs: u8) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(COUNTDOWN_LABEL) {
+        let _ = window.close();
+    }
+
+    let (mouse_x, mouse_y, _) = get_mouse_position_and_scale();
+    let (pos_x, pos_y) = match monitor_bounds_at(app, mouse_x, mouse_y) {
+        Some((left, top, right, bottom)) => (
+            left + (right - left - COUNTDOWN_SIZE) / 2.0,
+            top + (bottom - top - COUNTDOWN_SIZE) / 2.0,
+        ),
+        None => (mouse_x as f64, mouse_y as f64),
+    };
+
+    let url = format!("overlay.html?capture-countdown=true&remaining={}", delay_secs);
+    let window = WebviewWindowBuilder::new(app, COUNTDOWN_LABEL, WebviewUrl::App(url.into()))
+        .title("")
+        .inner_size(COUNTDOWN_SIZE, COUNTDOWN_SIZE)
+        .position(pos_x, pos_y)
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .build()
+        .map_err(|e| format!("Failed to create countdown window: {}", e))?;
+
+    let _ = window.show();
+    Ok(())
 }
 
 #[tauri::command]
@@ -132,7 +452,12 @@ pub async fn capture_region(
     y: i32,
     width: u32,
     height: u32,
+    format: Option<CaptureFormat>,
 ) -> Result<String, String> {
+    if let Some(format) = &format {
+        format.validate()?;
+    }
+
     let temp_dir = std::env::temp_dir().join("hands-captures");
     std::fs::create_dir_all(&temp_dir).ok();
 
@@ -147,16 +472,313 @@ pub async fn capture_region(
         .output()
         .map_err(|e| format!("Failed to run screencapture: {}", e))?;
 
+    if is_permission_denied(&output) {
+        let _ = app.emit("capture:permission-denied", serde_json::json!({}));
+        return Err("Screen recording permission denied".to_string());
+    }
+
     if !output.status.success() || !file_path.exists() {
         return Err("Screen capture failed".to_string());
     }
 
+    let format = format.unwrap_or_else(|| load_capture_format(&app));
+    let file_path_str = encode_capture(&file_path, format).await?.to_string_lossy().to_string();
+
     // Open action panel with the screenshot at exact capture location
-    open_capture_action_panel(&app, x, y, width, height, Some(file_path_str.clone())).await?;
+    let workbook_id = active_workbook_id(&app).await;
+    open_capture_action_panel(&app, x, y, width, height, Some(file_path_str.clone()), workbook_id).await?;
 
     Ok(file_path_str)
 }
 
+/// Capture a region straight to the clipboard, without opening the action panel.
+/// Emits `capture:clipboard-ready` with the image as a base64 string.
+#[tauri::command]
+pub async fn capture_to_clipboard(app: AppHandle) -> Result<(), String> {
+    // Small delay to ensure all windows are in proper state
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    // -c: copy to the clipboard instead of writing a file
+    // -i: interactive mode (crosshair cursor like Cmd+Shift+4)
+    // -x: no sound
+    let output = Command::new("screencapture")
+        .args(["-c", "-i", "-x"])
+        .output()
+        .map_err(|e| format!("Failed to run screencapture: {}", e))?;
+
+    if is_permission_denied(&output) {
+        println!("[capture] Screen recording permission denied");
+        let _ = app.emit("capture:permission-denied", serde_json::json!({}));
+        return Ok(());
+    }
+
+    // Nothing is written to a file here, so only the exit status and stderr can tell
+    if is_cancelled(&output, false) {
+        println!("[capture] User cancelled clipboard capture");
+        return Ok(());
+    }
+    if !output.status.success() {
+        return Err("Screen capture failed".to_string());
+    }
+
+    let clipboard = app.state::<tauri_plugin_clipboard::Clipboard>();
+    let image = clipboard
+        .read_image_base64()
+        .map_err(|e| format!("Failed to read capture from clipboard: {}", e))?;
+
+    println!("[capture] Screenshot copied to clipboard");
+    crate::telemetry::track(&app, "capture_used").await;
+    let _ = app.emit("capture:clipboard-ready", image);
+
+    Ok(())
+}
+
+const CAPTURE_PRESETS_KEY: &str = "capture_presets";
+
+/// A saved screen region that can be re-captured without selecting it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturePreset {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Saved capture presets (`settings.json["capture_presets"]`)
+pub fn load_capture_presets(app: &AppHandle) -> Vec<CapturePreset> {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(CAPTURE_PRESETS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save_capture_presets(app: &AppHandle, presets: &[CapturePreset]) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(CAPTURE_PRESETS_KEY, serde_json::json!(presets));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    // Tray lists presets in a submenu
+    let _ = app.emit("capture:presets-changed", presets);
+    Ok(())
+}
+
+/// Save a capture preset, replacing any existing preset with the same name
+#[tauri::command]
+pub fn save_capture_preset(app: AppHandle, preset: CapturePreset) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    if preset.width == 0 || preset.height == 0 {
+        return Err("Preset region must have a non-zero size".to_string());
+    }
+
+    let mut presets = load_capture_presets(&app);
+    match presets.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+    save_capture_presets(&app, &presets)
+}
+
+#[tauri::command]
+pub fn list_capture_presets(app: AppHandle) -> Result<Vec<CapturePreset>, String> {
+    Ok(load_capture_presets(&app))
+}
+
+/// Delete a capture preset. Returns false if no preset has that name.
+#[tauri::command]
+pub fn delete_capture_preset(app: AppHandle, name: String) -> Result<bool, String> {
+    let mut presets = load_capture_presets(&app);
+    let count = presets.len();
+    presets.retain(|p| p.name != name);
+    if presets.len() == count {
+        return Ok(false);
+    }
+    save_capture_presets(&app, &presets)?;
+    Ok(true)
+}
+
+/// Capture a saved preset's region. Returns the screenshot path.
+#[tauri::command]
+pub async fn capture_preset(app: AppHandle, name: String) -> Result<String, String> {
+    let preset = load_capture_presets(&app)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Capture preset not found: {}", name))?;
+    capture_region(app, preset.x, preset.y, preset.width, preset.height, None).await
+}
+
+/// Check if Screen Recording permission is granted by taking a throwaway screenshot
+#[tauri::command]
+pub async fn check_screen_recording_permission() -> Result<bool, String> {
+    let test_path = std::env::temp_dir().join("hands_permission_test.png");
+
+    let output = Command::new("screencapture")
+        .args(["-x", &test_path.to_string_lossy()])
+        .output()
+        .map_err(|e| format!("Failed to run screencapture: {}", e))?;
+
+    let granted = output.status.success() && test_path.exists() && !is_permission_denied(&output);
+    let _ = std::fs::remove_file(&test_path);
+
+    Ok(granted)
+}
+
+const OCR_ENABLED_KEY: &str = "capture_enable_ocr";
+const OCR_LANGUAGE_KEY: &str = "capture_ocr_language";
+const DEFAULT_OCR_LANGUAGE: &str = "eng";
+
+/// Whether the action panel OCRs captures (`settings.json["capture_enable_ocr"]`), off by default
+pub fn load_ocr_enabled(app: &AppHandle) -> bool {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(OCR_ENABLED_KEY))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Tesseract language code(s) used for OCR, e.g. `eng` or `deu+fra`
+pub fn load_ocr_language(app: &AppHandle) -> String {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(OCR_LANGUAGE_KEY))
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| DEFAULT_OCR_LANGUAGE.to_string())
+}
+
+/// Shown when OCR is used without the `tesseract` binary on PATH
+const TESSERACT_MISSING: &str = "Tesseract is not installed. Install it (e.g. `brew install tesseract`) to recognize text in screenshots.";
+
+/// Whether the `tesseract` CLI can be run
+pub fn tesseract_installed() -> bool {
+    Command::new("tesseract")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Extract text from a screenshot using the `tesseract` CLI (must be installed on PATH).
+/// `language` is a tesseract language code; tesseract's default (English) is used when `None`.
+pub async fn extract_text(image_path: &str, language: Option<&str>) -> Result<String, String> {
+    let mut command = tokio::process::Command::new("tesseract");
+    command.args([image_path, "stdout"]);
+    if let Some(language) = language {
+        command.args(["-l", language]);
+    }
+
+    let output = command
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => TESSERACT_MISSING.to_string(),
+            _ => format!("Failed to run tesseract: {}", e),
+        })?;
+
+    if !output.status.success() {
+        return Err(format!("OCR failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `capture:ocr-complete` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrComplete {
+    pub panel_id: String,
+    pub text: String,
+}
+
+/// `capture:ocr-failed` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrFailed {
+    pub panel_id: String,
+    pub error: String,
+}
+
+/// Number of recent panel OCR results kept for `capture_ocr_result`
+const MAX_CACHED_OCR_RESULTS: usize = 8;
+
+/// OCR results of the most recent action panels, oldest first. The OCR event can
+/// fire before a panel's webview is listening, so panels also ask for them directly.
+static OCR_RESULTS: std::sync::Mutex<Vec<(String, Result<String, String>)>> = std::sync::Mutex::new(Vec::new());
+
+/// OCR a capture for an action panel, then emit `capture:ocr-complete`
+/// (or `capture:ocr-failed`) with the panel's ID
+async fn run_panel_ocr(app: AppHandle, panel_id: String, image_path: String) {
+    let result = extract_text(&image_path, Some(&load_ocr_language(&app))).await;
+
+    if let Ok(mut results) = OCR_RESULTS.lock() {
+        if results.len() >= MAX_CACHED_OCR_RESULTS {
+            let _ = results.remove(0);
+        }
+        results.push((panel_id.clone(), result.clone()));
+    }
+
+    match result {
+        Ok(text) => {
+            let _ = app.emit("capture:ocr-complete", OcrComplete { panel_id, text });
+        }
+        Err(error) => {
+            eprintln!("[capture] OCR failed: {}", error);
+            let _ = app.emit("capture:ocr-failed", OcrFailed { panel_id, error });
+        }
+    }
+}
+
+/// OCR result for an action panel: `None` while OCR is still running
+#[tauri::command]
+pub fn capture_ocr_result(panel_id: String) -> Result<Option<String>, String> {
+    let results = OCR_RESULTS.lock().map_err(|e| format!("Failed to read OCR results: {}", e))?;
+    results
+        .iter()
+        .find(|(id, _)| *id == panel_id)
+        .map(|(_, result)| result.clone())
+        .transpose()
+}
+
+/// Run OCR on a captured image using the saved OCR language
+#[tauri::command]
+pub async fn capture_ocr_text(app: AppHandle, image_path: String) -> Result<String, String> {
+    if !Path::new(&image_path).exists() {
+        return Err(format!("Image not found: {}", image_path));
+    }
+    extract_text(&image_path, Some(&load_ocr_language(&app))).await
+}
+
+/// Turn OCR of captures in the action panel on or off
+#[tauri::command]
+pub fn capture_set_ocr_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    if enabled && !tesseract_installed() {
+        return Err(TESSERACT_MISSING.to_string());
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(OCR_ENABLED_KEY, serde_json::json!(enabled));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
+/// Set the tesseract language code(s) used for OCR, e.g. `jpn` or `eng+deu`
+#[tauri::command]
+pub fn capture_set_ocr_language(app: AppHandle, lang: String) -> Result<(), String> {
+    let lang = lang.trim();
+    let is_valid = !lang.is_empty()
+        && lang.split('+').all(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+    if !is_valid {
+        return Err(format!("Invalid OCR language: {}", lang));
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(OCR_LANGUAGE_KEY, serde_json::json!(lang));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
 /// Cancel capture (no-op with native screencapture, user presses ESC)
 #[tauri::command]
 pub async fn cancel_capture(_app: AppHandle) -> Result<(), String> {
@@ -164,31 +786,25 @@ pub async fn cancel_capture(_app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Open the capture action panel
-pub async fn open_capture_action_panel(
-    app: &AppHandle,
-    _x: i32,
-    _y: i32,
+/// Size and position of a capture action panel
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PanelGeometry {
+    /// Image size shown in the panel, capped so huge captures don't make huge windows
     img_width: u32,
     img_height: u32,
-    screenshot_path: Option<String>,
-) -> Result<(), String> {
-    let panel_id = uuid::Uuid::new_v4().to_string();
-    let label = format!("capture_action_{}", &panel_id[..8]);
+    width: f64,
+    height: f64,
+    x: f64,
+    y: f64,
+}
 
+/// Lay out the panel for an image captured at (x, y), kept on `monitor` when known
+fn panel_geometry(x: i32, y: i32, img_width: u32, img_height: u32, monitor: Option<MonitorBounds>) -> PanelGeometry {
     // Cap image size to reasonable max (e.g., 600px) to prevent huge windows
     let max_img_dim = 600.0;
     let capped_width = (img_width as f64).min(max_img_dim) as u32;
     let capped_height = (img_height as f64).min(max_img_dim) as u32;
 
-    // Build query params with capped dimensions
-    let mut query = format!("capture-action=true&panel-id={}&img-width={}&img-height={}", panel_id, capped_width, capped_height);
-    if let Some(ref path) = screenshot_path {
-        query.push_str(&format!("&screenshot={}", urlencoding::encode(path)));
-    }
-
-    let url = format!("overlay.html?{}", query);
-
     // Padding around image for glow effect (p-5 = 20px)
     let image_padding = 20.0;
     // Action panel below image - room for message bubble + action buttons
@@ -198,22 +814,38 @@ pub async fn open_capture_action_panel(
     let widget_width = 500.0;
     let widget_height = image_padding + capped_height as f64 + action_panel_height;
 
-    // Position window in top-left corner
-    let pos_x = 0.0;
-    let pos_y = 0.0;
+    // Position window at the top-left of the captured region, kept on-screen
+    let (pos_x, pos_y) = match monitor {
+        Some(bounds) => clamp_panel_position(x as f64, y as f64, widget_width, widget_height, bounds),
+        None => (x.max(0) as f64, y.max(0) as f64),
+    };
 
-    println!("[capture] Widget: {}x{}, Position: ({}, {})",
-        widget_width, widget_height, pos_x, pos_y);
+    PanelGeometry {
+        img_width: capped_width,
+        img_height: capped_height,
+        width: widget_width,
+        height: widget_height,
+        x: pos_x,
+        y: pos_y,
+    }
+}
 
+/// Create and show the panel window. Works with every other Hands window hidden.
+fn show_action_panel_window<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    url: String,
+    geometry: &PanelGeometry,
+) -> Result<(), String> {
     let window = WebviewWindowBuilder::new(
         app,
-        &label,
+        label,
         WebviewUrl::App(url.into()),
     )
     .title("")
-    .inner_size(widget_width, widget_height)
+    .inner_size(geometry.width, geometry.height)
     .min_inner_size(400.0, 180.0)
-    .position(pos_x, pos_y)
+    .position(geometry.x, geometry.y)
     .decorations(false)
     .transparent(true)
     .always_on_top(true)
@@ -222,14 +854,125 @@ pub async fn open_capture_action_panel(
     .build()
     .map_err(|e| format!("Failed to create capture panel: {}", e))?;
 
-    let _ = window.set_focus();
+    // The shortcut can fire while the app itself is hidden (no visible windows),
+    // so unhide the app first or the panel won't appear
+    #[cfg(target_os = "macos")]
+    {
+        let _ = app.show();
+    }
 
+    let _ = window.show();
+    let _ = window.set_focus();
     Ok(())
 }
 
-/// Close a capture action panel by ID
+/// Open the capture action panel. Returns the panel ID.
+pub async fn open_capture_action_panel(
+    app: &AppHandle,
+    x: i32,
+    y: i32,
+    img_width: u32,
+    img_height: u32,
+    screenshot_path: Option<String>,
+    workbook_id: Option<String>,
+) -> Result<String, String> {
+    let panel_id = uuid::Uuid::new_v4().to_string();
+    let label = format!("capture_action_{}", &panel_id[..8]);
+    let geometry = panel_geometry(x, y, img_width, img_height, monitor_bounds_at(app, x, y));
+
+    // Build query params with capped dimensions
+    let mut query = format!(
+        "capture-action=true&panel-id={}&img-width={}&img-height={}",
+        panel_id, geometry.img_width, geometry.img_height
+    );
+    let ocr_enabled = screenshot_path.is_some() && load_ocr_enabled(app);
+    if let Some(ref path) = screenshot_path {
+        query.push_str(&format!("&screenshot={}", urlencoding::encode(path)));
+    }
+    // Tells the panel to wait for `capture:ocr-complete`
+    if ocr_enabled {
+        query.push_str("&ocr=true");
+    }
+    if let Some(ref id) = workbook_id {
+        query.push_str(&format!("&workbook-id={}", urlencoding::encode(id)));
+        if let Ok(dir) = crate::get_workbook_dir(id) {
+            query.push_str(&format!("&workbook-dir={}", urlencoding::encode(&dir.to_string_lossy())));
+        }
+    }
+
+    println!("[capture] Widget: {}x{}, Position: ({}, {})",
+        geometry.width, geometry.height, geometry.x, geometry.y);
+
+    show_action_panel_window(app, &label, format!("overlay.html?{}", query), &geometry)?;
+
+    if let Some(path) = screenshot_path.filter(|_| ocr_enabled) {
+        tauri::async_runtime::spawn(run_panel_ocr(app.clone(), panel_id.clone(), path));
+    }
+
+    Ok(panel_id)
+}
+
+/// Logical bounds of a monitor as (left, top, right, bottom)
+pub(crate) type MonitorBounds = (f64, f64, f64, f64);
+
+/// Find the logical bounds of the monitor containing (x, y), falling back to the first monitor
+pub(crate) fn monitor_bounds_at(app: &AppHandle, x: i32, y: i32) -> Option<MonitorBounds> {
+    let monitors = app.available_monitors().ok()?;
+
+    let bounds: Vec<MonitorBounds> = monitors
+        .iter()
+        .map(|m| {
+            let scale = m.scale_factor();
+            let left = m.position().x as f64 / scale;
+            let top = m.position().y as f64 / scale;
+            let right = left + m.size().width as f64 / scale;
+            let bottom = top + m.size().height as f64 / scale;
+            (left, top, right, bottom)
+        })
+        .collect();
+
+    bounds_containing(&bounds, x, y)
+}
+
+/// The monitor in `bounds` containing (x, y), falling back to the first one
+fn bounds_containing(bounds: &[MonitorBounds], x: i32, y: i32) -> Option<MonitorBounds> {
+    let (px, py) = (x as f64, y as f64);
+    bounds
+        .iter()
+        .copied()
+        .find(|(left, top, right, bottom)| px >= *left && px < *right && py >= *top && py < *bottom)
+        .or_else(|| bounds.first().copied())
+}
+
+/// Shift the panel so it does not overflow the right/bottom (or left/top) edges of the monitor
+fn clamp_panel_position(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    (left, top, right, bottom): MonitorBounds,
+) -> (f64, f64) {
+    let mut panel_x = x;
+    let mut panel_y = y;
+
+    if panel_x + width > right {
+        panel_x = right - width;
+    }
+    if panel_y + height > bottom {
+        panel_y = bottom - height;
+    }
+
+    (panel_x.max(left), panel_y.max(top))
+}
+
+/// Close a capture action panel by ID. When `workbook_id` is given, the capture
+/// was used for that workbook and `capture:panel-closed` tells it to pick up the image.
 #[tauri::command]
-pub async fn close_capture_panel(app: AppHandle, panel_id: String) -> Result<(), String> {
+pub async fn close_capture_panel(
+    app: AppHandle,
+    panel_id: String,
+    workbook_id: Option<String>,
+) -> Result<(), String> {
     // Find and close matching window
     for window in app.webview_windows().values() {
         if window.label().contains(&panel_id) {
@@ -237,6 +980,14 @@ pub async fn close_capture_panel(app: AppHandle, panel_id: String) -> Result<(),
             break;
         }
     }
+
+    if let Some(workbook_id) = workbook_id {
+        let _ = app.emit("capture:panel-closed", serde_json::json!({
+            "panel_id": panel_id,
+            "workbook_id": workbook_id,
+        }));
+    }
+
     Ok(())
 }
 
@@ -246,3 +997,124 @@ pub async fn set_ignore_cursor_events(window: tauri::WebviewWindow, ignore: bool
     window.set_ignore_cursor_events(ignore)
         .map_err(|e| format!("Failed to set ignore cursor events: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PANEL: (f64, f64) = (400.0, 300.0);
+    const PRIMARY: MonitorBounds = (0.0, 0.0, 1440.0, 900.0);
+    /// Secondary monitor to the left of and above the primary's origin
+    const SECONDARY: MonitorBounds = (-1920.0, -180.0, 0.0, 900.0);
+
+    fn clamp(x: f64, y: f64, monitor: MonitorBounds) -> (f64, f64) {
+        clamp_panel_position(x, y, PANEL.0, PANEL.1, monitor)
+    }
+
+    #[test]
+    fn panel_inside_monitor_is_unchanged() {
+        assert_eq!(clamp(100.0, 200.0, PRIMARY), (100.0, 200.0));
+    }
+
+    #[test]
+    fn panel_past_right_and_bottom_edges_is_pulled_back() {
+        assert_eq!(clamp(1300.0, 800.0, PRIMARY), (1040.0, 600.0));
+        // Entirely off-screen
+        assert_eq!(clamp(5000.0, 5000.0, PRIMARY), (1040.0, 600.0));
+    }
+
+    #[test]
+    fn panel_past_left_and_top_edges_is_pushed_in() {
+        assert_eq!(clamp(-50.0, -20.0, PRIMARY), (0.0, 0.0));
+    }
+
+    #[test]
+    fn panel_larger_than_monitor_keeps_top_left_visible() {
+        let small: MonitorBounds = (0.0, 0.0, 300.0, 200.0);
+        assert_eq!(clamp(10.0, 10.0, small), (0.0, 0.0));
+    }
+
+    #[test]
+    fn panel_on_monitor_with_negative_origin() {
+        assert_eq!(clamp(-1800.0, -100.0, SECONDARY), (-1800.0, -100.0));
+        assert_eq!(clamp(-200.0, 800.0, SECONDARY), (-400.0, 600.0));
+        assert_eq!(clamp(-2500.0, -400.0, SECONDARY), (-1920.0, -180.0));
+    }
+
+    #[test]
+    fn capture_point_selects_its_own_monitor() {
+        let monitors = [PRIMARY, SECONDARY];
+        assert_eq!(bounds_containing(&monitors, 100, 100), Some(PRIMARY));
+        assert_eq!(bounds_containing(&monitors, -100, -100), Some(SECONDARY));
+
+        // Clamped against the secondary, the panel stays off the primary
+        let (x, _) = clamp(-100.0, 100.0, bounds_containing(&monitors, -100, 100).unwrap());
+        assert_eq!(x, -PANEL.0);
+    }
+
+    #[test]
+    fn action_panel_opens_with_every_window_hidden() {
+        let app = tauri::test::mock_app();
+        WebviewWindowBuilder::new(&app, "main", WebviewUrl::App("index.html".into()))
+            .build()
+            .unwrap();
+        // Simulate the app hidden in the background when the shortcut fires
+        for window in app.webview_windows().values() {
+            window.hide().unwrap();
+        }
+
+        let geometry = panel_geometry(1300, 800, 800, 600, Some(PRIMARY));
+        show_action_panel_window(app.handle(), "capture_action_test", "overlay.html".to_string(), &geometry)
+            .unwrap();
+        assert!(app.get_webview_window("capture_action_test").is_some());
+    }
+
+    #[test]
+    fn panel_geometry_caps_image_and_stays_on_monitor() {
+        let geometry = panel_geometry(1300, 800, 2000, 1500, Some(PRIMARY));
+        assert_eq!((geometry.img_width, geometry.img_height), (600, 600));
+        assert_eq!((geometry.width, geometry.height), (500.0, 860.0));
+        assert_eq!((geometry.x, geometry.y), (940.0, 40.0));
+
+        // Without monitor info the panel only avoids negative coordinates
+        let geometry = panel_geometry(-50, 20, 100, 100, None);
+        assert_eq!((geometry.x, geometry.y), (0.0, 20.0));
+    }
+
+    #[test]
+    fn point_outside_every_monitor_falls_back_to_the_first() {
+        assert_eq!(bounds_containing(&[PRIMARY, SECONDARY], 9000, 9000), Some(PRIMARY));
+        assert_eq!(bounds_containing(&[], 0, 0), None);
+    }
+
+    #[cfg(unix)]
+    fn screencapture_output(exit_code: i32, stderr: &str) -> std::process::Output {
+        use std::os::unix::process::ExitStatusExt;
+        std::process::Output {
+            status: std::process::ExitStatus::from_raw(exit_code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn esc_cancel_is_not_a_permission_error() {
+        let cancelled = screencapture_output(1, "");
+        assert!(!is_permission_denied(&cancelled));
+        assert!(is_cancelled(&cancelled, false));
+
+        let denied = screencapture_output(1, "No screen recording permission.\n");
+        assert!(is_permission_denied(&denied));
+        assert!(!is_cancelled(&denied, false));
+
+        assert!(!is_cancelled(&screencapture_output(0, ""), true));
+    }
+
+    #[test]
+    fn lossy_webp_quality_is_rejected() {
+        assert!(CaptureFormat::Webp { quality: 100 }.validate().is_ok());
+        assert!(CaptureFormat::Webp { quality: 80 }.validate().is_err());
+        assert!(CaptureFormat::Jpeg { quality: 80 }.validate().is_ok());
+    }
+}
@@ -5,22 +5,233 @@
 //! Expanded: Full chat interface
 //!
 //! The drawer never hides - it just collapses to the icon.
+//! Its position, size and collapsed/expanded state are restored across restarts.
 
-use tauri::{AppHandle, Emitter, Listener, Manager, WebviewUrl, WebviewWindowBuilder, LogicalPosition, LogicalSize};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Listener, Manager, Monitor, WebviewUrl, WebviewWindowBuilder, LogicalSize};
+use tauri_plugin_store::StoreExt;
 
-const FLOATING_CHAT_LABEL: &str = "floating_chat";
-const COLLAPSED_WIDTH: f64 = 64.0;  // Just the icon
-const EXPANDED_WIDTH: f64 = 400.0;  // Full chat width
+pub const FLOATING_CHAT_LABEL: &str = "floating_chat";
+const DIMENSIONS_KEY: &str = "floating_chat_dimensions";
+const WINDOW_STATE_STORE: &str = "window-state.json";
+const GEOMETRY_KEY: &str = "floating_chat_geometry";
+const EXPANDED_KEY: &str = "floating_chat_expanded";
+const MONITOR_KEY: &str = "floating_chat_monitor";
+const ANIMATION_DURATION_KEY: &str = "floating_chat_animation_ms";
 const VERTICAL_MARGIN: f64 = 48.0;  // Equal margin from top and bottom of screen
 
+/// Intermediate sizes per expand/collapse animation
+const ANIMATION_STEPS: u32 = 12;
+/// Default animation length - one step per frame at ~60 fps
+const DEFAULT_ANIMATION_MS: u64 = 16 * ANIMATION_STEPS as u64;
+/// Longest allowed animation
+const MAX_ANIMATION_MS: u64 = 2000;
+
+/// Bumped by each expand/collapse so an in-flight animation stops when superseded
+static ANIMATION_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Bumped by each drawer move/resize; only the last one in a burst writes the store
+static GEOMETRY_SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Quiet period after the last move/resize before the geometry is written to disk
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Workbook directory the floating chat is currently showing
+static WORKBOOK_DIR: Mutex<Option<String>> = Mutex::new(None);
+
+/// User-configurable drawer size (`settings.json["floating_chat_dimensions"]`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FloatingChatDimensions {
+    pub collapsed_width: f64,
+    pub expanded_width: f64,
+    pub min_height: f64,
+}
+
+impl Default for FloatingChatDimensions {
+    fn default() -> Self {
+        Self {
+            collapsed_width: 64.0,  // Just the icon
+            expanded_width: 400.0,  // Full chat width
+            min_height: 200.0,
+        }
+    }
+}
+
+fn load_dimensions(app: &AppHandle) -> FloatingChatDimensions {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(DIMENSIONS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Current drawer dimensions (loaded from settings on first use)
+fn dimensions(app: &AppHandle) -> FloatingChatDimensions {
+    if let Some(dims) = app.try_state::<Mutex<FloatingChatDimensions>>() {
+        return *dims.lock().unwrap();
+    }
+    let dims = load_dimensions(app);
+    app.manage(Mutex::new(dims));
+    dims
+}
+
+/// Last drawer position and size in logical pixels (`window-state.json["floating_chat_geometry"]`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FloatingChatGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+fn load_geometry(app: &AppHandle) -> Option<FloatingChatGeometry> {
+    app.store(WINDOW_STATE_STORE)
+        .ok()?
+        .get(GEOMETRY_KEY)
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+fn load_expanded(app: &AppHandle) -> bool {
+    app.store(WINDOW_STATE_STORE)
+        .ok()
+        .and_then(|store| store.get(EXPANDED_KEY))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn save_expanded(app: &AppHandle, expanded: bool) {
+    if let Ok(store) = app.store(WINDOW_STATE_STORE) {
+        store.set(EXPANDED_KEY, serde_json::json!(expanded));
+        let _ = store.save();
+    }
+}
+
+/// Record the drawer's position and size (called on `Moved`/`Resized` window events).
+/// The store is updated in memory right away and saved once the drag settles.
+pub fn save_geometry(window: &tauri::Window) {
+    let Ok(scale) = window.scale_factor() else { return };
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else { return };
+    let position = position.to_logical::<f64>(scale);
+    let size = size.to_logical::<f64>(scale);
+
+    let geometry = FloatingChatGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+
+    if let Ok(store) = window.app_handle().store(WINDOW_STATE_STORE) {
+        store.set(GEOMETRY_KEY, serde_json::json!(geometry));
+        // Remember which display the drawer is on so the next open targets it
+        if let Some(name) = window.current_monitor().ok().flatten().and_then(|m| m.name().cloned()) {
+            store.set(MONITOR_KEY, serde_json::json!(name));
+        }
+
+        let generation = GEOMETRY_SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(GEOMETRY_SAVE_DELAY).await;
+            if GEOMETRY_SAVE_GENERATION.load(Ordering::SeqCst) == generation {
+                let _ = store.save();
+            }
+        });
+    }
+}
+
+/// Monitor under the mouse cursor. NSScreen must be queried on the main thread.
+#[cfg(target_os = "macos")]
+async fn monitor_under_cursor(app: &AppHandle, monitors: &[Monitor]) -> Option<Monitor> {
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::{NSEvent, NSScreen};
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.run_on_main_thread(move || {
+        // mouseLocation has a bottom-left origin; flip Y against the main screen
+        let point = NSEvent::mouseLocation();
+        let position = MainThreadMarker::new()
+            .and_then(NSScreen::mainScreen)
+            .map(|screen| (point.x, screen.frame().size.height - point.y));
+        let _ = tx.send(position);
+    }).ok()?;
+    let (x, y) = rx.await.ok()??;
+
+    monitors.iter().find(|m| {
+        let scale = m.scale_factor();
+        let left = m.position().x as f64 / scale;
+        let top = m.position().y as f64 / scale;
+        let right = left + m.size().width as f64 / scale;
+        let bottom = top + m.size().height as f64 / scale;
+        x >= left && x < right && y >= top && y < bottom
+    }).cloned()
+}
+
+/// Without a global cursor API, use the monitor of the focused (or any visible) Hands window
+#[cfg(not(target_os = "macos"))]
+async fn monitor_under_cursor(app: &AppHandle, _monitors: &[Monitor]) -> Option<Monitor> {
+    let windows = app.webview_windows();
+    let window = windows.values()
+        .find(|w| w.is_focused().unwrap_or(false))
+        .or_else(|| windows.values().find(|w| w.is_visible().unwrap_or(false)))?;
+    window.current_monitor().ok().flatten()
+}
+
+/// Pick the monitor to open the drawer on: the one it was last on, else the one
+/// under the cursor, else the highest-DPI one. The flag is true for the saved monitor.
+async fn target_monitor(app: &AppHandle) -> Result<(Monitor, bool), String> {
+    let monitors = app.available_monitors().map_err(|e| format!("Failed to get monitors: {}", e))?;
+
+    let saved_name = app.store(WINDOW_STATE_STORE)
+        .ok()
+        .and_then(|store| store.get(MONITOR_KEY))
+        .and_then(|v| v.as_str().map(String::from));
+    if let Some(name) = saved_name {
+        if let Some(monitor) = monitors.iter().find(|m| m.name() == Some(&name)) {
+            return Ok((monitor.clone(), true));
+        }
+    }
+
+    if let Some(monitor) = monitor_under_cursor(app, &monitors).await {
+        return Ok((monitor, false));
+    }
+
+    monitors
+        .into_iter()
+        .max_by(|a, b| a.scale_factor().total_cmp(&b.scale_factor()))
+        .map(|monitor| (monitor, false))
+        .ok_or_else(|| "No monitor found".to_string())
+}
+
+/// Keep a restored drawer on the monitor it was last on (or the first monitor
+/// if that one is gone), shrinking it if the monitor is now smaller
+fn clamp_geometry(app: &AppHandle, geometry: FloatingChatGeometry, min_height: f64) -> FloatingChatGeometry {
+    let Some((left, top, right, bottom)) =
+        crate::capture::monitor_bounds_at(app, geometry.x as i32, geometry.y as i32)
+    else {
+        return geometry;
+    };
+
+    let width = geometry.width.min(right - left);
+    let height = geometry.height.min(bottom - top).max(min_height);
+    FloatingChatGeometry {
+        x: geometry.x.clamp(left, (right - width).max(left)),
+        y: geometry.y.clamp(top, (bottom - height).max(top)),
+        width,
+        height,
+    }
+}
+
 /// Open or focus the floating chat window (anchored to left edge)
 #[tauri::command]
 pub async fn open_floating_chat(
     app: AppHandle,
     workbook_dir: String,
 ) -> Result<String, String> {
-    // If window already exists, show and focus it
+    // If window already exists, switch its context if needed, then show and focus it
     if let Some(window) = app.get_webview_window(FLOATING_CHAT_LABEL) {
+        update_floating_chat_context(app.clone(), workbook_dir).await?;
         window
             .show()
             .map_err(|e| format!("Failed to show window: {}", e))?;
@@ -30,6 +241,8 @@ pub async fn open_floating_chat(
         return Ok(FLOATING_CHAT_LABEL.to_string());
     }
 
+    *WORKBOOK_DIR.lock().unwrap() = Some(workbook_dir.clone());
+
     // Build query params
     let query = format!(
         "floating-chat=true&workbook-dir={}",
@@ -38,28 +251,43 @@ pub async fn open_floating_chat(
 
     let url = format!("overlay.html?{}", query);
 
-    // Get screen dimensions to position on left edge
-    // Use the primary monitor's position and size
-    let monitors = app.available_monitors().map_err(|e| format!("Failed to get monitors: {}", e))?;
-    let primary = monitors.into_iter().next().ok_or("No monitor found")?;
-    let scale = primary.scale_factor();
+    // Pick up any dimension changes made in settings since the last launch
+    let dims = load_dimensions(&app);
+    if let Some(state) = app.try_state::<Mutex<FloatingChatDimensions>>() {
+        *state.lock().unwrap() = dims;
+    } else {
+        app.manage(Mutex::new(dims));
+    }
+
+    let (monitor, is_saved_monitor) = target_monitor(&app).await?;
+    let scale = monitor.scale_factor();
 
     // Convert physical to logical for consistent positioning
-    let screen_height = primary.size().height as f64 / scale;
+    let screen_left = monitor.position().x as f64 / scale;
+    let screen_top = monitor.position().y as f64 / scale;
+    let screen_height = monitor.size().height as f64 / scale;
 
-    // Start COLLAPSED on the left edge with equal top/bottom margins
-    let x = 0.0;
-    let y = VERTICAL_MARGIN;
-    let height = screen_height - (2.0 * VERTICAL_MARGIN);
+    // Restore the last position and height if that display is still attached,
+    // otherwise start on the left edge with equal top/bottom margins.
+    // Width follows the saved collapsed/expanded state.
+    let expanded = load_expanded(&app);
+    let width = if expanded { dims.expanded_width } else { dims.collapsed_width };
+    let (x, y, height) = match load_geometry(&app) {
+        Some(geometry) if is_saved_monitor => {
+            let geometry = clamp_geometry(&app, FloatingChatGeometry { width, ..geometry }, dims.min_height);
+            (geometry.x, geometry.y, geometry.height)
+        }
+        _ => (screen_left, screen_top + VERTICAL_MARGIN, screen_height - (2.0 * VERTICAL_MARGIN)),
+    };
 
-    println!("[floating_chat] Creating window: x={}, y={}, width={}, height={}, margin={}, scale={}",
-             x, y, COLLAPSED_WIDTH, height, VERTICAL_MARGIN, scale);
+    println!("[floating_chat] Creating window: x={}, y={}, width={}, height={}, expanded={}, scale={}",
+             x, y, width, height, expanded, scale);
 
     let window = WebviewWindowBuilder::new(&app, FLOATING_CHAT_LABEL, WebviewUrl::App(url.into()))
         .title("Hands")
         .position(x, y)
-        .inner_size(COLLAPSED_WIDTH, height)
-        .min_inner_size(COLLAPSED_WIDTH, 200.0)
+        .inner_size(width, height)
+        .min_inner_size(dims.collapsed_width, dims.min_height)
         .decorations(false)
         .transparent(true)
         .always_on_top(true)
@@ -73,8 +301,12 @@ pub async fn open_floating_chat(
     // Listen for ready signal from frontend to show window (avoids black flash)
     // Using once() instead of listen() since we only need to show once and it auto-unregisters
     let window_clone = window.clone();
+    let app_clone = app.clone();
     app.once("floating-chat-ready", move |_| {
         let _ = window_clone.show();
+        if expanded {
+            let _ = app_clone.emit("floating-chat-expanded", ());
+        }
     });
 
     // Open devtools in debug mode
@@ -86,22 +318,63 @@ pub async fn open_floating_chat(
     Ok(FLOATING_CHAT_LABEL.to_string())
 }
 
-/// Expand the drawer - just widen from left edge, keep same position/height
+fn load_animation_duration(app: &AppHandle) -> Duration {
+    let ms = app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(ANIMATION_DURATION_KEY))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_ANIMATION_MS);
+    Duration::from_millis(ms)
+}
+
+/// Resize the drawer to `width`, keeping its current position and height.
+/// The width is interpolated over `ANIMATION_STEPS` frames unless animation is disabled.
+async fn set_drawer_width(window: &tauri::WebviewWindow, width: f64) -> Result<(), String> {
+    let generation = ANIMATION_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let duration = load_animation_duration(window.app_handle());
+
+    let scale = window.scale_factor().map_err(|e| format!("{}", e))?;
+    let size = window.inner_size().map_err(|e| format!("{}", e))?.to_logical::<f64>(scale);
+
+    if !duration.is_zero() {
+        let frame = duration / ANIMATION_STEPS;
+        for step in 1..ANIMATION_STEPS {
+            let t = step as f64 / ANIMATION_STEPS as f64;
+            let frame_width = size.width + (width - size.width) * t;
+            window.set_size(LogicalSize::new(frame_width, size.height))
+                .map_err(|e| format!("{}", e))?;
+            tokio::time::sleep(frame).await;
+
+            // A newer expand/collapse took over
+            if ANIMATION_GENERATION.load(Ordering::SeqCst) != generation {
+                return Ok(());
+            }
+        }
+    }
+
+    window.set_size(LogicalSize::new(width, size.height))
+        .map_err(|e| format!("{}", e))
+}
+
+/// Set how long expand/collapse takes (0 disables the animation)
+#[tauri::command]
+pub async fn set_floating_chat_animation_duration(app: AppHandle, ms: u64) -> Result<(), String> {
+    if ms > MAX_ANIMATION_MS {
+        return Err(format!("Animation duration must be at most {}ms", MAX_ANIMATION_MS));
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(ANIMATION_DURATION_KEY, serde_json::json!(ms));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Expand the drawer - just widen it, keep same position/height
 #[tauri::command]
 pub async fn expand_floating_chat(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window(FLOATING_CHAT_LABEL) {
-        // Get screen dimensions to calculate proper height with margins
-        let monitors = app.available_monitors().map_err(|e| format!("{}", e))?;
-        let primary = monitors.into_iter().next().ok_or("No monitor")?;
-        let scale = primary.scale_factor();
-        let screen_height = primary.size().height as f64 / scale;
-        let height = screen_height - (2.0 * VERTICAL_MARGIN);
-
-        // Expand width from left edge (x stays at 0, y at margin)
-        window.set_position(LogicalPosition::new(0.0, VERTICAL_MARGIN))
-            .map_err(|e| format!("{}", e))?;
-        window.set_size(LogicalSize::new(EXPANDED_WIDTH, height))
-            .map_err(|e| format!("{}", e))?;
+        set_drawer_width(&window, dimensions(&app).expanded_width).await?;
+        save_expanded(&app, true);
 
         // Don't steal focus - user is just hovering to expand
         let _ = app.emit("floating-chat-expanded", ());
@@ -109,28 +382,50 @@ pub async fn expand_floating_chat(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Collapse the drawer - just narrow to left edge, keep same position/height
+/// Collapse the drawer - just narrow it, keep same position/height
 #[tauri::command]
 pub async fn collapse_floating_chat(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window(FLOATING_CHAT_LABEL) {
-        // Get screen dimensions to calculate proper height with margins
-        let monitors = app.available_monitors().map_err(|e| format!("{}", e))?;
-        let primary = monitors.into_iter().next().ok_or("No monitor")?;
-        let scale = primary.scale_factor();
-        let screen_height = primary.size().height as f64 / scale;
-        let height = screen_height - (2.0 * VERTICAL_MARGIN);
-
-        // Collapse width to left edge (x stays at 0, y at margin)
-        window.set_position(LogicalPosition::new(0.0, VERTICAL_MARGIN))
-            .map_err(|e| format!("{}", e))?;
-        window.set_size(LogicalSize::new(COLLAPSED_WIDTH, height))
-            .map_err(|e| format!("{}", e))?;
+        set_drawer_width(&window, dimensions(&app).collapsed_width).await?;
+        save_expanded(&app, false);
 
         let _ = app.emit("floating-chat-collapsed", ());
     }
     Ok(())
 }
 
+/// Save new drawer dimensions and apply them to the open drawer, keeping its
+/// current collapsed/expanded state
+#[tauri::command]
+pub async fn update_floating_chat_dimensions(
+    app: AppHandle,
+    dims: FloatingChatDimensions,
+) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(DIMENSIONS_KEY, serde_json::json!(dims));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    let previous = dimensions(&app);
+    if let Some(state) = app.try_state::<Mutex<FloatingChatDimensions>>() {
+        *state.lock().unwrap() = dims;
+    }
+
+    if let Some(window) = app.get_webview_window(FLOATING_CHAT_LABEL) {
+        let scale = window.scale_factor().map_err(|e| format!("{}", e))?;
+        let size = window.inner_size().map_err(|e| format!("{}", e))?.to_logical::<f64>(scale);
+        let expanded = size.width > previous.collapsed_width;
+        let width = if expanded { dims.expanded_width } else { dims.collapsed_width };
+
+        window.set_min_size(Some(LogicalSize::new(dims.collapsed_width, dims.min_height)))
+            .map_err(|e| format!("{}", e))?;
+        window.set_size(LogicalSize::new(width, size.height))
+            .map_err(|e| format!("{}", e))?;
+    }
+
+    Ok(())
+}
+
 /// Hide the floating chat window (doesn't destroy it)
 #[tauri::command]
 pub async fn hide_floating_chat(app: AppHandle) -> Result<(), String> {
@@ -185,6 +480,35 @@ pub async fn toggle_floating_chat(app: AppHandle) -> Result<bool, String> {
     }
 }
 
+/// Point an open floating chat at another workbook without recreating the window
+/// (which would flash). No-op if the window doesn't exist or already shows it.
+#[tauri::command]
+pub async fn update_floating_chat_context(app: AppHandle, workbook_dir: String) -> Result<(), String> {
+    let Some(window) = app.get_webview_window(FLOATING_CHAT_LABEL) else {
+        return Ok(());
+    };
+
+    {
+        let mut current = WORKBOOK_DIR.lock().unwrap();
+        if current.as_deref() == Some(workbook_dir.as_str()) {
+            return Ok(());
+        }
+        *current = Some(workbook_dir.clone());
+    }
+
+    println!("[floating_chat] Switching context to {}", workbook_dir);
+    window
+        .emit("floating-chat-context-changed", serde_json::json!({ "workbook_dir": workbook_dir }))
+        .map_err(|e| format!("Failed to emit context change: {}", e))
+}
+
+/// Workbook directory the floating chat is showing (None if it isn't open)
+#[tauri::command]
+pub fn get_floating_chat_workbook(app: AppHandle) -> Option<String> {
+    app.get_webview_window(FLOATING_CHAT_LABEL)?;
+    WORKBOOK_DIR.lock().unwrap().clone()
+}
+
 /// Check if any workbook windows are open (visible)
 #[tauri::command]
 pub fn has_open_workbook_windows(app: AppHandle) -> bool {
@@ -195,20 +519,36 @@ pub fn has_open_workbook_windows(app: AppHandle) -> bool {
         })
 }
 
-/// Open floating chat and start a new thread with the given prompt
+/// Open floating chat and send the given prompt.
+///
+/// * `new_thread` - start a fresh AI session before sending (default: true)
+/// * `session_id` - send to this existing session instead (takes precedence over `new_thread`)
 #[tauri::command]
 pub async fn open_floating_chat_with_prompt(
     app: AppHandle,
     workbook_dir: String,
     prompt: String,
+    new_thread: Option<bool>,
+    session_id: Option<String>,
 ) -> Result<String, String> {
+    let new_thread = new_thread.unwrap_or(true);
+
     // First open/focus the floating chat
     let label = open_floating_chat(app.clone(), workbook_dir).await?;
 
     // Wait a moment for the window to be ready, then emit the prompt
     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
-    // Emit event with the prompt - FloatingChat will pick this up and start a new thread
+    // Tell FloatingChat which session the prompt belongs to before injecting it
+    if let Some(session_id) = session_id {
+        app.emit("floating-chat-select-session", serde_json::json!({ "session_id": session_id }))
+            .map_err(|e| format!("Failed to emit session selection: {}", e))?;
+    } else if new_thread {
+        app.emit("floating-chat-new-thread", serde_json::json!({}))
+            .map_err(|e| format!("Failed to emit new thread: {}", e))?;
+    }
+
+    // Emit event with the prompt - FloatingChat will pick this up
     app.emit("floating-chat-prompt", &prompt)
         .map_err(|e| format!("Failed to emit prompt: {}", e))?;
 
@@ -2,31 +2,213 @@
 //!
 //! Registers system-wide shortcuts:
 //! - Cmd+Shift+H for screen capture
+//! - Cmd+Shift+Ctrl+H for screen capture straight to the clipboard
+//! - Cmd+Shift+M (hold) for push-to-talk STT
 //!
-//! Note: Option key handling (STT, show/hide) is done via rdev in keyboard.rs
+//! Workbooks can also register their own shortcuts, which emit
+//! `hotkey:triggered` and are removed when the workbook window closes.
+//!
+//! Note: Option key handling (STT, show/hide) is done via rdev in keyboard.rs.
+//! Cmd+Shift+M is an alternative for users whose Option key is taken by dead keys.
+//!
+//! Shortcuts fire even when all Hands windows are hidden - the capture flow
+//! creates its own panel window, so it does not depend on a visible window.
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutEvent, ShortcutState};
+
+/// A shortcut that could not be registered, usually because another app owns it.
+///
+/// Owner detection is best-effort: other apps' registrations aren't visible, so
+/// `conflicting_app` is only filled in on macOS, and only for the handful of
+/// system shortcuts in `SYMBOLIC_HOTKEYS`. It is always `None` elsewhere.
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyConflictError {
+    /// Human-readable shortcut, e.g. `Cmd+Shift+H`
+    pub shortcut: String,
+    /// Best-effort guess at the owner; `None` does not mean the shortcut is free
+    pub conflicting_app: Option<String>,
+    pub message: String,
+}
 
-use tauri::AppHandle;
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+/// Registration outcome for one of the app's global shortcuts
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredShortcut {
+    /// Action id, as accepted by `simulate_shortcut`
+    pub id: String,
+    pub shortcut: String,
+    pub registered: bool,
+    pub error: Option<HotkeyConflictError>,
+}
+
+/// Outcome of the last `register_global_shortcuts` call
+static REGISTERED: Mutex<Vec<RegisteredShortcut>> = Mutex::new(Vec::new());
+
+/// Register a shortcut, recording the outcome and emitting `hotkey:conflict` on failure
+fn register<F>(app: &AppHandle, id: &str, label: &str, shortcut: Shortcut, handler: F) -> Option<HotkeyConflictError>
+where
+    F: Fn(&AppHandle, &Shortcut, ShortcutEvent) + Send + Sync + 'static,
+{
+    let error = match app.global_shortcut().on_shortcut(shortcut, handler) {
+        Ok(()) => {
+            println!("[hotkeys] Registered {} for {}", label, id);
+            None
+        }
+        Err(e) => {
+            let conflict = HotkeyConflictError {
+                shortcut: label.to_string(),
+                conflicting_app: conflicting_app(&shortcut),
+                message: e.to_string(),
+            };
+            eprintln!("[hotkeys] Failed to register {} for {}: {:?}", label, id, conflict);
+            let _ = app.emit("hotkey:conflict", &conflict);
+            Some(conflict)
+        }
+    };
 
-/// Register all global shortcuts for the app
+    REGISTERED.lock().unwrap().push(RegisteredShortcut {
+        id: id.to_string(),
+        shortcut: label.to_string(),
+        registered: error.is_none(),
+        error: error.clone(),
+    });
+    error
+}
+
+/// macOS symbolic hotkeys that commonly clash with app shortcuts: (id, modifiers, key, owner)
+#[cfg(target_os = "macos")]
+const SYMBOLIC_HOTKEYS: [(i32, Modifiers, Code, &str); 5] = [
+    (28, Modifiers::SUPER.union(Modifiers::SHIFT), Code::Digit3, "macOS Screenshot"),
+    (30, Modifiers::SUPER.union(Modifiers::SHIFT), Code::Digit4, "macOS Screenshot (selection)"),
+    (184, Modifiers::SUPER.union(Modifiers::SHIFT), Code::Digit5, "macOS Screenshot and Recording"),
+    (64, Modifiers::SUPER, Code::Space, "Spotlight"),
+    (60, Modifiers::CONTROL, Code::Space, "macOS Input Sources"),
+];
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    /// Private SkyLight/CoreGraphics SPI: whether a system symbolic hotkey is enabled
+    fn CGSGetSymbolicHotKeyEnabled(hotkey: i32) -> bool;
+}
+
+/// Identify a system shortcut that owns `shortcut`. Other apps' registrations
+/// aren't visible, so this only recognizes enabled macOS symbolic hotkeys.
+#[cfg(target_os = "macos")]
+fn conflicting_app(shortcut: &Shortcut) -> Option<String> {
+    SYMBOLIC_HOTKEYS
+        .iter()
+        .find(|(id, mods, key, _)| {
+            shortcut.mods == *mods
+                && shortcut.key == *key
+                && unsafe { CGSGetSymbolicHotKeyEnabled(*id) }
+        })
+        .map(|(_, _, _, owner)| owner.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn conflicting_app(_shortcut: &Shortcut) -> Option<String> {
+    None
+}
+
+/// Register all global shortcuts for the app. Every shortcut is attempted;
+/// failures are reported via `list_registered_shortcuts` and `hotkey:conflict`.
 pub fn register_global_shortcuts(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    REGISTERED.lock().unwrap().clear();
+    let mut conflicts = Vec::new();
+
     // Cmd+Shift+H for screen capture
     // Note: Cmd+H alone is reserved by macOS for "Hide Window"
     let capture_shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyH);
 
     let app_handle = app.clone();
-    app.global_shortcut().on_shortcut(capture_shortcut, move |_app, _shortcut, event| {
+    conflicts.extend(register(app, "capture", "Cmd+Shift+H", capture_shortcut, move |_app, _shortcut, event| {
         if event.state == ShortcutState::Pressed {
             println!("[hotkey] Capture shortcut triggered");
             trigger_capture(&app_handle);
         }
-    })?;
+    }));
+
+    // Cmd+Shift+Ctrl+H for capture to clipboard (skips the action panel)
+    let clipboard_shortcut = Shortcut::new(
+        Some(Modifiers::SUPER | Modifiers::SHIFT | Modifiers::CONTROL),
+        Code::KeyH,
+    );
+
+    let app_handle = app.clone();
+    conflicts.extend(register(app, "capture_clipboard", "Cmd+Shift+Ctrl+H", clipboard_shortcut, move |_app, _shortcut, event| {
+        if event.state == ShortcutState::Pressed {
+            println!("[hotkey] Clipboard capture shortcut triggered");
+            trigger_clipboard_capture(&app_handle);
+        }
+    }));
+
+    // Cmd+Shift+M: hold to record, release to transcribe
+    let stt_shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyM);
+
+    let stt_tx = spawn_stt_worker(app);
+    conflicts.extend(register(app, "stt", "Cmd+Shift+M", stt_shortcut, move |_app, _shortcut, event| {
+        match event.state {
+            ShortcutState::Pressed => println!("[hotkey] STT shortcut pressed"),
+            ShortcutState::Released => println!("[hotkey] STT shortcut released"),
+        }
+        let _ = stt_tx.send(event.state);
+    }));
+
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        let shortcuts: Vec<&str> = conflicts.iter().map(|c| c.shortcut.as_str()).collect();
+        Err(format!("Shortcuts already in use: {}", shortcuts.join(", ")).into())
+    }
+}
 
-    println!("[hotkeys] Registered Cmd+Shift+H for screen capture");
+/// Which global shortcuts were registered, and why any failed.
+/// Failures are reported without an owner (`conflicting_app: None`) since the
+/// owner generally can't be detected; `hotkey:conflict` carries the best-effort guess.
+#[tauri::command]
+pub fn list_registered_shortcuts() -> Result<Vec<RegisteredShortcut>, String> {
+    let registered = REGISTERED
+        .lock()
+        .map_err(|e| format!("Failed to read shortcut registrations: {}", e))?;
+
+    Ok(registered
+        .iter()
+        .map(|entry| RegisteredShortcut {
+            error: entry.error.as_ref().map(|error| HotkeyConflictError {
+                conflicting_app: None,
+                ..error.clone()
+            }),
+            ..entry.clone()
+        })
+        .collect())
+}
 
+/// Run the action bound to a shortcut id, as if the shortcut had been pressed.
+/// Only available in debug builds (testing and scripting).
+#[tauri::command]
+pub fn simulate_shortcut(app: AppHandle, shortcut_id: String) -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Err("simulate_shortcut is only available in debug builds".to_string());
+    }
+
+    let action = shortcut_action(&shortcut_id).ok_or_else(|| format!("Unknown shortcut: {}", shortcut_id))?;
+    println!("[hotkey] Simulating {} shortcut", shortcut_id);
+    action(&app);
     Ok(())
 }
 
+/// Action bound to a press-only shortcut id (`stt` is hold-to-talk, so it has none)
+fn shortcut_action(shortcut_id: &str) -> Option<fn(&AppHandle)> {
+    match shortcut_id {
+        "capture" => Some(trigger_capture),
+        "capture_clipboard" => Some(trigger_clipboard_capture),
+        _ => None,
+    }
+}
+
 /// Trigger the screen capture flow
 fn trigger_capture(app: &AppHandle) {
     let app = app.clone();
@@ -38,9 +220,159 @@ fn trigger_capture(app: &AppHandle) {
     });
 }
 
+/// Trigger a screen capture straight to the clipboard
+fn trigger_clipboard_capture(app: &AppHandle) {
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::capture::capture_to_clipboard(app).await {
+            eprintln!("[hotkey] Failed to capture to clipboard: {}", e);
+        }
+    });
+}
+
+/// Handle push-to-talk presses and releases one at a time, in order. A quick
+/// tap's release then always waits for its press to finish starting the
+/// recording, instead of racing it and leaving the microphone on.
+fn spawn_stt_worker(app: &AppHandle) -> tokio::sync::mpsc::UnboundedSender<ShortcutState> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(key_state) = rx.recv().await {
+            match key_state {
+                ShortcutState::Pressed => start_stt(&app).await,
+                ShortcutState::Released => finish_stt(&app).await,
+            }
+        }
+    });
+    tx
+}
+
+/// Start STT recording (no-op if already recording)
+async fn start_stt(app: &AppHandle) {
+    let state = app.state::<crate::stt::SttManagedState>();
+    if let Err(e) = crate::stt::stt_start_recording(app.clone(), state).await {
+        eprintln!("[hotkey] Failed to start recording: {}", e);
+    }
+}
+
+/// Stop STT recording and emit the transcript as `stt:transcription`
+async fn finish_stt(app: &AppHandle) {
+    let state = app.state::<crate::stt::SttManagedState>();
+    match crate::stt::stt_stop_recording(state).await {
+        Ok(text) if !text.is_empty() => {
+            let _ = app.emit("stt:transcription", &text);
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("[hotkey] Failed to transcribe recording: {}", e),
+    }
+}
+
+/// Payload of `hotkey:triggered`
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkbookHotkeyTriggered {
+    pub workbook_id: String,
+    pub action: String,
+}
+
+/// Register a global shortcut for a workbook action (e.g. `CmdOrCtrl+Shift+R`).
+/// Pressing it emits `hotkey:triggered`. Re-registering an action replaces its shortcut;
+/// a shortcut already bound to another action or workbook is rejected.
+#[tauri::command]
+pub async fn register_workbook_hotkey(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<crate::AppState>>>,
+    workbook_id: String,
+    action: String,
+    shortcut: String,
+) -> Result<(), String> {
+    // Parse first so invalid strings fail early
+    let parsed: Shortcut = shortcut
+        .parse()
+        .map_err(|e| format!("Invalid shortcut {}: {}", shortcut, e))?;
+
+    let mut state_guard = state.lock().await;
+    for (bound_workbook_id, bindings) in &state_guard.workbook_hotkeys {
+        if let Some((bound_action, _)) = bindings.iter().find(|(_, bound)| *bound == parsed) {
+            if *bound_workbook_id == workbook_id && *bound_action == action {
+                return Ok(());
+            }
+            return Err(format!(
+                "{} is already bound to {} in workbook {}",
+                shortcut, bound_action, bound_workbook_id
+            ));
+        }
+    }
+
+    // Register the new shortcut before dropping the old one, so a failure
+    // leaves the existing binding working
+    let payload = WorkbookHotkeyTriggered {
+        workbook_id: workbook_id.clone(),
+        action: action.clone(),
+    };
+    app.global_shortcut()
+        .on_shortcut(parsed, move |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                let _ = app.emit("hotkey:triggered", &payload);
+            }
+        })
+        .map_err(|e| format!("Failed to register {}: {}", shortcut, e))?;
+
+    let bindings = state_guard.workbook_hotkeys.entry(workbook_id.clone()).or_default();
+    if let Some(index) = bindings.iter().position(|(existing, _)| *existing == action) {
+        let (_, previous) = bindings.remove(index);
+        if let Err(e) = app.global_shortcut().unregister(previous) {
+            eprintln!("[hotkeys] Failed to unregister previous shortcut for {}: {}", action, e);
+        }
+    }
+    bindings.push((action.clone(), parsed));
+    println!("[hotkeys] Registered {} for {} in workbook {}", shortcut, action, workbook_id);
+    Ok(())
+}
+
+/// Drop a workbook's shortcuts (also called when its window closes)
+pub async fn unregister_workbook_hotkeys_internal(
+    app: &AppHandle,
+    state: &Arc<tokio::sync::Mutex<crate::AppState>>,
+    workbook_id: &str,
+) {
+    let Some(bindings) = state.lock().await.workbook_hotkeys.remove(workbook_id) else { return };
+    for (action, shortcut) in bindings {
+        if let Err(e) = app.global_shortcut().unregister(shortcut) {
+            eprintln!("[hotkeys] Failed to unregister shortcut for {}: {}", action, e);
+        }
+    }
+    println!("[hotkeys] Unregistered shortcuts for workbook {}", workbook_id);
+}
+
+/// Unregister every shortcut registered for a workbook
+#[tauri::command]
+pub async fn unregister_workbook_hotkeys(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<crate::AppState>>>,
+    workbook_id: String,
+) -> Result<(), String> {
+    unregister_workbook_hotkeys_internal(&app, state.inner(), &workbook_id).await;
+    Ok(())
+}
+
 /// Unregister all global shortcuts
 pub fn unregister_global_shortcuts(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     app.global_shortcut().unregister_all()?;
     println!("[hotkeys] Unregistered all global shortcuts");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulate_shortcut_ids_map_to_actions() {
+        assert!(shortcut_action("capture").is_some());
+        assert!(shortcut_action("capture_clipboard").is_some());
+        assert!(shortcut_action("stt").is_none());
+        assert!(shortcut_action("unknown").is_none());
+    }
+}
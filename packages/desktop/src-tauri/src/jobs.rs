@@ -17,6 +17,30 @@ pub enum JobStatus {
     Cancelled,
 }
 
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Maximum tool calls recorded per job
+const MAX_TOOL_CALLS_PER_JOB: usize = 50;
+/// Maximum length of a tool call's input preview (in characters)
+const TOOL_INPUT_PREVIEW_LEN: usize = 100;
+
+/// A tool invocation made by the agent while a job was running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallSummary {
+    pub tool_name: String,
+    pub input_preview: String,
+    pub timestamp_ms: u64,
+}
+
 /// Information about an active job
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobInfo {
@@ -27,6 +51,11 @@ pub struct JobInfo {
     pub description: String,
     pub started_at: u64,
     pub updated_at: u64,
+    pub tool_calls: Vec<ToolCallSummary>,
+    /// Fraction complete (0.0 - 1.0), if the agent reports it
+    pub progress: Option<f32>,
+    /// Description of what the job is currently doing
+    pub current_step: Option<String>,
 }
 
 impl JobInfo {
@@ -44,6 +73,9 @@ impl JobInfo {
             description,
             started_at: now,
             updated_at: now,
+            tool_calls: Vec::new(),
+            progress: None,
+            current_step: None,
         }
     }
 
@@ -99,6 +131,24 @@ impl JobRegistry {
         }
     }
 
+    /// Update how far along an active job is. `progress` is clamped to 0.0 - 1.0;
+    /// a `None` step keeps the previous one. Returns false if the job isn't active.
+    pub fn update_progress(&mut self, job_id: &str, progress: f32, step: Option<String>) -> bool {
+        let Some(job) = self.jobs.get_mut(job_id).filter(|j| j.is_active()) else {
+            return false;
+        };
+
+        job.progress = Some(progress.clamp(0.0, 1.0));
+        if step.is_some() {
+            job.current_step = step;
+        }
+        job.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        true
+    }
+
     /// Mark a job as completed
     pub fn complete(&mut self, job_id: &str) {
         self.update_status(job_id, JobStatus::Completed);
@@ -126,6 +176,42 @@ impl JobRegistry {
             .find(|j| j.session_id == session_id && j.is_active())
     }
 
+    /// Record a tool call against the active job for a session.
+    /// Returns false if the session has no active job.
+    pub fn record_tool_call(&mut self, session_id: &str, tool_name: &str, input: &serde_json::Value) -> bool {
+        let Some(job) = self
+            .jobs
+            .values_mut()
+            .find(|j| j.session_id == session_id && j.is_active())
+        else {
+            return false;
+        };
+
+        if job.tool_calls.len() >= MAX_TOOL_CALLS_PER_JOB {
+            return true;
+        }
+
+        let input = serde_json::to_string(input).unwrap_or_default();
+        let input_preview = if input.chars().count() > TOOL_INPUT_PREVIEW_LEN {
+            format!("{}...", input.chars().take(TOOL_INPUT_PREVIEW_LEN).collect::<String>())
+        } else {
+            input
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        job.tool_calls.push(ToolCallSummary {
+            tool_name: tool_name.to_string(),
+            input_preview,
+            timestamp_ms: now,
+        });
+        job.updated_at = now;
+        true
+    }
+
     /// Get job by ID
     pub fn get(&self, job_id: &str) -> Option<&JobInfo> {
         self.jobs.get(job_id)
@@ -169,6 +255,35 @@ impl JobRegistry {
         workbooks
     }
 
+    /// Register, complete or fail a session's job for a session status. Returns the
+    /// transition and job ID, or None when the status changes nothing.
+    pub fn apply_session_status(
+        &mut self,
+        workbook_id: &str,
+        session_id: &str,
+        status: &str,
+    ) -> Option<(JobTransition, String)> {
+        let active_job = self.find_active_by_session(session_id).map(|j| j.id.clone());
+
+        if SessionEvent::is_running_status(status) {
+            if active_job.is_some() {
+                return None;
+            }
+            let job_id = self.register(workbook_id, session_id, "AI processing...");
+            Some((JobTransition::Started, job_id))
+        } else if SessionEvent::is_completed_status(status) {
+            let job_id = active_job?;
+            self.complete(&job_id);
+            Some((JobTransition::Completed, job_id))
+        } else if SessionEvent::is_failed_status(status) {
+            let job_id = active_job?;
+            self.fail(&job_id);
+            Some((JobTransition::Failed, job_id))
+        } else {
+            None
+        }
+    }
+
     /// Clean up old completed/failed jobs (older than 1 hour)
     pub fn cleanup_old(&mut self) {
         let one_hour_ago = SystemTime::now()
@@ -182,6 +297,25 @@ impl JobRegistry {
     }
 }
 
+/// How a session status event changed its job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobTransition {
+    Started,
+    Completed,
+    Failed,
+}
+
+impl JobTransition {
+    /// Event emitted to the frontend and tray with the job ID
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            JobTransition::Started => "job:started",
+            JobTransition::Completed => "job:completed",
+            JobTransition::Failed => "job:failed",
+        }
+    }
+}
+
 /// SSE event types from OpenCode server
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -202,12 +336,37 @@ pub enum SessionEvent {
     MessagePartUpdated {
         #[serde(rename = "sessionId")]
         session_id: String,
+        #[serde(default)]
+        progress: Option<f32>,
+        #[serde(default)]
+        step: Option<String>,
+    },
+    #[serde(rename = "tool.call")]
+    ToolCall {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        #[serde(rename = "toolName")]
+        tool_name: String,
+        #[serde(default)]
+        input: serde_json::Value,
     },
     #[serde(other)]
     Unknown,
 }
 
 impl SessionEvent {
+    /// Fold a `SessionUpdated` that carries a status into `SessionStatus`, so
+    /// handlers see a single status event. Done in place rather than by
+    /// re-dispatching, so a stream of updates never recurses.
+    pub fn normalize(self) -> Self {
+        match self {
+            SessionEvent::SessionUpdated { session_id, status: Some(status) } => {
+                SessionEvent::SessionStatus { session_id, status }
+            }
+            other => other,
+        }
+    }
+
     /// Parse session status to determine if job is active
     pub fn is_running_status(status: &str) -> bool {
         matches!(status, "running" | "pending" | "streaming")
@@ -221,3 +380,74 @@ impl SessionEvent {
         matches!(status, "failed" | "error" | "cancelled")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_updated(status: &str) -> SessionEvent {
+        SessionEvent::SessionUpdated {
+            session_id: "ses_1".to_string(),
+            status: Some(status.to_string()),
+        }
+    }
+
+    /// Handle a chain of updates the way `handle_session_event` does
+    fn apply_chain(registry: &mut JobRegistry, events: Vec<SessionEvent>) -> Vec<JobTransition> {
+        events
+            .into_iter()
+            .filter_map(|event| match event.normalize() {
+                SessionEvent::SessionStatus { session_id, status } => {
+                    registry.apply_session_status("wb-1", &session_id, &status)
+                }
+                _ => None,
+            })
+            .map(|(transition, _)| transition)
+            .collect()
+    }
+
+    #[test]
+    fn session_updated_chain_is_handled_without_recursion() {
+        // A small stack would overflow if each update re-dispatched recursively
+        let transitions = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let mut registry = JobRegistry::new();
+                let chain = (0..10)
+                    .map(|i| session_updated(if i % 2 == 0 { "running" } else { "idle" }))
+                    .collect();
+                let transitions = apply_chain(&mut registry, chain);
+                assert_eq!(registry.active_count(), 0);
+                transitions
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let expected: Vec<JobTransition> = (0..10)
+            .map(|i| if i % 2 == 0 { JobTransition::Started } else { JobTransition::Completed })
+            .collect();
+        assert_eq!(transitions, expected);
+    }
+
+    #[test]
+    fn repeated_statuses_change_nothing() {
+        let mut registry = JobRegistry::new();
+        let chain = vec![
+            session_updated("running"),
+            session_updated("streaming"),
+            session_updated("idle"),
+            session_updated("idle"),
+        ];
+        assert_eq!(
+            apply_chain(&mut registry, chain),
+            vec![JobTransition::Started, JobTransition::Completed]
+        );
+    }
+
+    #[test]
+    fn session_updated_without_status_is_ignored() {
+        let event = SessionEvent::SessionUpdated { session_id: "ses_1".to_string(), status: None };
+        assert!(matches!(event.normalize(), SessionEvent::SessionUpdated { status: None, .. }));
+    }
+}
@@ -8,18 +8,48 @@
 
 use device_query::{DeviceQuery, DeviceState, Keycode};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
-use std::time::Duration;
+use std::sync::{Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 
-/// Tracks whether Option is currently held
-static OPTION_HELD: AtomicBool = AtomicBool::new(false);
-/// Tracks whether Space was pressed while Option was held
-static SPACE_PRESSED_WITH_OPTION: AtomicBool = AtomicBool::new(false);
-/// Tracks whether another key was pressed with Option (makes it a combo, not STT trigger)
-static OTHER_KEY_WITH_OPTION: AtomicBool = AtomicBool::new(false);
+/// Option key state shared between the listener thread and readers.
+/// Kept behind one lock so multi-field reads are never torn.
+#[derive(Debug, Default, Clone, Copy)]
+struct ListenerState {
+    /// Whether Option is currently held
+    option_held: bool,
+    /// Whether Space was pressed while Option was held
+    space_pressed_with_option: bool,
+    /// Whether another key was pressed with Option (makes it a combo, not STT trigger)
+    other_key_with_option: bool,
+}
+
+static LISTENER_STATE: Mutex<ListenerState> = Mutex::new(ListenerState {
+    option_held: false,
+    space_pressed_with_option: false,
+    other_key_with_option: false,
+});
+/// Whether a listener thread is currently running (only one may run at a time)
+static LISTENER_RUNNING: AtomicBool = AtomicBool::new(false);
 /// Shutdown flag for the keyboard listener thread
 static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+/// Handle of the running listener thread, joined on stop
+static LISTENER_THREAD: OnceLock<Mutex<Option<JoinHandle<()>>>> = OnceLock::new();
+
+/// How long `stop_keyboard_listener` waits for the thread to exit
+const STOP_JOIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+fn listener_thread() -> std::sync::MutexGuard<'static, Option<JoinHandle<()>>> {
+    LISTENER_THREAD
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn listener_state() -> std::sync::MutexGuard<'static, ListenerState> {
+    LISTENER_STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
 
 /// Check if only Option key(s) are pressed (no other keys)
 fn is_option_alone(keys: &[Keycode]) -> bool {
@@ -28,12 +58,22 @@ fn is_option_alone(keys: &[Keycode]) -> bool {
 
 /// Start the global keyboard listener using device_query (polling-based)
 pub fn start_keyboard_listener(app: AppHandle) {
+    // Only one listener may run - a second one would emit duplicate/spurious events
+    if LISTENER_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        eprintln!("[keyboard] Listener already running, ignoring start request");
+        return;
+    }
+
     // Reset shutdown flag in case of restart
     SHUTDOWN.store(false, Ordering::SeqCst);
+    *listener_state() = ListenerState::default();
 
     let app_handle = app.clone();
 
-    thread::spawn(move || {
+    let handle = thread::spawn(move || {
         let device_state = DeviceState::new();
         let mut prev_option_held = false;
         let mut prev_space_held = false;
@@ -52,9 +92,11 @@ pub fn start_keyboard_listener(app: AppHandle) {
 
             // Option key pressed (transition from not held to held)
             if option_held && !prev_option_held {
-                OPTION_HELD.store(true, Ordering::SeqCst);
-                SPACE_PRESSED_WITH_OPTION.store(false, Ordering::SeqCst);
-                OTHER_KEY_WITH_OPTION.store(false, Ordering::SeqCst);
+                *listener_state() = ListenerState {
+                    option_held: true,
+                    space_pressed_with_option: false,
+                    other_key_with_option: false,
+                };
                 stt_started = false;
 
                 // Only trigger STT if Option is pressed alone
@@ -76,8 +118,13 @@ pub fn start_keyboard_listener(app: AppHandle) {
 
             // If Option is held and another key is pressed, mark as combo (not STT)
             if option_held && !option_alone && !space_held {
-                if !OTHER_KEY_WITH_OPTION.load(Ordering::SeqCst) {
-                    OTHER_KEY_WITH_OPTION.store(true, Ordering::SeqCst);
+                let newly_combo = {
+                    let mut state = listener_state();
+                    let was_combo = state.other_key_with_option;
+                    state.other_key_with_option = true;
+                    !was_combo
+                };
+                if newly_combo {
                     // Cancel STT if it was started
                     if stt_started {
                         let _ = app_handle.emit("option-key-cancelled", ());
@@ -88,21 +135,19 @@ pub fn start_keyboard_listener(app: AppHandle) {
 
             // Space pressed while Option is held
             if space_held && !prev_space_held && option_held {
-                SPACE_PRESSED_WITH_OPTION.store(true, Ordering::SeqCst);
+                listener_state().space_pressed_with_option = true;
                 let _ = app_handle.emit("option-space-pressed", ());
             }
 
             // Option key released (transition from held to not held)
             if !option_held && prev_option_held {
-                OPTION_HELD.store(false, Ordering::SeqCst);
+                *listener_state() = ListenerState::default();
 
                 // Always emit release event to stop STT recording
                 // The frontend will handle whether to transcribe or cancel
                 let _ = app_handle.emit("option-key-released", ());
 
                 stt_started = false;
-                SPACE_PRESSED_WITH_OPTION.store(false, Ordering::SeqCst);
-                OTHER_KEY_WITH_OPTION.store(false, Ordering::SeqCst);
             }
 
             prev_option_held = option_held;
@@ -112,17 +157,53 @@ pub fn start_keyboard_listener(app: AppHandle) {
             thread::sleep(Duration::from_millis(10));
         }
 
+        *listener_state() = ListenerState::default();
+        LISTENER_RUNNING.store(false, Ordering::SeqCst);
         println!("[keyboard] Listener thread stopped");
     });
+    *listener_thread() = Some(handle);
 }
 
-/// Stop the keyboard listener thread.
+/// Stop the running listener (if any) and start a fresh one.
+/// The old thread is joined first so the new one isn't rejected.
+pub fn restart_keyboard_listener(app: AppHandle) {
+    stop_keyboard_listener();
+    start_keyboard_listener(app);
+}
+
+/// Stop the keyboard listener thread and wait (up to 200ms) for it to exit.
 /// Call this on app shutdown to prevent resource leaks.
 pub fn stop_keyboard_listener() {
     SHUTDOWN.store(true, Ordering::SeqCst);
+
+    let Some(handle) = listener_thread().take() else {
+        return;
+    };
+
+    // The thread polls every 10ms, so it should notice the flag almost immediately
+    let deadline = Instant::now() + STOP_JOIN_TIMEOUT;
+    while !handle.is_finished() {
+        let now = Instant::now();
+        if now >= deadline {
+            eprintln!("[keyboard] Listener did not stop within {:?}, detaching", STOP_JOIN_TIMEOUT);
+            return;
+        }
+        thread::park_timeout((deadline - now).min(Duration::from_millis(10)));
+    }
+    let _ = handle.join();
 }
 
 /// Check if Option key is currently held
 pub fn is_option_held() -> bool {
-    OPTION_HELD.load(Ordering::SeqCst)
+    listener_state().option_held
+}
+
+/// Check if Space was pressed during the current Option hold
+pub fn was_space_pressed_with_option() -> bool {
+    listener_state().space_pressed_with_option
+}
+
+/// Check if another key was pressed during the current Option hold (a combo, not STT)
+pub fn is_option_combo() -> bool {
+    listener_state().other_key_with_option
 }
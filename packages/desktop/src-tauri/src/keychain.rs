@@ -0,0 +1,74 @@
+//! OS secret storage for API keys.
+//!
+//! Uses the macOS Keychain and Windows Credential Manager via `keyring`. On
+//! other platforms there is no secret store, so callers fall back to
+//! `settings.json`.
+
+/// Keychain service name for all Hands secrets
+pub const SERVICE: &str = "com.hands.app";
+
+/// Stored in `settings.json` in place of a secret that lives in the keychain,
+/// so the UI can tell a key is set without reading it
+pub const SENTINEL: &str = "<keychain>";
+
+/// Store a secret in the OS keychain
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub fn set_secret(service: &str, key: &str, value: &str) -> Result<(), String> {
+    keyring::Entry::new(service, key)
+        .and_then(|entry| entry.set_password(value))
+        .map_err(|e| format!("Failed to save {} to keychain: {}", key, e))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn set_secret(_service: &str, key: &str, _value: &str) -> Result<(), String> {
+    Err(format!("No keychain available to store {}", key))
+}
+
+/// Read a secret from the OS keychain
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub fn get_secret(service: &str, key: &str) -> Option<String> {
+    match keyring::Entry::new(service, key).and_then(|entry| entry.get_password()) {
+        Ok(value) => Some(value),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            eprintln!("[keychain] Failed to read {}: {}", key, e);
+            None
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn get_secret(_service: &str, _key: &str) -> Option<String> {
+    None
+}
+
+/// Remove a secret from the OS keychain (a missing entry is not an error)
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub fn delete_secret(service: &str, key: &str) -> Result<(), String> {
+    match keyring::Entry::new(service, key).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove {} from keychain: {}", key, e)),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn delete_secret(_service: &str, _key: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/// Store a secret in the keychain, returning the value to keep in `settings.json`:
+/// `SENTINEL` on success, or the secret itself when the keychain can't be written.
+/// On fallback any older keychain entry is removed, since readers check the
+/// keychain first and a stale entry would shadow the new value.
+pub fn store_secret(key: &str, value: &str) -> String {
+    match set_secret(SERVICE, key, value) {
+        Ok(()) => SENTINEL.to_string(),
+        Err(e) => {
+            println!("[keychain] {}, storing {} in settings", e, key);
+            if let Err(e) = delete_secret(SERVICE, key) {
+                eprintln!("[keychain] {}", e);
+            }
+            value.to_string()
+        }
+    }
+}
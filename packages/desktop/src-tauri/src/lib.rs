@@ -1,5 +1,13 @@
+//! Hands desktop app (Tauri backend).
+//!
+//! Quit flow: closing the main or a workbook window only hides it - the app keeps
+//! running in the tray. Quitting goes through the tray's "Quit Hands" item, which
+//! calls [`quit_app`]: it broadcasts on `AppState::quit_tx` so background tasks
+//! (e.g. the runtime monitor) stop, then calls `app.exit(0)`. The main window's
+//! `Destroyed` event then runs process cleanup (keyboard listener, runtimes, agent).
+
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::process::Stdio;
@@ -26,37 +34,134 @@ pub mod stt;
 pub mod keyboard;
 pub mod sfx;
 pub mod websearch;
+pub mod shell_ext;
+pub mod telemetry;
+pub mod keychain;
+pub mod settings_transfer;
+pub mod workbook_watcher;
 
-use runtime_manager::RuntimeManager;
-use jobs::{JobRegistry, SessionEvent};
+use runtime_manager::{RestartPolicy, RuntimeInfo, RuntimeManager, RuntimePorts};
+use jobs::{JobRegistry, JobTransition, SessionEvent};
 
 // Port configuration - matches packages/workbook-server/src/ports.ts
 // All ports use 5-digit scheme with configurable prefix (default 55xxx)
-const PORT_PREFIX: u16 = 55;
-// const PORT_RUNTIME: u16 = PORT_PREFIX * 1000;        // 55000
-// const PORT_POSTGRES: u16 = PORT_PREFIX * 1000 + 100; // 55100
-// const PORT_WORKER: u16 = PORT_PREFIX * 1000 + 200;   // 55200
-const PORT_OPENCODE: u16 = PORT_PREFIX * 1000 + 300;    // 55300
-
-// Workbook server process info
-#[derive(Debug)]
-pub struct WorkbookServerProcess {
-    pub child: Child,
-    pub runtime_port: u16,
-    pub directory: String,
-    pub restart_count: u32,
+// PORT_RUNTIME  = prefix * 1000        // 55000
+// PORT_POSTGRES = prefix * 1000 + 100  // 55100
+// PORT_WORKER   = prefix * 1000 + 200  // 55200
+// PORT_OPENCODE = prefix * 1000 + 300  // 55300
+pub(crate) const DEFAULT_PORT_PREFIX: u16 = 55;
+const PORT_PREFIX_KEY: &str = "port_prefix";
+// Prefixes below 2 land in privileged ports; above 65 the OpenCode port overflows u16
+const PORT_PREFIX_RANGE: std::ops::RangeInclusive<u16> = 2..=65;
+
+/// Port prefix in effect for this launch (read from settings once at startup).
+/// Managed separately from `AppState` so sync code can read it without the async lock.
+pub struct PortPrefix(pub u16);
+
+/// Read the configured port prefix from settings, falling back to the default
+fn load_port_prefix(app: &tauri::AppHandle) -> u16 {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(PORT_PREFIX_KEY))
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u16::try_from(v).ok())
+        .filter(|prefix| PORT_PREFIX_RANGE.contains(prefix))
+        .unwrap_or(DEFAULT_PORT_PREFIX)
+}
+
+fn port_prefix(app: &tauri::AppHandle) -> u16 {
+    app.try_state::<PortPrefix>().map(|prefix| prefix.0).unwrap_or(DEFAULT_PORT_PREFIX)
+}
+
+fn port_opencode(app: &tauri::AppHandle) -> u16 {
+    port_prefix(app) * 1000 + 300
+}
+
+/// Port prefix in effect for this launch, so the UI can derive service ports
+#[tauri::command]
+fn get_port_prefix(app: tauri::AppHandle) -> u16 {
+    port_prefix(&app)
+}
+
+/// Persist a new port prefix. Takes effect on next launch, since running
+/// runtimes and the agent server are already bound to the current ports.
+#[tauri::command]
+fn set_port_prefix(app: tauri::AppHandle, prefix: u16) -> Result<(), String> {
+    if !PORT_PREFIX_RANGE.contains(&prefix) {
+        return Err(format!(
+            "Port prefix must be between {} and {}",
+            PORT_PREFIX_RANGE.start(),
+            PORT_PREFIX_RANGE.end()
+        ));
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(PORT_PREFIX_KEY, serde_json::json!(prefix));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
 }
 
 // App state - tracks runtime processes, opencode server, and multi-window state
 pub struct AppState {
     pub server: Option<Child>,
-    pub workbook_servers: HashMap<String, WorkbookServerProcess>, // workbook_id -> server process (legacy)
-    pub runtime_manager: RuntimeManager,           // new multi-runtime manager
+    pub runtime_manager: RuntimeManager,           // workbook_id -> runtime process
     pub job_registry: JobRegistry,                 // background job tracking
     pub active_workbook_id: Option<String>,        // currently active workbook
-    pub should_quit: bool,                         // track if app should actually quit
+    pub eval_watches: HashMap<String, EvalWatch>,  // stream_id -> eval watch task
+    pub domain_whitelist: Vec<String>,             // web search restricted to these domains (empty = any)
+    pub telemetry_enabled: bool,                   // user opted in to anonymous usage counts
+    pub telemetry_counts: telemetry::TelemetryCounts, // event name -> count since last flush
+    pub agent_port_tx: tokio::sync::watch::Sender<u16>, // agent server port, signals SSE reconnects
+    pub quit_tx: tokio::sync::broadcast::Sender<()>, // fired once when the user quits
+    pub workbook_watchers: HashMap<String, notify::RecommendedWatcher>, // workbook_id -> file watcher
+    pub session_cache: HashMap<(String, usize), (std::time::Instant, Vec<SessionSummary>)>, // (workbook_id, limit) -> recent sessions
+    pub workbook_hotkeys: HashMap<String, Vec<(String, tauri_plugin_global_shortcut::Shortcut)>>, // workbook_id -> (action, shortcut)
+    pub robots_cache: websearch::RobotsCache,      // origin -> fetched robots.txt groups
+}
+
+impl AppState {
+    pub fn new(
+        port_prefix: u16,
+        agent_port_tx: tokio::sync::watch::Sender<u16>,
+        telemetry_enabled: bool,
+        telemetry_counts: telemetry::TelemetryCounts,
+    ) -> Self {
+        let (quit_tx, _) = tokio::sync::broadcast::channel(1);
+        Self {
+            server: None,
+            runtime_manager: RuntimeManager::new(port_prefix),
+            job_registry: JobRegistry::new(),
+            active_workbook_id: None,
+            eval_watches: HashMap::new(),
+            domain_whitelist: Vec::new(),
+            telemetry_enabled,
+            telemetry_counts,
+            agent_port_tx,
+            quit_tx,
+            workbook_watchers: HashMap::new(),
+            session_cache: HashMap::new(),
+            workbook_hotkeys: HashMap::new(),
+            robots_cache: HashMap::new(),
+        }
+    }
 }
 
+/// Quit the app: notify background tasks, then exit (cleanup runs when the
+/// main window is destroyed)
+pub async fn quit_app(app: &tauri::AppHandle) {
+    println!("[shutdown] Quit requested");
+    if let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() {
+        let _ = state.lock().await.quit_tx.send(());
+    }
+    app.exit(0);
+}
+
+/// Serializes agent (OpenCode) server stop/start sequences.
+/// Managed separately from `AppState` so a restart doesn't hold the app state lock
+/// while waiting for the server to come up.
+pub struct AgentServerMutex(pub Mutex<()>);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheck {
     pub healthy: bool,
@@ -81,14 +186,41 @@ pub struct Workbook {
     pub created_at: u64,
     pub updated_at: u64,
     pub last_opened_at: u64,
+    /// Workbook lives in a user-provided directory instead of ~/.hands/<id>
+    #[serde(default)]
+    pub external_directory: bool,
+    /// Hidden from listings and the tray unless explicitly requested
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Label color as `#rrggbb` (or `#rgb`)
+    #[serde(default)]
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateWorkbookRequest {
     pub name: String,
     pub description: Option<String>,
+    /// `WorkbookTemplate.id` to initialize from (empty workbook if omitted)
+    #[serde(default)]
+    pub template_id: Option<String>,
+}
+
+/// A starting point for new workbooks. `init_args` are passed to `hands-cli init`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkbookTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub init_args: Vec<String>,
 }
 
+/// Directory under ~/.hands holding user-defined templates (one JSON file per template)
+const USER_TEMPLATES_DIR: &str = "templates";
+
 fn get_hands_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     let hands_dir = home.join(".hands");
@@ -98,7 +230,29 @@ fn get_hands_dir() -> Result<PathBuf, String> {
     Ok(hands_dir)
 }
 
+/// Registry of workbooks adopted from external directories (workbook_id -> directory)
+const EXTERNAL_WORKBOOKS_FILE: &str = ".external-workbooks.json";
+
+fn read_external_workbooks() -> HashMap<String, String> {
+    get_hands_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(EXTERNAL_WORKBOOKS_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_external_workbooks(registry: &HashMap<String, String>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(registry)
+        .map_err(|e| format!("Failed to serialize external workbooks: {}", e))?;
+    fs::write(get_hands_dir()?.join(EXTERNAL_WORKBOOKS_FILE), content)
+        .map_err(|e| format!("Failed to write external workbooks: {}", e))
+}
+
 fn get_workbook_dir(id: &str) -> Result<PathBuf, String> {
+    // External workbooks stay where the user created them
+    if let Some(dir) = read_external_workbooks().get(id) {
+        return Ok(PathBuf::from(dir));
+    }
     Ok(get_hands_dir()?.join(id))
 }
 
@@ -128,6 +282,21 @@ fn get_runtime_path() -> String {
     }
 }
 
+/// npm package name for a workbook: lowercase, with runs of other characters
+/// collapsed to single dashes
+fn package_name(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "workbook".to_string() } else { slug.to_string() }
+}
+
 fn save_workbook_config(workbook: &Workbook) -> Result<(), String> {
     let workbook_dir = PathBuf::from(&workbook.directory);
     let package_path = workbook_dir.join("package.json");
@@ -139,7 +308,7 @@ fn save_workbook_config(workbook: &Workbook) -> Result<(), String> {
             .map_err(|e| format!("Failed to parse package.json: {}", e))?
     } else {
         serde_json::json!({
-            "name": workbook.name.to_lowercase().replace(" ", "-"),
+            "name": package_name(&workbook.name),
             "version": "0.0.1",
             "private": true
         })
@@ -151,7 +320,11 @@ fn save_workbook_config(workbook: &Workbook) -> Result<(), String> {
         "description": workbook.description,
         "createdAt": workbook.created_at,
         "updatedAt": workbook.updated_at,
-        "lastOpenedAt": workbook.last_opened_at
+        "lastOpenedAt": workbook.last_opened_at,
+        "externalDirectory": workbook.external_directory,
+        "archived": workbook.archived,
+        "tags": workbook.tags,
+        "color": workbook.color
     });
 
     let content = serde_json::to_string_pretty(&package)
@@ -178,35 +351,158 @@ fn read_workbook_config(workbook_dir: &PathBuf) -> Option<Workbook> {
         created_at: hands.get("createdAt")?.as_u64()?,
         updated_at: hands.get("updatedAt")?.as_u64()?,
         last_opened_at: hands.get("lastOpenedAt")?.as_u64()?,
+        external_directory: hands.get("externalDirectory").and_then(|v| v.as_bool()).unwrap_or(false),
+        archived: hands.get("archived").and_then(|v| v.as_bool()).unwrap_or(false),
+        tags: hands.get("tags")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default(),
+        color: hands.get("color").and_then(|v| v.as_str()).map(|s| s.to_string()),
     })
 }
 
+/// Error returned by workbook commands. Serialized with a `kind` tag so the UI
+/// can handle specific failures (e.g. missing runtime dependencies) instead of
+/// showing raw CLI output.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum HandsError {
+    /// The CLI sidecar couldn't load `@hands/runtime` (dependencies not installed)
+    RuntimeNotInstalled { hint: String },
+    Failed { message: String },
+}
+
+impl std::fmt::Display for HandsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandsError::RuntimeNotInstalled { hint } => write!(f, "Hands runtime is not installed. {}", hint),
+            HandsError::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for HandsError {
+    fn from(message: String) -> Self {
+        HandsError::Failed { message }
+    }
+}
+
+impl From<&str> for HandsError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// Whether CLI output indicates missing JS dependencies rather than a real init failure
+fn is_runtime_not_installed(output: &str) -> bool {
+    output.contains("Cannot find module")
+        || output.contains("ERR_MODULE_NOT_FOUND")
+        || output.contains("node_modules")
+}
+
 /// Initialize workbook by calling the shared TypeScript implementation.
 /// This ensures CLI and desktop app create identical workbook structures.
-fn init_workbook(workbook_dir: &PathBuf, name: &str, _description: Option<&str>) -> Result<(), String> {
-    let output = sidecar::command_sync(sidecar::Sidecar::Cli)
+fn init_workbook(
+    app: &tauri::AppHandle,
+    workbook_dir: &std::path::Path,
+    name: &str,
+    _description: Option<&str>,
+    init_args: &[String],
+) -> Result<(), HandsError> {
+    let output = sidecar::command_sync(app, sidecar::Sidecar::Cli)
         .args([
             "init",
             &format!("--name={}", name),
             &format!("--dir={}", workbook_dir.to_string_lossy()),
         ])
+        .args(init_args)
         .output()
         .map_err(|e| format!("Failed to run init script: {}", e))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         let stdout = String::from_utf8_lossy(&output.stdout);
-        return Err(format!("Init failed: {} {}", stderr, stdout));
+        if is_runtime_not_installed(&stderr) {
+            eprintln!("[init] Runtime dependencies missing: {}", stderr);
+            return Err(HandsError::RuntimeNotInstalled {
+                hint: "Run `bun install` in the packages/runtime directory".to_string(),
+            });
+        }
+        return Err(format!("Init failed: {} {}", stderr, stdout).into());
     }
 
     Ok(())
 }
 
+/// Path of the bundled templates.json (falls back to the source tree in dev builds)
+fn bundled_templates_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    let bundled = app.path().resource_dir().ok().map(|dir| dir.join("templates.json"));
+    if let Some(path) = bundled.filter(|p| p.exists()) {
+        return Some(path);
+    }
+
+    #[cfg(debug_assertions)]
+    {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources/templates.json");
+        if path.exists() {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+/// Bundled templates followed by user-defined ones from ~/.hands/templates/*.json.
+/// A user template replaces a bundled template with the same ID.
+fn load_workbook_templates(app: &tauri::AppHandle) -> Result<Vec<WorkbookTemplate>, String> {
+    let mut templates: Vec<WorkbookTemplate> = match bundled_templates_path(app) {
+        Some(path) => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?
+        }
+        None => {
+            eprintln!("[templates] Bundled templates.json not found");
+            Vec::new()
+        }
+    };
+
+    let user_dir = get_hands_dir()?.join(USER_TEMPLATES_DIR);
+    if let Ok(entries) = fs::read_dir(&user_dir) {
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            // Skip broken user templates rather than hiding every template
+            let template = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<WorkbookTemplate>(&content).map_err(|e| e.to_string()));
+            match template {
+                Ok(template) => {
+                    templates.retain(|t| t.id != template.id);
+                    templates.push(template);
+                }
+                Err(e) => eprintln!("[templates] Skipping {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    Ok(templates)
+}
+
+/// List templates available for new workbooks
 #[tauri::command]
-async fn create_workbook(
-    request: CreateWorkbookRequest,
-) -> Result<Workbook, String> {
-    let slug = request.name.to_lowercase()
+async fn list_workbook_templates(app: tauri::AppHandle) -> Result<Vec<WorkbookTemplate>, String> {
+    load_workbook_templates(&app)
+}
+
+/// Generate a workbook ID (`<slug>-<hex timestamp>`) from a name.
+/// Returns the ID and the current time in milliseconds.
+fn new_workbook_id(name: &str) -> Result<(String, u64), String> {
+    let slug = name.to_lowercase()
         .chars()
         .map(|c| if c.is_alphanumeric() { c } else { '-' })
         .collect::<String>();
@@ -214,7 +510,36 @@ async fn create_workbook(
         .duration_since(std::time::UNIX_EPOCH)
         .map_err(|e| e.to_string())?
         .as_millis();
-    let id = format!("{}-{:x}", slug, timestamp % 0xFFFF);
+    Ok((format!("{}-{:x}", slug, timestamp % 0xFFFF), timestamp as u64))
+}
+
+/// Check that the CLI sidecar (and the runtime it depends on) can run
+#[tauri::command]
+async fn check_runtime_installed(app: tauri::AppHandle) -> Result<bool, String> {
+    let output = sidecar::command(&app, sidecar::Sidecar::Cli)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run CLI: {}", e))?;
+    Ok(output.status.success())
+}
+
+#[tauri::command]
+async fn create_workbook(
+    app: tauri::AppHandle,
+    request: CreateWorkbookRequest,
+) -> Result<Workbook, HandsError> {
+    let (id, now) = new_workbook_id(&request.name)?;
+
+    // Resolve the template before creating anything on disk
+    let init_args = match &request.template_id {
+        Some(template_id) => load_workbook_templates(&app)?
+            .into_iter()
+            .find(|t| &t.id == template_id)
+            .map(|t| t.init_args)
+            .ok_or_else(|| format!("Template {} not found", template_id))?,
+        None => Vec::new(),
+    };
 
     let workbook_dir = get_workbook_dir(&id)?;
     fs::create_dir_all(&workbook_dir).map_err(|e| format!("Failed to create workbook directory: {}", e))?;
@@ -224,9 +549,8 @@ async fn create_workbook(
         .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
 
     // Create project structure from template
-    init_workbook(&workbook_dir, &request.name, request.description.as_deref())?;
+    init_workbook(&app, &workbook_dir, &request.name, request.description.as_deref(), &init_args)?;
 
-    let now = timestamp as u64;
     let workbook = Workbook {
         id: id.clone(),
         name: request.name,
@@ -235,16 +559,135 @@ async fn create_workbook(
         created_at: now,
         updated_at: now,
         last_opened_at: now,
+        external_directory: false,
+        archived: false,
+        tags: Vec::new(),
+        color: None,
     };
 
     save_workbook_config(&workbook)?;
 
+    telemetry::track(&app, "workbook_created").await;
+    let _ = app.emit("workbook:created", &workbook);
+
     Ok(workbook)
 }
 
-/// List all workbooks by scanning ~/.hands directories
+/// Adopt an existing project directory as a workbook, keeping it in place.
+/// Skips the CLI template step - only git and package.json metadata are added.
 #[tauri::command]
-async fn list_workbooks() -> Result<Vec<Workbook>, String> {
+async fn create_workbook_from_directory(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+) -> Result<Workbook, String> {
+    let workbook_dir = PathBuf::from(&path)
+        .canonicalize()
+        .map_err(|e| format!("Directory {} not found: {}", path, e))?;
+
+    if !workbook_dir.is_dir() {
+        return Err(format!("{} is not a directory", path));
+    }
+
+    let dir_name = workbook_dir.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("workbook")
+        .to_string();
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis() as u64;
+
+    let workbook = match read_workbook_config(&workbook_dir) {
+        // Already a Hands workbook (e.g. previously adopted) - keep its identity
+        Some(existing) => Workbook {
+            name: name.unwrap_or(existing.name),
+            updated_at: now,
+            last_opened_at: now,
+            external_directory: true,
+            ..existing
+        },
+        None => {
+            let slug = dir_name.to_lowercase()
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '-' })
+                .collect::<String>();
+            let uuid = uuid::Uuid::new_v4().to_string();
+
+            Workbook {
+                id: format!("{}-{}", slug, &uuid[..8]),
+                name: name.unwrap_or(dir_name),
+                description: None,
+                directory: workbook_dir.to_string_lossy().to_string(),
+                created_at: now,
+                updated_at: now,
+                last_opened_at: now,
+                external_directory: true,
+                archived: false,
+                tags: Vec::new(),
+                color: None,
+            }
+        }
+    };
+
+    if get_hands_dir()?.join(&workbook.id).exists() {
+        return Err(format!("A workbook with id {} already exists", workbook.id));
+    }
+
+    // Initialize git repo only if the project doesn't have one already. This comes
+    // after validation so a rejected directory is left untouched.
+    if git2::Repository::open(&workbook_dir).is_err() {
+        git2::Repository::init(&workbook_dir)
+            .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
+    }
+
+    save_workbook_config(&workbook)?;
+
+    let mut external = read_external_workbooks();
+    external.insert(workbook.id.clone(), workbook.directory.clone());
+    write_external_workbooks(&external)?;
+
+    let _ = app.emit("workbook:created", &workbook);
+
+    Ok(workbook)
+}
+
+/// Field to sort workbooks by
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    LastOpenedAt,
+    CreatedAt,
+    UpdatedAt,
+    Name,
+    /// Number of agent sessions, as cached by `fetch_workbook_sessions`
+    /// (0 for workbooks whose sessions haven't been fetched yet)
+    TotalSessions,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Sorting and pagination for `list_workbooks`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListWorkbooksQuery {
+    pub sort_by: SortField,
+    pub order: SortOrder,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+/// All workbooks in ~/.hands plus adopted external directories, most recently
+/// opened first. Archived workbooks are skipped unless `include_archived` is set.
+async fn scan_workbooks(include_archived: bool) -> Result<Vec<Workbook>, String> {
     let hands_dir = get_hands_dir()?;
     let mut workbooks: Vec<Workbook> = Vec::new();
 
@@ -264,8 +707,8 @@ async fn list_workbooks() -> Result<Vec<Workbook>, String> {
             .unwrap_or("")
             .to_string();
 
-        // Skip hidden directories
-        if dir_name.starts_with('.') {
+        // Skip hidden directories and user templates
+        if dir_name.starts_with('.') || dir_name == USER_TEMPLATES_DIR {
             continue;
         }
 
@@ -289,6 +732,10 @@ async fn list_workbooks() -> Result<Vec<Workbook>, String> {
                 created_at: created,
                 updated_at: created,
                 last_opened_at: created,
+                external_directory: false,
+                archived: false,
+                tags: Vec::new(),
+                color: None,
             };
 
             // Save config so it's recognized next time
@@ -297,12 +744,80 @@ async fn list_workbooks() -> Result<Vec<Workbook>, String> {
         }
     }
 
-    // Sort by last opened (most recent first)
-    workbooks.sort_by(|a, b| b.last_opened_at.cmp(&a.last_opened_at));
+    // Workbooks adopted from directories outside ~/.hands
+    for (id, dir) in read_external_workbooks() {
+        match read_workbook_config(&PathBuf::from(&dir)) {
+            Some(workbook) => workbooks.push(workbook),
+            None => eprintln!("[workbooks] External workbook {} missing at {}", id, dir),
+        }
+    }
+
+    if !include_archived {
+        workbooks.retain(|w| !w.archived);
+    }
+    workbooks.sort_by_key(|w| std::cmp::Reverse(w.last_opened_at));
 
     Ok(workbooks)
 }
 
+/// Session counts per workbook from `fetch_workbook_sessions`'s cache
+/// (the largest page cached for each workbook)
+fn cached_session_counts(state: &AppState) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for ((workbook_id, _), (_, sessions)) in &state.session_cache {
+        let count = counts.entry(workbook_id.clone()).or_default();
+        *count = (*count).max(sessions.len());
+    }
+    counts
+}
+
+/// List all workbooks by scanning ~/.hands directories
+/// Defaults to most recently opened first when no query is given.
+/// Archived workbooks are skipped unless `include_archived` is set, and only
+/// workbooks with every tag in `filter_tags` (case-insensitive) are returned.
+#[tauri::command]
+async fn list_workbooks(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    query: Option<ListWorkbooksQuery>,
+    include_archived: Option<bool>,
+    filter_tags: Option<Vec<String>>,
+) -> Result<Vec<Workbook>, String> {
+    let query = query.unwrap_or_default();
+    let mut workbooks = scan_workbooks(include_archived.unwrap_or(false)).await?;
+
+    if let Some(filter_tags) = filter_tags.filter(|tags| !tags.is_empty()) {
+        workbooks.retain(|w| {
+            filter_tags.iter().all(|tag| w.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim())))
+        });
+    }
+
+    let session_counts = match query.sort_by {
+        SortField::TotalSessions => cached_session_counts(&*state.lock().await),
+        _ => HashMap::new(),
+    };
+    let session_count = |w: &Workbook| session_counts.get(&w.id).copied().unwrap_or(0);
+
+    workbooks.sort_by(|a, b| {
+        let ordering = match query.sort_by {
+            SortField::LastOpenedAt => a.last_opened_at.cmp(&b.last_opened_at),
+            SortField::TotalSessions => session_count(a).cmp(&session_count(b)),
+            SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        };
+        match query.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    });
+
+    Ok(workbooks
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
 #[tauri::command]
 async fn get_workbook(id: String) -> Result<Workbook, String> {
     let workbook_dir = get_workbook_dir(&id)?;
@@ -330,6 +845,10 @@ async fn get_workbook(id: String) -> Result<Workbook, String> {
         created_at: created,
         updated_at: created,
         last_opened_at: created,
+        external_directory: false,
+        archived: false,
+        tags: Vec::new(),
+        color: None,
     };
 
     let _ = save_workbook_config(&workbook);
@@ -337,7 +856,7 @@ async fn get_workbook(id: String) -> Result<Workbook, String> {
 }
 
 #[tauri::command]
-async fn update_workbook(workbook: Workbook) -> Result<Workbook, String> {
+async fn update_workbook(app: tauri::AppHandle, workbook: Workbook) -> Result<Workbook, String> {
     let workbook_dir = get_workbook_dir(&workbook.id)?;
 
     if !workbook_dir.exists() {
@@ -346,18 +865,180 @@ async fn update_workbook(workbook: Workbook) -> Result<Workbook, String> {
 
     save_workbook_config(&workbook)?;
 
+    let auto_commit = app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("auto_commit_on_config_save"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if auto_commit {
+        if let Err(e) = commit_workbook(&app, &workbook.id, "Update workbook config", &["package.json"]) {
+            eprintln!("[git] Auto-commit failed for {}: {}", workbook.id, e);
+        }
+    }
+
+    let _ = app.emit("workbook:updated", &workbook);
+
+    Ok(workbook)
+}
+
+/// Set a workbook's archived flag and persist it to package.json
+async fn set_workbook_archived(app: &tauri::AppHandle, id: String, archived: bool) -> Result<Workbook, String> {
+    let mut workbook = get_workbook(id).await?;
+    workbook.archived = archived;
+    save_workbook_config(&workbook)?;
+
+    println!("[workbooks] {} {}", if archived { "Archived" } else { "Unarchived" }, workbook.id);
+    let _ = app.emit("workbook:updated", &workbook);
+
+    Ok(workbook)
+}
+
+/// Whether `color` is a `#rgb` or `#rrggbb` hex color
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Replace a workbook's tags and label color
+#[tauri::command]
+async fn tag_workbook(
+    app: tauri::AppHandle,
+    id: String,
+    tags: Vec<String>,
+    color: Option<String>,
+) -> Result<Workbook, String> {
+    if let Some(color) = &color {
+        if !is_hex_color(color) {
+            return Err(format!("Invalid color {} (expected #rrggbb)", color));
+        }
+    }
+
+    // Trim and drop empty/duplicate tags, keeping the first spelling
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+
+    let mut workbook = get_workbook(id).await?;
+    workbook.tags = normalized;
+    workbook.color = color;
+    save_workbook_config(&workbook)?;
+
+    let _ = app.emit("workbook:updated", &workbook);
+
+    Ok(workbook)
+}
+
+/// Hide a workbook from listings and the tray without deleting anything
+#[tauri::command]
+async fn archive_workbook(app: tauri::AppHandle, id: String) -> Result<Workbook, String> {
+    set_workbook_archived(&app, id, true).await
+}
+
+#[tauri::command]
+async fn unarchive_workbook(app: tauri::AppHandle, id: String) -> Result<Workbook, String> {
+    set_workbook_archived(&app, id, false).await
+}
+
+/// Rename a workbook. Updates its metadata, package.json name and open window
+/// title; the directory (and therefore the ID) stays the same.
+#[tauri::command]
+async fn rename_workbook(app: tauri::AppHandle, id: String, new_name: String) -> Result<Workbook, String> {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() {
+        return Err("Workbook name cannot be empty".to_string());
+    }
+
+    let mut workbook = get_workbook(id.clone()).await?;
+    workbook.name = new_name;
+    workbook.updated_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis() as u64;
+
+    save_workbook_config(&workbook)?;
+
+    // save_workbook_config keeps an existing package name, so recompute it here
+    let package_path = PathBuf::from(&workbook.directory).join("package.json");
+    let content = fs::read_to_string(&package_path)
+        .map_err(|e| format!("Failed to read package.json: {}", e))?;
+    let mut package: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse package.json: {}", e))?;
+    package["name"] = serde_json::json!(package_name(&workbook.name));
+    let content = serde_json::to_string_pretty(&package)
+        .map_err(|e| format!("Failed to serialize package.json: {}", e))?;
+    fs::write(&package_path, content)
+        .map_err(|e| format!("Failed to write package.json: {}", e))?;
+
+    if let Some(window) = app.get_webview_window(&window_manager::window_label(&id)) {
+        let _ = window.set_title(&workbook.name);
+    }
+
+    // Tray listens for this to refresh workbook names
+    let _ = app.emit("workbook:updated", &workbook);
+
     Ok(workbook)
 }
 
+/// Delete a workbook. Moves it to the system trash unless `permanent` is set,
+/// in which case the user is asked to confirm before it is removed for good.
 #[tauri::command]
 async fn delete_workbook(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     id: String,
+    permanent: Option<bool>,
 ) -> Result<bool, String> {
-    // Stop runtime if running
+    let permanent = permanent.unwrap_or(false);
+
+    if permanent {
+        use tauri_plugin_dialog::{MessageDialogButtons, MessageDialogKind};
+
+        let name = get_workbook_dir(&id)
+            .ok()
+            .and_then(|dir| read_workbook_config(&dir))
+            .map(|workbook| workbook.name)
+            .unwrap_or_else(|| id.clone());
+        // External workbooks are only unregistered, so don't warn about losing files
+        let (message, title, action) = match read_external_workbooks().get(&id) {
+            Some(dir) => (
+                format!("Remove workbook \"{}\" from Hands? Its files in {} are kept.", name, dir),
+                "Remove Workbook",
+                "Remove",
+            ),
+            None => (
+                format!("Permanently delete workbook \"{}\"? This cannot be undone.", name),
+                "Delete Workbook",
+                "Delete",
+            ),
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        app.dialog()
+            .message(message)
+            .title(title)
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(action.to_string(), "Cancel".to_string()))
+            .show(move |confirmed| {
+                let _ = tx.send(confirmed);
+            });
+
+        let confirmed = rx.await
+            .map_err(|e| format!("Failed to receive confirmation: {}", e))?;
+        if !confirmed {
+            return Ok(false);
+        }
+    }
+
+    // Stop runtime and file watcher if running
     {
         let mut state = state.lock().await;
-        if let Some(mut runtime) = state.workbook_servers.remove(&id) {
+        state.workbook_watchers.remove(&id);
+        if let Some(mut runtime) = state.runtime_manager.remove(&id) {
             // Call /stop endpoint first for graceful shutdown
             let stop_url = format!("http://localhost:{}/stop", runtime.runtime_port);
             let _ = reqwest::Client::new()
@@ -365,84 +1046,1209 @@ async fn delete_workbook(
                 .send()
                 .await;
             // Then kill the process
-            let _ = runtime.child.kill().await;
+            let _ = runtime.process.kill().await;
         }
     }
 
+    // External workbooks belong to the user - just forget them, never delete the directory
+    let mut external = read_external_workbooks();
+    if external.remove(&id).is_some() {
+        write_external_workbooks(&external)?;
+        let _ = app.emit("workbook:deleted", &id);
+        return Ok(true);
+    }
+
     let workbook_dir = get_workbook_dir(&id)?;
 
     if workbook_dir.exists() {
-        fs::remove_dir_all(&workbook_dir)
-            .map_err(|e| format!("Failed to delete workbook: {}", e))?;
+        if permanent {
+            fs::remove_dir_all(&workbook_dir)
+                .map_err(|e| format!("Failed to delete workbook: {}", e))?;
+        } else {
+            trash::delete(&workbook_dir)
+                .map_err(|e| format!("Failed to move workbook to trash: {}", e))?;
+        }
     }
 
+    let _ = app.emit("workbook:deleted", &id);
+
     Ok(true)
 }
 
-// Runtime status from the runtime server
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RuntimeStatus {
-    #[serde(rename = "workbookId")]
-    pub workbook_id: String,
-    #[serde(rename = "workbookDir")]
-    pub workbook_dir: String,
-    #[serde(rename = "runtimePort")]
-    pub runtime_port: u16,
-    #[serde(rename = "startedAt")]
-    pub started_at: u64,
-    pub services: RuntimeServices,
+/// Restore a workbook previously moved to the trash by `delete_workbook`
+#[tauri::command]
+async fn restore_workbook_from_trash(id: String) -> Result<Workbook, String> {
+    let workbook_dir = get_workbook_dir(&id)?;
+
+    if workbook_dir.exists() {
+        return Err(format!("Workbook {} already exists", id));
+    }
+
+    #[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"))))]
+    {
+        let items: Vec<trash::TrashItem> = trash::os_limited::list()
+            .map_err(|e| format!("Failed to list trash: {}", e))?
+            .into_iter()
+            .filter(|item| item.original_path() == workbook_dir)
+            .collect();
+
+        if items.is_empty() {
+            return Err(format!("Workbook {} not found in trash", id));
+        }
+
+        trash::os_limited::restore_all(items)
+            .map_err(|e| format!("Failed to restore workbook: {}", e))?;
+
+        get_workbook(id).await
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // The trash crate cannot enumerate the macOS Trash
+        Err("Restoring from trash is not supported on macOS - use Finder's \"Put Back\"".to_string())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RuntimeServices {
-    pub postgres: ServiceStatus,
-    pub worker: ServiceStatus,
+#[derive(Debug, Clone, Serialize)]
+struct WorkbookCommitted {
+    workbook_id: String,
+    hash: String,
+    message: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ServiceStatus {
-    pub up: bool,
-    pub port: u16,
+/// Stage `pathspecs` (nothing extra if empty) and commit the workbook index.
+/// Returns the short hash of the new commit, or of HEAD if there was nothing to commit.
+fn commit_workbook(
+    app: &tauri::AppHandle,
+    workbook_id: &str,
+    message: &str,
+    pathspecs: &[&str],
+) -> Result<String, String> {
+    let workbook_dir = get_workbook_dir(workbook_id)?;
+    let repo = git2::Repository::open(&workbook_dir)
+        .map_err(|e| format!("Failed to open git repository: {}", e))?;
+
+    let mut index = repo.index()
+        .map_err(|e| format!("Failed to read git index: {}", e))?;
+    if !pathspecs.is_empty() {
+        index.add_all(pathspecs.iter(), git2::IndexAddOption::DEFAULT, None)
+            .map_err(|e| format!("Failed to stage changes: {}", e))?;
+        index.write()
+            .map_err(|e| format!("Failed to write git index: {}", e))?;
+    }
+
+    let tree_id = index.write_tree()
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo.find_tree(tree_id)
+        .map_err(|e| format!("Failed to find tree: {}", e))?;
+
+    let short_id = |oid: git2::Oid| -> Result<String, String> {
+        repo.find_object(oid, None)
+            .and_then(|obj| obj.short_id())
+            .map(|buf| buf.as_str().unwrap_or_default().to_string())
+            .map_err(|e| format!("Failed to get commit hash: {}", e))
+    };
+
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    match &parent {
+        Some(parent) if parent.tree_id() == tree_id => return short_id(parent.id()),
+        None if tree.is_empty() => return Err("Nothing to commit".to_string()),
+        _ => {}
+    }
+
+    let (author_name, author_email) = match app.store("settings.json") {
+        Ok(store) => (
+            store.get("git_author_name").and_then(|v| v.as_str().map(String::from)),
+            store.get("git_author_email").and_then(|v| v.as_str().map(String::from)),
+        ),
+        Err(_) => (None, None),
+    };
+    let signature = git2::Signature::now(
+        author_name.as_deref().unwrap_or("Hands User"),
+        author_email.as_deref().unwrap_or("user@hands.local"),
+    ).map_err(|e| format!("Failed to create signature: {}", e))?;
+
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let oid = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .map_err(|e| format!("Failed to create commit: {}", e))?;
+    let hash = short_id(oid)?;
+
+    println!("[git] Committed {} in workbook {}", hash, workbook_id);
+    let _ = app.emit("workbook:committed", WorkbookCommitted {
+        workbook_id: workbook_id.to_string(),
+        hash: hash.clone(),
+        message: message.to_string(),
+    });
+
+    Ok(hash)
+}
+
+/// Commit the workbook's changes with a user-provided message.
+/// When `add_all` is set, all changes are staged first.
+#[tauri::command]
+async fn workbook_git_commit(
+    app: tauri::AppHandle,
+    workbook_id: String,
+    message: String,
+    add_all: bool,
+) -> Result<String, HandsError> {
+    let pathspecs: &[&str] = if add_all { &["*"] } else { &[] };
+    Ok(commit_workbook(&app, &workbook_id, &message, pathspecs)?)
+}
+
+/// A commit in a workbook's git history
+#[derive(Debug, Clone, Serialize)]
+struct GitCommit {
+    hash: String,
+    short_hash: String,
+    message: String,
+    author: String,
+    /// Unix timestamp (seconds) of the commit
+    timestamp: u64,
+}
+
+/// List the workbook's commits, newest first (default: 50).
+/// A repository without commits yields an empty list.
+#[tauri::command]
+async fn get_workbook_git_log(workbook_id: String, limit: Option<usize>) -> Result<Vec<GitCommit>, String> {
+    let limit = limit.unwrap_or(50);
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    let repo = git2::Repository::open(&workbook_dir)
+        .map_err(|e| format!("Failed to open git repository: {}", e))?;
+
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read HEAD: {}", e)),
+    };
+    let head_commit = head.peel_to_commit()
+        .map_err(|e| format!("Failed to read HEAD commit: {}", e))?;
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("Failed to walk git history: {}", e))?;
+    revwalk.push(head_commit.id())
+        .map_err(|e| format!("Failed to walk git history: {}", e))?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk.take(limit) {
+        let oid = oid.map_err(|e| format!("Failed to walk git history: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to read commit {}: {}", oid, e))?;
+        let short_hash = commit.as_object().short_id()
+            .ok()
+            .and_then(|buf| buf.as_str().map(String::from))
+            .unwrap_or_else(|| oid.to_string()[..7].to_string());
+
+        commits.push(GitCommit {
+            hash: oid.to_string(),
+            short_hash,
+            message: commit.message().unwrap_or_default().trim_end().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            timestamp: commit.time().seconds().max(0) as u64,
+        });
+    }
+
+    Ok(commits)
+}
+
+/// Uncommitted changes in a workbook's git repository (paths relative to the workbook)
+#[derive(Debug, Clone, Serialize)]
+struct WorkbookGitStatus {
+    staged: Vec<String>,
+    unstaged: Vec<String>,
+    untracked: Vec<String>,
+    is_clean: bool,
+}
+
+fn read_workbook_git_status(workbook_dir: &std::path::Path) -> Result<WorkbookGitStatus, String> {
+    let repo = git2::Repository::open(workbook_dir)
+        .map_err(|e| format!("Failed to open git repository: {}", e))?;
+
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let statuses = repo.statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to read git status: {}", e))?;
+
+    let mut staged = Vec::new();
+    let mut unstaged = Vec::new();
+    let mut untracked = Vec::new();
+    for entry in statuses.iter() {
+        let Some(path) = entry.path().map(String::from) else { continue };
+        let status = entry.status();
+
+        if status.is_wt_new() {
+            untracked.push(path);
+            continue;
+        }
+        if status.intersects(
+            git2::Status::INDEX_NEW
+                | git2::Status::INDEX_MODIFIED
+                | git2::Status::INDEX_DELETED
+                | git2::Status::INDEX_RENAMED
+                | git2::Status::INDEX_TYPECHANGE,
+        ) {
+            staged.push(path.clone());
+        }
+        if status.intersects(
+            git2::Status::WT_MODIFIED
+                | git2::Status::WT_DELETED
+                | git2::Status::WT_RENAMED
+                | git2::Status::WT_TYPECHANGE,
+        ) {
+            unstaged.push(path);
+        }
+    }
+
+    let is_clean = staged.is_empty() && unstaged.is_empty() && untracked.is_empty();
+    Ok(WorkbookGitStatus { staged, unstaged, untracked, is_clean })
+}
+
+/// Staged, unstaged, and untracked files in the workbook (ignored files excluded)
+#[tauri::command]
+async fn get_workbook_git_status(workbook_id: String) -> Result<WorkbookGitStatus, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    tokio::task::spawn_blocking(move || read_workbook_git_status(&workbook_dir))
+        .await
+        .map_err(|e| format!("Git status task failed: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WorkbookDirtyChanged {
+    workbook_id: String,
+    is_dirty: bool,
+}
+
+/// Skip dependencies, git history, and database data when exporting or copying a workbook
+fn is_excluded_from_copy(relative: &std::path::Path) -> bool {
+    let mut components = relative.components().map(|c| c.as_os_str());
+    match components.next() {
+        Some(first) if first == ".git" || first == "postgres" => true,
+        _ => relative.components().any(|c| c.as_os_str() == "node_modules"),
+    }
+}
+
+/// Export a workbook as a ZIP archive (without node_modules, .git, or postgres data).
+/// Returns the path of the written archive.
+#[tauri::command]
+async fn export_workbook(workbook_id: String, dest_path: String) -> Result<String, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let file = fs::File::create(&dest_path)
+            .map_err(|e| format!("Failed to create {}: {}", dest_path, e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        let walker = walkdir::WalkDir::new(&workbook_dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.path()
+                    .strip_prefix(&workbook_dir)
+                    .map(|relative| !is_excluded_from_copy(relative))
+                    .unwrap_or(false)
+            });
+
+        for entry in walker.filter_map(|e| e.ok()) {
+            let relative = entry.path().strip_prefix(&workbook_dir).unwrap_or(entry.path());
+            // ZIP entries always use forward slashes
+            let name = relative.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            if entry.file_type().is_dir() {
+                zip.add_directory(name, options)
+                    .map_err(|e| format!("Failed to add directory to archive: {}", e))?;
+            } else if entry.file_type().is_file() {
+                zip.start_file(name, options)
+                    .map_err(|e| format!("Failed to add file to archive: {}", e))?;
+                let mut source = fs::File::open(entry.path())
+                    .map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+                std::io::copy(&mut source, &mut zip)
+                    .map_err(|e| format!("Failed to write archive: {}", e))?;
+            }
+        }
+
+        zip.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
+        println!("[export] Exported workbook {} to {}", workbook_id, dest_path);
+        Ok(dest_path)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+/// Import a workbook from a ZIP archive created by `export_workbook`.
+/// The workbook gets a new ID and a fresh git repository.
+#[tauri::command]
+async fn import_workbook(app: tauri::AppHandle, zip_path: String) -> Result<Workbook, String> {
+    let archive_path = zip_path.clone();
+    let (mut archive, name) = tokio::task::spawn_blocking(move || {
+        let file = fs::File::open(&archive_path)
+            .map_err(|e| format!("Failed to open {}: {}", archive_path, e))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| format!("Invalid workbook archive: {}", e))?;
+
+        // Name the new workbook after the exported one
+        let mut content = String::new();
+        let name = archive.by_name("package.json")
+            .ok()
+            .and_then(|mut f| std::io::Read::read_to_string(&mut f, &mut content).ok())
+            .and_then(|_| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|pkg| pkg["hands"]["name"].as_str().map(String::from))
+            .unwrap_or_else(|| "Imported Workbook".to_string());
+        Ok::<_, String>((archive, name))
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))??;
+
+    let (id, now) = new_workbook_id(&name)?;
+    let workbook_dir = get_workbook_dir(&id)?;
+    fs::create_dir_all(&workbook_dir)
+        .map_err(|e| format!("Failed to create workbook directory: {}", e))?;
+
+    let imported = async {
+        let target_dir = workbook_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            // extract() rejects entries that would escape the target directory
+            archive.extract(&target_dir)
+                .map_err(|e| format!("Failed to extract archive: {}", e))?;
+            git2::Repository::init(&target_dir)
+                .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
+            Ok::<_, String>(())
+        })
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))??;
+
+        let description = read_workbook_config(&workbook_dir).and_then(|w| w.description);
+        let workbook = Workbook {
+            id,
+            name,
+            description,
+            directory: workbook_dir.to_string_lossy().to_string(),
+            created_at: now,
+            updated_at: now,
+            last_opened_at: now,
+            external_directory: false,
+            archived: false,
+            tags: Vec::new(),
+            color: None,
+        };
+
+        // Rewrites package.json hands.id to the new ID
+        save_workbook_config(&workbook)?;
+        Ok::<_, String>(workbook)
+    }
+    .await;
+
+    let workbook = match imported {
+        Ok(workbook) => workbook,
+        Err(e) => {
+            // Don't leave a partially extracted workbook behind
+            let _ = fs::remove_dir_all(&workbook_dir);
+            return Err(e);
+        }
+    };
+
+    println!("[import] Imported {} as workbook {}", zip_path, workbook.id);
+    let _ = app.emit("workbook:created", &workbook);
+
+    Ok(workbook)
+}
+
+/// Recursively copy `source_dir` into `target_dir`, skipping paths (relative to
+/// `source_dir`) for which `exclude` returns true
+fn copy_dir_filtered(
+    source_dir: &std::path::Path,
+    target_dir: &std::path::Path,
+    exclude: fn(&std::path::Path) -> bool,
+) -> Result<(), String> {
+    let walker = walkdir::WalkDir::new(source_dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.path()
+                .strip_prefix(source_dir)
+                .map(|relative| !exclude(relative))
+                .unwrap_or(false)
+        });
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        let relative = entry.path().strip_prefix(source_dir).unwrap_or(entry.path());
+        let target = target_dir.join(relative);
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target)
+                .map_err(|e| format!("Failed to copy {}: {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy a workbook as a new workbook (without postgres data, node_modules, or git history)
+#[tauri::command]
+async fn duplicate_workbook(
+    app: tauri::AppHandle,
+    source_id: String,
+    new_name: String,
+) -> Result<Workbook, String> {
+    let source_dir = get_workbook_dir(&source_id)?;
+    if !source_dir.exists() {
+        return Err(format!("Workbook {} not found", source_id));
+    }
+
+    let (id, now) = new_workbook_id(&new_name)?;
+    let workbook_dir = get_workbook_dir(&id)?;
+
+    let target_dir = workbook_dir.clone();
+    tokio::task::spawn_blocking(move || copy_dir_filtered(&source_dir, &target_dir, is_excluded_from_copy))
+        .await
+        .map_err(|e| format!("Copy task failed: {}", e))??;
+
+    git2::Repository::init(&workbook_dir)
+        .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
+
+    let description = read_workbook_config(&workbook_dir).and_then(|w| w.description);
+    let workbook = Workbook {
+        id,
+        name: new_name,
+        description,
+        directory: workbook_dir.to_string_lossy().to_string(),
+        created_at: now,
+        updated_at: now,
+        last_opened_at: now,
+        external_directory: false,
+        archived: false,
+        tags: Vec::new(),
+        color: None,
+    };
+
+    save_workbook_config(&workbook)?;
+
+    println!("[duplicate] Duplicated workbook {} as {}", source_id, workbook.id);
+    let _ = app.emit("workbook:created", &workbook);
+
+    Ok(workbook)
+}
+
+/// Skip dependencies and database data (but keep git history) when importing a directory
+fn is_excluded_from_import(relative: &std::path::Path) -> bool {
+    let mut components = relative.components().map(|c| c.as_os_str());
+    match components.next() {
+        Some(first) if first == "postgres" => true,
+        _ => relative.components().any(|c| c.as_os_str() == "node_modules"),
+    }
+}
+
+/// Copy an existing project directory into ~/.hands/<new-id> as a new workbook.
+/// The name defaults to package.json (`hands.name`, then `name`) or the directory
+/// name. Projects without Hands metadata are initialized from the default
+/// template; an existing git repository is kept as-is.
+#[tauri::command]
+async fn import_workbook_from_directory(
+    app: tauri::AppHandle,
+    src_dir: String,
+    name: Option<String>,
+) -> Result<Workbook, HandsError> {
+    let source_dir = PathBuf::from(&src_dir)
+        .canonicalize()
+        .map_err(|e| format!("Directory {} not found: {}", src_dir, e))?;
+    if !source_dir.is_dir() {
+        return Err(format!("{} is not a directory", src_dir).into());
+    }
+
+    let package: Option<serde_json::Value> = fs::read_to_string(source_dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let has_hands_config = package.as_ref().is_some_and(|pkg| pkg.get("hands").is_some());
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| {
+            let pkg = package.as_ref()?;
+            pkg["hands"]["name"].as_str().or_else(|| pkg["name"].as_str()).map(String::from)
+        })
+        .or_else(|| source_dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Imported Workbook".to_string());
+
+    let (id, now) = new_workbook_id(&name)?;
+    let workbook_dir = get_workbook_dir(&id)?;
+    if workbook_dir.exists() {
+        return Err(format!("Workbook {} already exists", id).into());
+    }
+
+    // Copying into a directory inside the source would copy the copy as it grows
+    let resolved_target = workbook_dir
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .map(|parent| parent.join(&id))
+        .unwrap_or_else(|| workbook_dir.clone());
+    if resolved_target.starts_with(&source_dir) {
+        return Err(format!("Cannot import {}: it contains the workbooks directory", src_dir).into());
+    }
+
+    let imported = async {
+        let target_dir = workbook_dir.clone();
+        tokio::task::spawn_blocking(move || copy_dir_filtered(&source_dir, &target_dir, is_excluded_from_import))
+            .await
+            .map_err(|e| format!("Copy task failed: {}", e))??;
+
+        // Keep the project's history if it has one
+        if git2::Repository::open(&workbook_dir).is_err() {
+            git2::Repository::init(&workbook_dir)
+                .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
+        }
+
+        if !has_hands_config {
+            init_workbook(&app, &workbook_dir, &name, None, &[])?;
+        }
+
+        let existing = read_workbook_config(&workbook_dir);
+        let workbook = Workbook {
+            id,
+            name,
+            description: existing.as_ref().and_then(|w| w.description.clone()),
+            directory: workbook_dir.to_string_lossy().to_string(),
+            created_at: now,
+            updated_at: now,
+            last_opened_at: now,
+            external_directory: false,
+            archived: false,
+            tags: existing.as_ref().map(|w| w.tags.clone()).unwrap_or_default(),
+            color: existing.and_then(|w| w.color),
+        };
+
+        // Rewrites package.json hands.id to the new ID
+        save_workbook_config(&workbook)?;
+        Ok::<_, HandsError>(workbook)
+    }
+    .await;
+
+    let workbook = match imported {
+        Ok(workbook) => workbook,
+        Err(e) => {
+            // Don't leave a half-copied workbook behind
+            let _ = fs::remove_dir_all(&workbook_dir);
+            return Err(e);
+        }
+    };
+
+    println!("[import] Imported directory {} as workbook {}", src_dir, workbook.id);
+    let _ = app.emit("workbook:created", &workbook);
+
+    Ok(workbook)
+}
+
+/// Top-level files and directories created by `hands init`
+/// (see `initWorkbook` in packages/workbook-server/src/config)
+const EXPECTED_WORKBOOK_ENTRIES: &[&str] = &[
+    "package.json",
+    "tsconfig.json",
+    ".gitignore",
+    "pages",
+    "migrations",
+    "lib",
+    "ui",
+];
+
+/// Local database state persisted by the dev runtime
+const WORKBOOK_DATA_DIR: &str = ".hands/db";
+
+/// Result of `check_workbook_integrity`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkbookIntegrityReport {
+    /// package.json parses and has a `hands` section
+    pub package_json_valid: bool,
+    pub git_repo_valid: bool,
+    pub data_dir_exists: bool,
+    /// Expected top-level entries that are missing
+    pub missing_files: Vec<String>,
+    pub total_size_bytes: u64,
+}
+
+/// Diagnose a workbook directory (e.g. after a crash or manual edits)
+#[tauri::command]
+async fn check_workbook_integrity(workbook_id: String) -> Result<WorkbookIntegrityReport, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let package_json_valid = fs::read_to_string(workbook_dir.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .map(|package| package.get("hands").is_some_and(|h| h.is_object()))
+            .unwrap_or(false);
+
+        let git_repo_valid = git2::Repository::open(&workbook_dir).is_ok();
+        let data_dir_exists = workbook_dir.join(WORKBOOK_DATA_DIR).is_dir();
+
+        let missing_files = EXPECTED_WORKBOOK_ENTRIES
+            .iter()
+            .filter(|entry| !workbook_dir.join(entry).exists())
+            .map(|entry| entry.to_string())
+            .collect();
+
+        let total_size_bytes = walkdir::WalkDir::new(&workbook_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| entry.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum();
+
+        WorkbookIntegrityReport {
+            package_json_valid,
+            git_repo_valid,
+            data_dir_exists,
+            missing_files,
+            total_size_bytes,
+        }
+    })
+    .await
+    .map_err(|e| format!("Integrity check failed: {}", e))
+}
+
+/// Result of `get_workbook_disk_usage`. Every file counts toward exactly one category.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiskUsageReport {
+    pub total_bytes: u64,
+    /// Local database state (`.hands/db`)
+    pub data_dir_bytes: u64,
+    /// Git history (`.git`, including pack files)
+    pub git_dir_bytes: u64,
+    pub postgres_dir_bytes: u64,
+    /// Everything else (sources, assets, dependencies)
+    pub source_bytes: u64,
+}
+
+fn measure_disk_usage(workbook_dir: &std::path::Path) -> DiskUsageReport {
+    let data_dir = workbook_dir.join(WORKBOOK_DATA_DIR);
+    let git_dir = workbook_dir.join(".git");
+    let postgres_dir = workbook_dir.join("postgres");
+
+    let mut report = DiskUsageReport::default();
+    for entry in walkdir::WalkDir::new(workbook_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else { continue };
+        let size = metadata.len();
+        let path = entry.path();
+
+        let bucket = if path.starts_with(&git_dir) {
+            &mut report.git_dir_bytes
+        } else if path.starts_with(&data_dir) {
+            &mut report.data_dir_bytes
+        } else if path.starts_with(&postgres_dir) {
+            &mut report.postgres_dir_bytes
+        } else {
+            &mut report.source_bytes
+        };
+        *bucket += size;
+        report.total_bytes += size;
+    }
+
+    report
+}
+
+/// Break down a workbook's disk usage by category
+#[tauri::command]
+async fn get_workbook_disk_usage(workbook_id: String) -> Result<DiskUsageReport, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+
+    tokio::task::spawn_blocking(move || measure_disk_usage(&workbook_dir))
+        .await
+        .map_err(|e| format!("Disk usage scan failed: {}", e))
+}
+
+/// Total disk usage in bytes for every workbook, keyed by workbook id
+#[tauri::command]
+async fn get_all_workbooks_disk_usage() -> Result<HashMap<String, u64>, String> {
+    let workbooks = scan_workbooks(true).await?;
+
+    tokio::task::spawn_blocking(move || {
+        workbooks
+            .into_iter()
+            .map(|workbook| {
+                let total = measure_disk_usage(std::path::Path::new(&workbook.directory)).total_bytes;
+                (workbook.id, total)
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Disk usage scan failed: {}", e))
+}
+
+/// Files larger than this are skipped by workbook search
+const MAX_SEARCH_FILE_BYTES: u64 = 1024 * 1024;
+/// Default number of hits returned per workbook
+const DEFAULT_SEARCH_MAX_RESULTS: usize = 100;
+/// Longest line preview returned with a hit
+const SEARCH_PREVIEW_CHARS: usize = 200;
+
+/// A line matching a workbook file search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// Path relative to the workbook directory, with forward slashes
+    pub file: String,
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based character column of the first match on the line
+    pub column: u32,
+    pub preview: String,
+}
+
+/// Case-insensitive substring search over a workbook's text files
+/// (skipping node_modules, .git, postgres data, and files over 1 MB).
+/// `file_glob` restricts the search to matching relative paths.
+fn search_files(
+    workbook_dir: &std::path::Path,
+    query: &str,
+    max_results: usize,
+    file_glob: Option<&globset::GlobMatcher>,
+) -> Vec<SearchHit> {
+    let query = query.to_lowercase();
+    let mut hits = Vec::new();
+    if query.is_empty() || max_results == 0 {
+        return hits;
+    }
+
+    let walker = walkdir::WalkDir::new(workbook_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.path()
+                .strip_prefix(workbook_dir)
+                .map(|relative| !is_excluded_from_copy(relative))
+                .unwrap_or(false)
+        });
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file()
+            || !entry.metadata().is_ok_and(|m| m.len() <= MAX_SEARCH_FILE_BYTES)
+        {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(workbook_dir).unwrap_or(entry.path());
+        let file = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if file_glob.is_some_and(|glob| !glob.is_match(&file)) {
+            continue;
+        }
+
+        // Non-UTF-8 content is treated as binary and skipped
+        let Ok(content) = fs::read_to_string(entry.path()) else { continue };
+
+        for (index, line) in content.lines().enumerate() {
+            let lowered = line.to_lowercase();
+            let Some(byte_offset) = lowered.find(&query) else { continue };
+
+            hits.push(SearchHit {
+                file: file.clone(),
+                line: index as u32 + 1,
+                column: lowered[..byte_offset].chars().count() as u32 + 1,
+                preview: line.trim().chars().take(SEARCH_PREVIEW_CHARS).collect(),
+            });
+            if hits.len() >= max_results {
+                return hits;
+            }
+        }
+    }
+
+    hits
+}
+
+/// Search a workbook's source files for `query` (default: 100 results)
+#[tauri::command]
+async fn search_workbook_files(
+    workbook_id: String,
+    query: String,
+    max_results: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+    let max_results = max_results.unwrap_or(DEFAULT_SEARCH_MAX_RESULTS);
+
+    tokio::task::spawn_blocking(move || search_files(&workbook_dir, &query, max_results, None))
+        .await
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Search every (non-archived) workbook. Only workbooks with hits are included.
+#[tauri::command]
+async fn search_all_workbooks(
+    query: String,
+    max_results_per_workbook: Option<usize>,
+) -> Result<HashMap<String, Vec<SearchHit>>, String> {
+    let workbooks = scan_workbooks(false).await?;
+    let max_results = max_results_per_workbook.unwrap_or(DEFAULT_SEARCH_MAX_RESULTS);
+
+    tokio::task::spawn_blocking(move || {
+        workbooks
+            .into_iter()
+            .filter_map(|workbook| {
+                let hits = search_files(std::path::Path::new(&workbook.directory), &query, max_results, None);
+                (!hits.is_empty()).then_some((workbook.id, hits))
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Default total number of hits returned by a global search
+const DEFAULT_GLOBAL_SEARCH_MAX_TOTAL: usize = 500;
+
+/// A line matching a search across all workbooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalSearchHit {
+    pub workbook_id: String,
+    pub workbook_name: String,
+    /// Path relative to the workbook directory, with forward slashes
+    pub file: String,
+    /// 1-based line number
+    pub line: u32,
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalSearchResults {
+    pub hits: Vec<GlobalSearchHit>,
+    pub total_workbooks_searched: usize,
+    /// True if more than `max_total` hits were found
+    pub truncated: bool,
+}
+
+/// Search the files of every (non-archived) workbook in parallel, optionally
+/// restricted to paths matching `file_glob` (e.g. `**/*.ts`). Results are
+/// capped at `max_total` hits (default: 500).
+#[tauri::command]
+async fn search_all_workbooks_files(
+    query: String,
+    file_glob: Option<String>,
+    max_total: Option<usize>,
+) -> Result<GlobalSearchResults, String> {
+    let file_glob = file_glob
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(|pattern| {
+            globset::Glob::new(pattern.trim())
+                .map(|glob| glob.compile_matcher())
+                .map_err(|e| format!("Invalid file pattern {}: {}", pattern, e))
+        })
+        .transpose()?;
+    let max_total = max_total.unwrap_or(DEFAULT_GLOBAL_SEARCH_MAX_TOTAL);
+    let workbooks = scan_workbooks(false).await?;
+    let total_workbooks_searched = workbooks.len();
+
+    // One extra hit per workbook tells us whether the results were truncated
+    let searches = workbooks.into_iter().map(|workbook| {
+        let query = query.clone();
+        let file_glob = file_glob.clone();
+        tokio::task::spawn_blocking(move || {
+            let hits = search_files(
+                std::path::Path::new(&workbook.directory),
+                &query,
+                max_total + 1,
+                file_glob.as_ref(),
+            );
+            (workbook, hits)
+        })
+    });
+
+    let mut hits = Vec::new();
+    let mut truncated = false;
+    for result in futures_util::future::join_all(searches).await {
+        let (workbook, workbook_hits) = result.map_err(|e| format!("Search failed: {}", e))?;
+        for hit in workbook_hits {
+            if hits.len() >= max_total {
+                truncated = true;
+                break;
+            }
+            hits.push(GlobalSearchHit {
+                workbook_id: workbook.id.clone(),
+                workbook_name: workbook.name.clone(),
+                file: hit.file,
+                line: hit.line,
+                preview: hit.preview,
+            });
+        }
+    }
+
+    Ok(GlobalSearchResults {
+        hits,
+        total_workbooks_searched,
+        truncated,
+    })
+}
+
+// Runtime status from the runtime server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeStatus {
+    #[serde(rename = "workbookId")]
+    pub workbook_id: String,
+    #[serde(rename = "workbookDir")]
+    pub workbook_dir: String,
+    #[serde(rename = "runtimePort")]
+    pub runtime_port: u16,
+    #[serde(rename = "startedAt")]
+    pub started_at: u64,
+    pub services: RuntimeServices,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeServices {
+    pub postgres: ServiceStatus,
+    pub worker: ServiceStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub up: bool,
+    pub port: u16,
     pub pid: Option<u32>,
     pub error: Option<String>,
 }
 
-// Workbook server ready message from stdout
-#[derive(Debug, Clone, Deserialize)]
-struct WorkbookServerReady {
-    #[serde(rename = "type")]
-    msg_type: String,
-    #[serde(rename = "runtimePort")]
-    runtime_port: u16,
+// Workbook server ready message from stdout
+#[derive(Debug, Clone, Deserialize)]
+struct WorkbookServerReady {
+    #[serde(rename = "type")]
+    msg_type: String,
+    #[serde(rename = "runtimePort")]
+    runtime_port: u16,
+    #[serde(rename = "postgresPort", default)]
+    postgres_port: u16,
+    #[serde(rename = "workerPort", default)]
+    worker_port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevServerStatus {
+    pub running: bool,
+    pub workbook_id: String,
+    pub directory: String,
+    pub runtime_port: u16,
+    pub message: String,
+    /// Time until the monitor restarts a crashed runtime (backing off after repeated crashes)
+    pub next_restart_in_ms: Option<u64>,
+    /// OS whose port cleanup path is used (`lsof`/`kill` or `netstat`/`taskkill` on windows)
+    pub target_os: String,
+}
+
+/// PIDs of processes with a socket on `port` (lsof)
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn pids_on_port(port: u16) -> Vec<u32> {
+    match std::process::Command::new("lsof")
+        .args(["-ti", &format!(":{}", port)])
+        .output()
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|pid| pid.trim().parse().ok())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// PIDs of processes with a socket on `port` (`netstat -ano`)
+#[cfg(target_os = "windows")]
+fn pids_on_port(port: u16) -> Vec<u32> {
+    let Ok(output) = std::process::Command::new("netstat").arg("-ano").output() else {
+        return Vec::new();
+    };
+
+    // Rows look like: `TCP  127.0.0.1:55000  0.0.0.0:0  LISTENING  1234`
+    let suffix = format!(":{}", port);
+    let mut pids: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let local = columns.get(1)?;
+            if !local.ends_with(&suffix) {
+                return None;
+            }
+            columns.last()?.parse().ok()
+        })
+        .filter(|pid| *pid != 0)
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    pids
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn pids_on_port(_port: u16) -> Vec<u32> {
+    Vec::new()
+}
+
+/// Forcefully terminate a process
+fn force_kill_pid(pid: u32) {
+    #[cfg(target_os = "windows")]
+    let _ = std::process::Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .output();
+
+    #[cfg(not(target_os = "windows"))]
+    let _ = std::process::Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .output();
+}
+
+/// Kill processes listening on a specific port
+fn kill_processes_on_port(port: u16) {
+    for pid in pids_on_port(port) {
+        println!("[cleanup] Killing process {} on port {}", pid, port);
+        force_kill_pid(pid);
+    }
+}
+
+/// How long to wait for a conflicting process to release its port
+const PORT_FREE_TIMEOUT: Duration = Duration::from_secs(5);
+const PORT_FREE_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A runtime port that stayed in use, with the process holding it
+#[derive(Debug, Clone, Serialize)]
+pub struct PortConflictError {
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
+
+/// Why a workbook runtime failed to start. Serialized with a `kind` tag so the
+/// UI can name the process blocking a port instead of showing a bind error.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum RuntimeStartError {
+    PortConflict(PortConflictError),
+    Failed { message: String },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DevServerStatus {
-    pub running: bool,
-    pub workbook_id: String,
-    pub directory: String,
-    pub runtime_port: u16,
-    pub message: String,
+impl std::fmt::Display for RuntimeStartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeStartError::PortConflict(conflict) => match (&conflict.process_name, conflict.pid) {
+                (Some(name), Some(pid)) => write!(f, "Port {} is in use by {} (pid {})", conflict.port, name, pid),
+                _ => write!(f, "Port {} is in use", conflict.port),
+            },
+            RuntimeStartError::Failed { message } => f.write_str(message),
+        }
+    }
 }
 
-/// Kill processes listening on a specific port
-fn kill_processes_on_port(port: u16) {
-    if let Ok(output) = std::process::Command::new("lsof")
-        .args(["-ti", &format!(":{}", port)])
+impl From<String> for RuntimeStartError {
+    fn from(message: String) -> Self {
+        RuntimeStartError::Failed { message }
+    }
+}
+
+impl From<&str> for RuntimeStartError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// PID and command name of the process listening on `port`, via lsof
+#[cfg(not(target_os = "windows"))]
+fn port_owner(port: u16) -> (Option<u32>, Option<String>) {
+    let Ok(output) = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fpc"])
         .output()
-    {
-        if output.status.success() {
-            let pids = String::from_utf8_lossy(&output.stdout);
-            for pid in pids.lines() {
-                if let Ok(pid_num) = pid.trim().parse::<i32>() {
-                    println!("[cleanup] Killing process {} on port {}", pid_num, port);
-                    let _ = std::process::Command::new("kill")
-                        .args(["-9", &pid_num.to_string()])
-                        .output();
-                }
-            }
+    else {
+        return (None, None);
+    };
+
+    // Field output: one `p<pid>` line followed by `c<command>` per process
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let pid = stdout.lines().find_map(|l| l.strip_prefix('p')).and_then(|p| p.parse().ok());
+    let name = stdout.lines().find_map(|l| l.strip_prefix('c')).map(String::from);
+    (pid, name)
+}
+
+/// PID and image name of the process listening on `port`, via netstat and tasklist
+#[cfg(target_os = "windows")]
+fn port_owner(port: u16) -> (Option<u32>, Option<String>) {
+    let Ok(output) = std::process::Command::new("netstat").args(["-ano", "-p", "TCP"]).output() else {
+        return (None, None);
+    };
+
+    // Rows look like: `TCP  127.0.0.1:55000  0.0.0.0:0  LISTENING  1234`
+    let suffix = format!(":{}", port);
+    let pid: Option<u32> = String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if columns.len() < 5 || columns[3] != "LISTENING" || !columns[1].ends_with(&suffix) {
+            return None;
+        }
+        columns[4].parse().ok()
+    });
+    let Some(pid) = pid else {
+        return (None, None);
+    };
+
+    // CSV row: `"node.exe","1234","Console","1","42,000 K"`
+    let name = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()
+        .and_then(|output| {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let name = stdout.lines().next()?.split(',').next()?.trim_matches('"').to_string();
+            (!name.is_empty() && !name.starts_with("INFO:")).then_some(name)
+        });
+    (Some(pid), name)
+}
+
+async fn is_port_free(port: u16) -> bool {
+    tokio::net::TcpListener::bind(("127.0.0.1", port)).await.is_ok()
+}
+
+/// Make sure `port` can be bound, killing whatever holds it and waiting up to
+/// `PORT_FREE_TIMEOUT` for it to be released. Processes in `owned_pids` (our
+/// own runtimes) are never killed; a port held by one is reported as a conflict.
+async fn ensure_port_free(port: u16, owned_pids: &HashSet<u32>) -> Result<(), PortConflictError> {
+    if is_port_free(port).await {
+        return Ok(());
+    }
+
+    let (pid, process_name) = port_owner(port);
+    let holders = pids_on_port(port);
+    if pid.is_some_and(|pid| owned_pids.contains(&pid)) || holders.iter().any(|pid| owned_pids.contains(pid)) {
+        return Err(PortConflictError { port, pid, process_name });
+    }
+
+    println!("[preflight] Port {} in use by {:?} (pid {:?}), killing", port, process_name, pid);
+    for holder in holders {
+        force_kill_pid(holder);
+    }
+
+    let deadline = tokio::time::Instant::now() + PORT_FREE_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(PORT_FREE_RETRY_INTERVAL).await;
+        if is_port_free(port).await {
+            return Ok(());
         }
     }
+
+    // Report whoever holds the port now (it may have been respawned)
+    let (current_pid, current_name) = port_owner(port);
+    Err(PortConflictError {
+        port,
+        pid: current_pid.or(pid),
+        process_name: current_name.or(process_name),
+    })
 }
 
 /// Force cleanup any stale runtime lockfile and processes
@@ -528,48 +2334,267 @@ async fn force_cleanup_workbook_server() {
     }
 }
 
+fn runtime_memory_limit_key(workbook_id: &str) -> String {
+    format!("runtime_memory_limits.{}", workbook_id)
+}
+
+/// Memory limit for a workbook's runtime (`settings.json["runtime_memory_limits.<id>"]`), if set
+fn load_runtime_memory_limit(app: &tauri::AppHandle, workbook_id: &str) -> Option<u64> {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(runtime_memory_limit_key(workbook_id)))
+        .and_then(|v| v.as_u64())
+        .filter(|&mb| mb > 0)
+}
+
+/// Set a workbook runtime's memory limit in MB (0 removes it). Applies from the next runtime start.
+#[tauri::command]
+async fn set_runtime_memory_limit(app: tauri::AppHandle, workbook_id: String, limit_mb: u64) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    if limit_mb == 0 {
+        store.delete(runtime_memory_limit_key(&workbook_id));
+    } else {
+        store.set(runtime_memory_limit_key(&workbook_id), serde_json::json!(limit_mb));
+    }
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
+/// Settings key for the runtime max uptime in hours
+const RUNTIME_MAX_UPTIME_KEY: &str = "runtime_max_uptime_hours";
+
+/// Restart policy for the runtime monitor, with `max_uptime_hours` from
+/// `settings.json["runtime_max_uptime_hours"]` (unset or 0 disables it)
+fn load_restart_policy(app: &tauri::AppHandle) -> RestartPolicy {
+    let max_uptime_hours = app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(RUNTIME_MAX_UPTIME_KEY))
+        .and_then(|v| v.as_u64())
+        .filter(|&hours| hours > 0)
+        .map(|hours| hours.min(u32::MAX as u64) as u32);
+    RestartPolicy { max_uptime_hours, ..RestartPolicy::default() }
+}
+
+/// Restart runtimes that have been up longer than `hours` (0 disables).
+/// Runtimes with active jobs are restarted once their jobs finish.
+#[tauri::command]
+async fn set_runtime_max_uptime(app: tauri::AppHandle, hours: u32) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    if hours == 0 {
+        store.delete(RUNTIME_MAX_UPTIME_KEY);
+    } else {
+        store.set(RUNTIME_MAX_UPTIME_KEY, serde_json::json!(hours));
+    }
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
+/// Runtime log files kept per workbook (oldest are deleted first)
+const MAX_RUNTIME_LOG_FILES: usize = 5;
+/// Default number of lines returned by `get_runtime_log`
+const DEFAULT_RUNTIME_LOG_LINES: u32 = 200;
+
+/// Directory of a workbook's runtime stderr logs, under the app data directory so
+/// logs stay out of the workbook (and its `.hands/` build output)
+fn runtime_log_dir(app: &tauri::AppHandle, workbook_id: &str) -> Result<PathBuf, String> {
+    let data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(data_dir.join("logs").join(workbook_id))
+}
+
+/// `runtime-<timestamp>.log` files in a log directory, oldest first
+fn runtime_log_files(log_dir: &std::path::Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(log_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("runtime-") && n.ends_with(".log"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Create a new log file for a runtime run, deleting the oldest so at most
+/// `MAX_RUNTIME_LOG_FILES` remain
+fn open_runtime_log(log_dir: &std::path::Path) -> std::io::Result<(PathBuf, fs::File)> {
+    fs::create_dir_all(log_dir)?;
+
+    let existing = runtime_log_files(log_dir);
+    let excess = (existing.len() + 1).saturating_sub(MAX_RUNTIME_LOG_FILES);
+    for old in existing.iter().take(excess) {
+        if let Err(e) = fs::remove_file(old) {
+            eprintln!("[runtime] Failed to remove old log {}: {}", old.display(), e);
+        }
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = log_dir.join(format!("runtime-{}.log", timestamp));
+    let file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    Ok((path, file))
+}
+
+/// Write a runtime's stderr to its log file and emit each line as `runtime:log-line`
+fn capture_runtime_stderr(
+    app: &tauri::AppHandle,
+    workbook_id: &str,
+    stderr: tokio::process::ChildStderr,
+) {
+    let opened = runtime_log_dir(app, workbook_id)
+        .and_then(|dir| open_runtime_log(&dir).map_err(|e| e.to_string()));
+    let mut log_file = match opened {
+        Ok((path, file)) => {
+            println!("[runtime] Logging {} to {}", workbook_id, path.display());
+            Some(tokio::fs::File::from_std(file))
+        }
+        Err(e) => {
+            eprintln!("[runtime] Failed to open log file for {}: {}", workbook_id, e);
+            None
+        }
+    };
+
+    let app = app.clone();
+    let workbook_id = workbook_id.to_string();
+    tokio::spawn(async move {
+        use tokio::io::AsyncWriteExt;
+
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            eprintln!("[runtime] {}", line);
+
+            let write_failed = match log_file.as_mut() {
+                Some(file) => file.write_all(format!("{}\n", line).as_bytes()).await.is_err(),
+                None => false,
+            };
+            if write_failed {
+                eprintln!("[runtime] Failed to write log for {}, disabling file logging", workbook_id);
+                log_file = None;
+            }
+
+            let _ = app.emit("runtime:log-line", serde_json::json!({
+                "workbook_id": workbook_id,
+                "line": line,
+            }));
+        }
+    });
+}
+
 /// Internal helper to spawn and wait for runtime ready
 async fn spawn_workbook_server(
+    app: &tauri::AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    workbook_id: &str,
+    directory: &str,
+    env_vars: HashMap<String, String>,
+) -> Result<RuntimeInfo, RuntimeStartError> {
+    // Reserve a dynamic port so concurrent runtimes don't fight over one
+    let (runtime_port, owned_pids) = {
+        let mut state_guard = state.lock().await;
+        let port = state_guard
+            .runtime_manager
+            .allocate_available_port()
+            .ok_or("No free runtime port available")?;
+        (port, state_guard.runtime_manager.owned_pids())
+    };
+
+    let result =
+        spawn_workbook_server_on_port(app, workbook_id, directory, env_vars, runtime_port, &owned_pids).await;
+    if result.is_err() {
+        state.lock().await.runtime_manager.release_port(runtime_port);
+    }
+    result
+}
+
+async fn spawn_workbook_server_on_port(
+    app: &tauri::AppHandle,
     workbook_id: &str,
     directory: &str,
     env_vars: HashMap<String, String>,
-) -> Result<(Child, u16), String> {
+    runtime_port: u16,
+    owned_pids: &HashSet<u32>,
+) -> Result<RuntimeInfo, RuntimeStartError> {
     // Force cleanup any stale processes before starting
     force_cleanup_workbook_server().await;
 
+    // Fail with the blocking process rather than a cryptic bind error from the runtime
+    ensure_port_free(runtime_port, owned_pids)
+        .await
+        .map_err(RuntimeStartError::PortConflict)?;
+
+    // Per-workbook overrides shadow the global keys
+    let mut env_vars = env_vars;
+    let overrides = load_workbook_env(app, workbook_id).env;
+    if !overrides.is_empty() {
+        println!("[env] Applying {} workbook env overrides for {}", overrides.len(), workbook_id);
+    }
+    env_vars.extend(overrides);
+
     // Get runtime path - in dev this is packages/runtime in monorepo
     // The compiled sidecar needs this since import.meta.dir doesn't work in compiled binaries
     let runtime_path = get_runtime_path();
 
     // Start hands-runtime process - run from the workbook directory
-    let mut child = sidecar::command(sidecar::Sidecar::WorkbookServer)
+    let mut command = sidecar::command(app, sidecar::Sidecar::WorkbookServer);
+    command
         .args([
             &format!("--workbook-id={}", workbook_id),
             &format!("--workbook-dir={}", directory),
+            &format!("--port={}", runtime_port),
         ])
         .env("HANDS_RUNTIME_PATH", &runtime_path)
+        .env("HANDS_PORT_PREFIX", port_prefix(app).to_string())
         .envs(&env_vars)
         .current_dir(directory)
         .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
-        .kill_on_drop(true)
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // Enforced by the runtime monitor, which polls RSS
+    let memory_limit_mb = load_runtime_memory_limit(app, workbook_id);
+    if let Some(limit_mb) = memory_limit_mb {
+        println!("[runtime] Limiting {} to {} MB", workbook_id, limit_mb);
+    }
+
+    let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to start runtime: {}", e))?;
 
+    if let Some(stderr) = child.stderr.take() {
+        capture_runtime_stderr(app, workbook_id, stderr);
+    }
+
     // Read stdout to get the ready message with port info
     let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
     let mut reader = BufReader::new(stdout).lines();
+    let stdout_lines = runtime_manager::new_output_log();
 
     // Wait for ready message (with timeout)
     let timeout_result = tokio::time::timeout(Duration::from_secs(60), async {
         while let Ok(Some(line)) = reader.next_line().await {
             println!("[runtime] {}", line);
+            runtime_manager::push_output_line(&stdout_lines, line.clone());
 
             // Try to parse as ready message
             if line.starts_with('{') {
                 if let Ok(ready) = serde_json::from_str::<WorkbookServerReady>(&line) {
                     if ready.msg_type == "ready" {
-                        return Ok((ready.runtime_port, reader));
+                        let ports = RuntimePorts {
+                            runtime_port: ready.runtime_port,
+                            postgres_port: ready.postgres_port,
+                            worker_port: ready.worker_port,
+                        };
+                        return Ok((ports, reader));
                     }
                 }
             }
@@ -578,64 +2603,151 @@ async fn spawn_workbook_server(
     }).await;
 
     match timeout_result {
-        Ok(Ok((runtime_port, mut reader))) => {
-            // Continue reading stdout in background to show Vite logs
+        Ok(Ok((ports, mut reader))) => {
+            let mut runtime = RuntimeInfo::new(
+                workbook_id.to_string(),
+                directory.to_string(),
+                child,
+                ports,
+            );
+            runtime.memory_limit_mb = memory_limit_mb;
+            runtime.stdout_lines = stdout_lines.clone();
+
+            // Continue reading stdout in background to show Vite logs,
+            // recording request log lines for the network inspector
+            let network_log = runtime.network_log.clone();
+            let app = app.clone();
+            let log_workbook_id = workbook_id.to_string();
             tokio::spawn(async move {
                 while let Ok(Some(line)) = reader.next_line().await {
                     println!("[runtime] {}", line);
+                    if let Some(request) = runtime_manager::parse_request_line(&line) {
+                        let _ = app.emit("runtime:network-request", serde_json::json!({
+                            "workbook_id": log_workbook_id,
+                            "request": request,
+                        }));
+                        runtime_manager::push_network_request(&network_log, request);
+                    }
+                    runtime_manager::push_output_line(&stdout_lines, line);
                 }
             });
-            Ok((child, runtime_port))
+
+            Ok(runtime)
         }
         Ok(Err(e)) => {
             let _ = child.kill().await;
-            Err(e)
+            Err(e.into())
         }
         Err(_) => {
             let _ = child.kill().await;
-            Err("Timeout waiting for runtime to start".to_string())
+            Err("Timeout waiting for runtime to start".into())
         }
     }
 }
 
-/// Start runtime monitoring task that auto-restarts crashed runtimes
-fn start_workbook_server_monitor(state: Arc<Mutex<AppState>>, app: tauri::AppHandle) {
-    const MAX_RESTARTS: u32 = 5;
-    const RESTART_DELAY_MS: u64 = 2000;
+/// How often the monitor checks runtimes when no restart is due sooner
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the monitor emits `runtime:metrics`
+const RUNTIME_METRICS_INTERVAL: Duration = Duration::from_secs(30);
 
+/// Start runtime monitoring task that auto-restarts crashed runtimes.
+/// Restarts back off exponentially (see `RestartPolicy::backoff_delay`) and are
+/// queued in the runtime manager so a long delay doesn't stall the monitor.
+fn start_workbook_server_monitor(state: Arc<Mutex<AppState>>, app: tauri::AppHandle) {
     tauri::async_runtime::spawn(async move {
+        let mut quit_rx = state.lock().await.quit_tx.subscribe();
+        let mut last_metrics = std::time::Instant::now();
+        // Last known dirty state of each running workbook, to emit only on transitions
+        let mut dirty: HashMap<String, bool> = HashMap::new();
+
         loop {
-            tokio::time::sleep(Duration::from_secs(5)).await;
+            // Wake early if a scheduled restart is due before the next regular check
+            let wait = {
+                let state_guard = state.lock().await;
+                state_guard.runtime_manager.next_restart_at()
+                    .map(|at| at.saturating_duration_since(std::time::Instant::now()).min(MONITOR_INTERVAL))
+                    .unwrap_or(MONITOR_INTERVAL)
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = quit_rx.recv() => {
+                    // Don't restart runtimes that are being torn down on quit
+                    println!("[monitor] Quit requested, stopping runtime monitor");
+                    break;
+                }
+            }
 
+            // Reloaded each pass so settings changes apply without restarting the app
+            let policy = load_restart_policy(&app);
             let mut state_guard = state.lock().await;
+            let AppState { runtime_manager, job_registry, .. } = &mut *state_guard;
 
-            // Collect workbooks that need restart
-            let mut to_restart: Vec<(String, String, u32)> = Vec::new();
+            // Collect workbooks that need restart: (workbook_id, restart count of the new runtime, delay)
+            let mut to_schedule: Vec<(String, u32, Duration)> = Vec::new();
 
-            for (workbook_id, runtime) in state_guard.workbook_servers.iter_mut() {
+            for (workbook_id, runtime) in runtime_manager.iter_mut() {
                 // Check if process has exited
-                match runtime.child.try_wait() {
+                match runtime.process.try_wait() {
                     Ok(Some(status)) => {
-                        // Process exited
-                        if runtime.restart_count < MAX_RESTARTS {
+                        // A runtime that was stable for a while gets a fresh restart budget,
+                        // so occasional transient crashes don't add up to giving up
+                        let restart_count = if runtime.uptime() >= policy.stable_uptime {
+                            0
+                        } else {
+                            runtime.restart_count
+                        };
+
+                        if restart_count < policy.max_restarts {
+                            let delay = policy.backoff_delay(restart_count);
                             println!(
-                                "[monitor] Runtime for {} exited with {:?}, will restart (attempt {}/{})",
-                                workbook_id, status, runtime.restart_count + 1, MAX_RESTARTS
+                                "[monitor] Runtime for {} exited with {:?}, restarting in {:?} (attempt {}/{})",
+                                workbook_id, status, delay, restart_count + 1, policy.max_restarts
                             );
-                            to_restart.push((
-                                workbook_id.clone(),
-                                runtime.directory.clone(),
-                                runtime.restart_count + 1,
-                            ));
+                            to_schedule.push((workbook_id.clone(), restart_count + 1, delay));
                         } else {
                             eprintln!(
                                 "[monitor] Runtime for {} exceeded max restarts ({}), giving up",
-                                workbook_id, MAX_RESTARTS
+                                workbook_id, policy.max_restarts
                             );
                         }
                     }
                     Ok(None) => {
-                        // Still running, all good
+                        // Still running - kill it if it has grown past its memory limit
+                        let rss_bytes = runtime.memory_limit_mb
+                            .and(runtime.process.id())
+                            .and_then(runtime_manager::process_rss_bytes);
+                        if let (Some(limit_mb), Some(rss_bytes)) = (runtime.memory_limit_mb, rss_bytes) {
+                            if rss_bytes > limit_mb.saturating_mul(1024 * 1024) {
+                                eprintln!(
+                                    "[monitor] Runtime for {} is using {} MB, over its {} MB limit, killing",
+                                    workbook_id, rss_bytes / (1024 * 1024), limit_mb
+                                );
+                                let _ = runtime.process.start_kill();
+                                let _ = app.emit("runtime:oom", serde_json::json!({
+                                    "workbook_id": workbook_id,
+                                    "rss_bytes": rss_bytes,
+                                    "limit_mb": limit_mb,
+                                }));
+                                if runtime.restart_count < policy.max_restarts {
+                                    let delay = policy.backoff_delay(runtime.restart_count);
+                                    to_schedule.push((workbook_id.clone(), runtime.restart_count + 1, delay));
+                                }
+                                continue;
+                            }
+                        }
+
+                        // Restart if it has been up unusually long (potential zombie),
+                        // but not while an agent job is using it - retried on a later pass
+                        if policy.exceeds_max_uptime(runtime.uptime()) && !job_registry.has_active_jobs(workbook_id) {
+                            println!(
+                                "[monitor] Runtime for {} exceeded max uptime ({:?}h), restarting",
+                                workbook_id, policy.max_uptime_hours
+                            );
+                            let _ = runtime.process.start_kill();
+                            to_schedule.push((workbook_id.clone(), runtime.restart_count, Duration::ZERO));
+                        }
                     }
                     Err(e) => {
                         eprintln!("[monitor] Error checking runtime {}: {}", workbook_id, e);
@@ -643,30 +2755,39 @@ fn start_workbook_server_monitor(state: Arc<Mutex<AppState>>, app: tauri::AppHan
                 }
             }
 
-            // Remove dead runtimes before restarting
-            for (workbook_id, _, _) in &to_restart {
-                state_guard.workbook_servers.remove(workbook_id);
+            // Move dead runtimes into the restart queue, keeping their window registrations
+            let now = std::time::Instant::now();
+            for (workbook_id, restart_count, delay) in to_schedule {
+                if let Some(runtime) = state_guard.runtime_manager.remove(&workbook_id) {
+                    state_guard.runtime_manager.schedule_restart(workbook_id, runtime_manager::PendingRestart {
+                        directory: runtime.directory,
+                        restart_count,
+                        windows: runtime.windows,
+                        crashed_at: now,
+                        restart_at: now + delay,
+                    });
+                }
             }
 
+            let due = state_guard.runtime_manager.take_due_restarts(now);
+
             // Drop lock before spawning new processes
             drop(state_guard);
 
-            // Restart crashed runtimes
-            for (workbook_id, directory, restart_count) in to_restart {
-                tokio::time::sleep(Duration::from_millis(RESTART_DELAY_MS)).await;
-
+            // Restart runtimes whose backoff has elapsed
+            for (workbook_id, pending) in due {
                 println!("[monitor] Restarting runtime for {}...", workbook_id);
 
-                let env_vars = get_api_keys_from_store(&app);
-                match spawn_workbook_server(&workbook_id, &directory, env_vars).await {
-                    Ok((child, runtime_port)) => {
+                let env_vars = get_api_keys_from_store(&app, Some(&pending.directory));
+                match spawn_workbook_server(&app, &state, &workbook_id, &pending.directory, env_vars).await {
+                    Ok(mut runtime) => {
+                        let runtime_port = runtime.runtime_port;
+                        runtime.restart_count = pending.restart_count;
+                        runtime.windows = pending.windows;
+                        runtime.last_crash_at = Some(pending.crashed_at);
+
                         let mut state_guard = state.lock().await;
-                        state_guard.workbook_servers.insert(workbook_id.clone(), WorkbookServerProcess {
-                            child,
-                            runtime_port,
-                            directory,
-                            restart_count,
-                        });
+                        state_guard.runtime_manager.insert(workbook_id.clone(), runtime);
                         println!(
                             "[monitor] Runtime restarted for {} on port {}",
                             workbook_id, runtime_port
@@ -674,24 +2795,84 @@ fn start_workbook_server_monitor(state: Arc<Mutex<AppState>>, app: tauri::AppHan
                     }
                     Err(e) => {
                         eprintln!("[monitor] Failed to restart runtime for {}: {}", workbook_id, e);
+
+                        // Failing to start counts as another crash
+                        if pending.restart_count < policy.max_restarts {
+                            let now = std::time::Instant::now();
+                            let delay = policy.backoff_delay(pending.restart_count);
+                            let mut state_guard = state.lock().await;
+                            state_guard.runtime_manager.schedule_restart(workbook_id, runtime_manager::PendingRestart {
+                                restart_count: pending.restart_count + 1,
+                                restart_at: now + delay,
+                                ..pending
+                            });
+                        } else {
+                            eprintln!(
+                                "[monitor] Runtime for {} exceeded max restarts ({}), giving up",
+                                workbook_id, policy.max_restarts
+                            );
+                        }
                     }
                 }
             }
+
+            // Ping runtimes from a snapshot (lock released during HTTP) and publish health
+            let statuses = collect_running_runtimes(&state).await;
+            let _ = app.emit("runtimes:list-updated", &statuses);
+
+            // Publish clean/dirty transitions so the UI can mark uncommitted changes
+            dirty.retain(|id, _| statuses.iter().any(|s| &s.workbook_id == id));
+            for status in &statuses {
+                let Ok(workbook_dir) = get_workbook_dir(&status.workbook_id) else { continue };
+                let git_status = tokio::task::spawn_blocking(move || read_workbook_git_status(&workbook_dir)).await;
+                let Ok(Ok(git_status)) = git_status else { continue };
+
+                let is_dirty = !git_status.is_clean;
+                if dirty.insert(status.workbook_id.clone(), is_dirty) != Some(is_dirty) {
+                    let _ = app.emit("workbook:dirty-changed", WorkbookDirtyChanged {
+                        workbook_id: status.workbook_id.clone(),
+                        is_dirty,
+                    });
+                }
+            }
+
+            // Publish resource usage for live charts
+            if last_metrics.elapsed() >= RUNTIME_METRICS_INTERVAL {
+                last_metrics = std::time::Instant::now();
+                let metrics = collect_runtime_metrics(&state).await;
+                if !metrics.is_empty() {
+                    let _ = app.emit("runtime:metrics", &metrics);
+                }
+            }
         }
     });
 }
 
 /// Start SSE listener for job/session status tracking
-fn start_sse_job_listener(state: Arc<Mutex<AppState>>, app: tauri::AppHandle) {
+/// Follows `agent_port_rx` so it reconnects to the right server after restarts.
+fn start_sse_job_listener(
+    state: Arc<Mutex<AppState>>,
+    app: tauri::AppHandle,
+    mut agent_port_rx: tokio::sync::watch::Receiver<u16>,
+) {
     tauri::async_runtime::spawn(async move {
         // Wait for server to be ready
         tokio::time::sleep(Duration::from_secs(5)).await;
 
+        let mut port = *agent_port_rx.borrow_and_update();
+        let mut consecutive_failures: u32 = 0;
+
         loop {
+            if agent_port_rx.has_changed().unwrap_or(false) {
+                port = *agent_port_rx.borrow_and_update();
+                consecutive_failures = 0;
+                println!("[sse] Agent server restarted, connecting on port {}", port);
+            }
+
             // Connect to OpenCode SSE endpoint
-            let url = format!("http://localhost:{}/event", PORT_OPENCODE);
+            let url = format!("http://localhost:{}/event", port);
 
-            match reqwest::Client::new()
+            let delay = match reqwest::Client::new()
                 .get(&url)
                 .header("Accept", "text/event-stream")
                 .send()
@@ -700,6 +2881,11 @@ fn start_sse_job_listener(state: Arc<Mutex<AppState>>, app: tauri::AppHandle) {
                 Ok(response) => {
                     if response.status().is_success() {
                         println!("[sse] Connected to OpenCode event stream");
+                        consecutive_failures = 0;
+
+                        // Events may have been missed while disconnected - resync the badge
+                        let active = state.lock().await.job_registry.active_count() as usize;
+                        tray::update_tray_badge(&app, active);
 
                         // Read SSE stream
                         let mut stream = response.bytes_stream();
@@ -735,135 +2921,334 @@ fn start_sse_job_listener(state: Arc<Mutex<AppState>>, app: tauri::AppHandle) {
                         }
 
                         println!("[sse] Disconnected from event stream, reconnecting...");
+                        SSE_RETRY_BASE
+                    } else if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+                        // Server is up but busy (e.g. restarting) - retry sooner than for a dead server
+                        let delay = sse_backoff_delay(consecutive_failures).min(SSE_BUSY_RETRY_MAX);
+                        consecutive_failures += 1;
+                        println!("[sse] Event stream unavailable (503), retrying in {:?}", delay);
+                        delay
+                    } else {
+                        let delay = sse_backoff_delay(consecutive_failures);
+                        consecutive_failures += 1;
+                        eprintln!("[sse] Event stream returned {}, retrying in {:?}", response.status(), delay);
+                        delay
                     }
                 }
                 Err(e) => {
-                    // Connection failed, will retry
-                    eprintln!("[sse] Failed to connect: {}", e);
+                    // Connection failed (server down) - back off
+                    let delay = sse_backoff_delay(consecutive_failures);
+                    consecutive_failures += 1;
+                    eprintln!("[sse] Failed to connect: {}, retrying in {:?}", e, delay);
+                    delay
                 }
-            }
+            };
 
-            // Wait before reconnecting
-            tokio::time::sleep(Duration::from_secs(3)).await;
+            // Wait before reconnecting, but reconnect right away if the agent server restarts
+            tokio::select! {
+                _ = tokio::time::sleep(with_jitter(delay)) => {}
+                Ok(()) = agent_port_rx.changed() => {
+                    port = *agent_port_rx.borrow_and_update();
+                    consecutive_failures = 0;
+                    println!("[sse] Agent server restarted, connecting on port {}", port);
+                }
+            }
         }
     });
 }
 
+/// First SSE reconnect delay; grows by `SSE_RETRY_MULTIPLIER` per consecutive failure
+const SSE_RETRY_BASE: Duration = Duration::from_secs(1);
+const SSE_RETRY_MULTIPLIER: f64 = 1.5;
+const SSE_RETRY_MAX: Duration = Duration::from_secs(60);
+/// Cap for retries while the server answers 503 (up, but busy)
+const SSE_BUSY_RETRY_MAX: Duration = Duration::from_secs(5);
+
+/// Reconnect delay after `failures` consecutive failed attempts
+fn sse_backoff_delay(failures: u32) -> Duration {
+    let secs = SSE_RETRY_BASE.as_secs_f64() * SSE_RETRY_MULTIPLIER.powi(failures.min(64) as i32);
+    Duration::from_secs_f64(secs.min(SSE_RETRY_MAX.as_secs_f64()))
+}
+
+/// Randomize a delay by ±20% so clients don't reconnect in lockstep
+fn with_jitter(delay: Duration) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+    // RandomState is randomly seeded, which is plenty for jitter
+    let random = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    let factor = 0.8 + (random % 10_000) as f64 / 10_000.0 * 0.4;
+    delay.mul_f64(factor)
+}
+
 /// Handle incoming session events
 async fn handle_session_event(
     state: &Arc<Mutex<AppState>>,
     app: &tauri::AppHandle,
     event: SessionEvent,
 ) {
-    match event {
+    match event.normalize() {
         SessionEvent::SessionStatus { session_id, status } => {
             let mut state_guard = state.lock().await;
+            let workbook_id = state_guard.active_workbook_id.clone().unwrap_or_default();
 
-            if SessionEvent::is_running_status(&status) {
-                // Check if we already have a job for this session
-                if state_guard.job_registry.find_active_by_session(&session_id).is_none() {
-                    // Get active workbook ID
-                    let workbook_id = state_guard.active_workbook_id.clone().unwrap_or_default();
-
-                    // Register new job
-                    let job_id = state_guard.job_registry.register(
-                        &workbook_id,
-                        &session_id,
-                        "AI processing...",
-                    );
-                    println!("[jobs] Registered job {} for session {}", job_id, session_id);
-
-                    // Emit event to update tray
-                    let _ = app.emit("job:started", &job_id);
-                }
-            } else if SessionEvent::is_completed_status(&status) {
-                // Find and complete the job
-                if let Some(job) = state_guard.job_registry.find_by_session(&session_id) {
-                    let job_id = job.id.clone();
-                    state_guard.job_registry.complete(&job_id);
-                    println!("[jobs] Completed job {} for session {}", job_id, session_id);
-
-                    // Emit event to update tray
-                    let _ = app.emit("job:completed", &job_id);
-                }
-            } else if SessionEvent::is_failed_status(&status) {
-                // Find and fail the job
-                if let Some(job) = state_guard.job_registry.find_by_session(&session_id) {
-                    let job_id = job.id.clone();
-                    state_guard.job_registry.fail(&job_id);
-                    println!("[jobs] Failed job {} for session {}", job_id, session_id);
-
-                    // Emit event to update tray
-                    let _ = app.emit("job:failed", &job_id);
+            if let Some((transition, job_id)) =
+                state_guard.job_registry.apply_session_status(&workbook_id, &session_id, &status)
+            {
+                println!("[jobs] Job {} for session {}: {:?}", job_id, session_id, transition);
+                if transition == JobTransition::Started {
+                    telemetry::record(&state_guard, "session_started");
+                    sfx::play(app, "processing");
                 }
+
+                // Cached session summaries carry job status, so refetch them
+                state_guard.session_cache.retain(|(cached_workbook_id, _), _| *cached_workbook_id != workbook_id);
+
+                // Emit event to update tray
+                let _ = app.emit(transition.event_name(), &job_id);
+            }
+
+            tray::update_tray_badge(app, state_guard.job_registry.active_count() as usize);
+        }
+        SessionEvent::MessagePartUpdated { session_id, progress: Some(progress), step } => {
+            let mut state_guard = state.lock().await;
+            let Some(job_id) = state_guard.job_registry.find_active_by_session(&session_id).map(|j| j.id.clone()) else {
+                return;
+            };
+            if state_guard.job_registry.update_progress(&job_id, progress, step) {
+                let job = state_guard.job_registry.get(&job_id);
+                let _ = app.emit("job:progress", serde_json::json!({
+                    "job_id": job_id,
+                    "progress": job.and_then(|j| j.progress),
+                    "step": job.and_then(|j| j.current_step.clone()),
+                }));
             }
         }
-        SessionEvent::SessionUpdated { session_id, status } => {
-            if let Some(status) = status {
-                // Re-dispatch as SessionStatus
-                let status_event = SessionEvent::SessionStatus { session_id, status };
-                // Use Box::pin to handle the recursive async call
-                Box::pin(handle_session_event(state, app, status_event)).await;
+        SessionEvent::ToolCall { session_id, tool_name, input } => {
+            let mut state_guard = state.lock().await;
+            if state_guard.job_registry.record_tool_call(&session_id, &tool_name, &input) {
+                println!("[jobs] Session {} used tool {}", session_id, tool_name);
             }
         }
         _ => {}
     }
 }
 
+/// Get the tool calls recorded for a job
+#[tauri::command]
+async fn get_job_tool_calls(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    job_id: String,
+) -> Result<Vec<jobs::ToolCallSummary>, String> {
+    let state = state.lock().await;
+    state.job_registry
+        .get(&job_id)
+        .map(|job| job.tool_calls.clone())
+        .ok_or_else(|| format!("Job {} not found", job_id))
+}
+
+/// How long `list_workbook_sessions` results are reused
+const SESSION_CACHE_TTL: Duration = Duration::from_secs(30);
+/// Sessions returned when no limit is given
+const DEFAULT_SESSION_LIMIT: usize = 10;
+
+/// An agent session that ran in a workbook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub title: Option<String>,
+    /// Job status (`running`, `completed`, ...) if tracked this run, otherwise `idle`
+    pub status: String,
+    pub started_at: u64,
+    pub completed_at: Option<u64>,
+    pub message_count: u32,
+}
+
+/// Most recent agent sessions for a workbook, newest first. Cached for `SESSION_CACHE_TTL`.
+pub(crate) async fn fetch_workbook_sessions(
+    state: &Arc<Mutex<AppState>>,
+    workbook_id: &str,
+    limit: usize,
+) -> Result<Vec<SessionSummary>, String> {
+    let cache_key = (workbook_id.to_string(), limit);
+    if let Some((fetched_at, sessions)) = state.lock().await.session_cache.get(&cache_key) {
+        if fetched_at.elapsed() < SESSION_CACHE_TTL {
+            return Ok(sessions.clone());
+        }
+    }
+
+    let workbook_dir = get_workbook_dir(workbook_id)?;
+    let port = *state.lock().await.agent_port_tx.borrow();
+    let client = reqwest::Client::new();
+
+    let all_sessions: Vec<serde_json::Value> = client
+        .get(format!("http://localhost:{}/session", port))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch sessions: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse sessions: {}", e))?;
+
+    // Sessions record the directory the agent ran in
+    let mut sessions: Vec<&serde_json::Value> = all_sessions
+        .iter()
+        .filter(|s| {
+            s["directory"].as_str().map(PathBuf::from).as_deref() == Some(workbook_dir.as_path())
+        })
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s["time"]["created"].as_u64().unwrap_or(0)));
+    sessions.truncate(limit);
+
+    // Message counts aren't part of the session list, so ask for each session's messages
+    let message_counts = futures_util::future::join_all(sessions.iter().map(|s| {
+        let url = format!("http://localhost:{}/session/{}/message", port, s["id"].as_str().unwrap_or_default());
+        let client = client.clone();
+        async move {
+            let messages: Option<Vec<serde_json::Value>> = match client.get(&url).timeout(Duration::from_secs(5)).send().await {
+                Ok(resp) => resp.json().await.ok(),
+                Err(_) => None,
+            };
+            messages.map(|m| m.len() as u32).unwrap_or(0)
+        }
+    }))
+    .await;
+
+    let summaries: Vec<SessionSummary> = {
+        let state_guard = state.lock().await;
+        sessions
+            .iter()
+            .zip(message_counts)
+            .filter_map(|(s, message_count)| {
+                let session_id = s["id"].as_str()?.to_string();
+                let job = state_guard.job_registry.find_by_session(&session_id);
+                let status = job.map_or("idle", |j| j.status.as_str()).to_string();
+                let completed_at = if job.is_some_and(|j| j.is_active()) {
+                    None
+                } else {
+                    s["time"]["updated"].as_u64()
+                };
+                Some(SessionSummary {
+                    title: s["title"].as_str().map(|t| t.to_string()),
+                    status,
+                    started_at: s["time"]["created"].as_u64().unwrap_or(0),
+                    completed_at,
+                    message_count,
+                    session_id,
+                })
+            })
+            .collect()
+    };
+
+    state.lock().await.session_cache.insert(cache_key, (std::time::Instant::now(), summaries.clone()));
+    Ok(summaries)
+}
+
+/// List recent agent sessions for a workbook, newest first (default limit 10)
+#[tauri::command]
+async fn list_workbook_sessions(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<SessionSummary>, String> {
+    fetch_workbook_sessions(&state, &workbook_id, limit.unwrap_or(DEFAULT_SESSION_LIMIT)).await
+}
+
+/// Cancel a running job: ask the agent to stop its session, then mark it cancelled.
+/// Returns false if the job doesn't exist or has already finished.
+#[tauri::command]
+async fn cancel_job(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    job_id: String,
+) -> Result<bool, String> {
+    let (session_id, port) = {
+        let state_guard = state.lock().await;
+        match state_guard.job_registry.get(&job_id) {
+            Some(job) if job.is_active() => (job.session_id.clone(), *state_guard.agent_port_tx.borrow()),
+            _ => return Ok(false),
+        }
+    };
+
+    // Don't hold the state lock while talking to the agent
+    let cancel_url = format!("http://localhost:{}/session/{}/cancel", port, session_id);
+    let response = reqwest::Client::new()
+        .post(&cancel_url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to cancel session {}: {}", session_id, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to cancel session {}: HTTP {}", session_id, response.status()));
+    }
+
+    {
+        let mut state_guard = state.lock().await;
+        // The session may have finished while the request was in flight
+        if !state_guard.job_registry.get(&job_id).map(|j| j.is_active()).unwrap_or(false) {
+            return Ok(false);
+        }
+        state_guard.job_registry.cancel(&job_id);
+        tray::update_tray_badge(&app, state_guard.job_registry.active_count() as usize);
+    }
+    println!("[jobs] Cancelled job {} for session {}", job_id, session_id);
+
+    // Emit event to update tray
+    let _ = app.emit("job:cancelled", &job_id);
+    Ok(true)
+}
+
+/// Stop the runtime already running for `workbook_id`, if any, returning its
+/// port and the windows registered against it. Runtimes for other workbooks
+/// are left alone.
+async fn stop_previous_runtime(
+    state: &Arc<Mutex<AppState>>,
+    workbook_id: &str,
+) -> Option<(u16, HashSet<String>)> {
+    let mut runtime = state.lock().await.runtime_manager.remove(workbook_id)?;
+    println!("[internal] Stopping existing runtime: {}", workbook_id);
+
+    // Try graceful shutdown
+    let stop_url = format!("http://localhost:{}/stop", runtime.runtime_port);
+    let _ = reqwest::Client::new()
+        .post(&stop_url)
+        .timeout(Duration::from_secs(2))
+        .send()
+        .await;
+    // Force kill
+    let _ = runtime.process.kill().await;
+
+    Some((runtime.runtime_port, std::mem::take(&mut runtime.windows)))
+}
+
 /// Internal version of start_workbook_server for use from startup code
 pub async fn start_workbook_server_internal(
     app: &tauri::AppHandle,
     state: &Arc<Mutex<AppState>>,
     workbook_id: &str,
     directory: &str,
-) -> Result<DevServerStatus, String> {
+) -> Result<DevServerStatus, RuntimeStartError> {
     println!("[internal] start_workbook_server: {} at {}", workbook_id, directory);
 
-    let mut did_stop_existing = false;
-
-    // Stop ALL existing runtimes first (they share port 55000)
-    {
-        let mut state_guard = state.lock().await;
-        let existing_ids: Vec<String> = state_guard.workbook_servers.keys().cloned().collect();
-
-        for existing_id in existing_ids {
-            if let Some(mut runtime) = state_guard.workbook_servers.remove(&existing_id) {
-                println!("[internal] Stopping existing runtime: {}", existing_id);
-                // Try graceful shutdown
-                let stop_url = format!("http://localhost:{}/stop", runtime.runtime_port);
-                let _ = reqwest::Client::new()
-                    .post(&stop_url)
-                    .timeout(Duration::from_secs(2))
-                    .send()
-                    .await;
-                // Force kill
-                let _ = runtime.child.kill().await;
-                did_stop_existing = true;
-            }
+    // Replace this workbook's previous runtime; other workbooks keep running
+    let previous = stop_previous_runtime(state, workbook_id).await;
+    let previous_windows = match previous {
+        Some((previous_port, windows)) => {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            // Force kill anything still on the old port - handles orphaned processes
+            kill_processes_on_port(previous_port);
+            windows
         }
-    }
-
-    // Only wait for port release if we stopped existing runtimes
-    if did_stop_existing {
-        tokio::time::sleep(Duration::from_millis(300)).await;
-    }
-
-    // Force kill any process still on runtime port (55000) - handles orphaned processes
-    let runtime_port_default: u16 = PORT_PREFIX as u16 * 1000;
-    kill_processes_on_port(runtime_port_default);
+        None => HashSet::new(),
+    };
 
-    let env_vars = get_api_keys_from_store(app);
-    let (child, runtime_port) =
-        spawn_workbook_server(workbook_id, directory, env_vars).await?;
+    let env_vars = get_api_keys_from_store(app, Some(directory));
+    let mut runtime = spawn_workbook_server(app, state, workbook_id, directory, env_vars).await?;
+    let runtime_port = runtime.runtime_port;
+    runtime.windows = previous_windows;
 
     // Re-acquire lock and store
     let mut state_guard = state.lock().await;
-    state_guard.workbook_servers.insert(workbook_id.to_string(), WorkbookServerProcess {
-        child,
-        runtime_port,
-        directory: directory.to_string(),
-        restart_count: 0,
-    });
+    state_guard.runtime_manager.insert(workbook_id.to_string(), runtime);
 
     println!(
         "Workbook server started for {} on port {}",
@@ -876,6 +3261,8 @@ pub async fn start_workbook_server_internal(
         directory: directory.to_string(),
         runtime_port,
         message: format!("Workbook server started on port {}", runtime_port),
+        next_restart_in_ms: None,
+        target_os: std::env::consts::OS.to_string(),
     })
 }
 
@@ -886,7 +3273,7 @@ async fn start_workbook_server(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     workbook_id: String,
     directory: String,
-) -> Result<DevServerStatus, String> {
+) -> Result<DevServerStatus, RuntimeStartError> {
     start_workbook_server_internal(&app, state.inner(), &workbook_id, &directory).await
 }
 
@@ -963,15 +3350,74 @@ pub async fn set_active_workbook_internal(app: &tauri::AppHandle, workbook_id: &
     Ok(())
 }
 
-/// Stop the runtime for a workbook
-#[tauri::command]
-async fn stop_runtime(
-    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+/// Interval between checks while waiting for a runtime's jobs to drain
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait (up to `timeout`) for a workbook's active jobs to finish, emitting
+/// `runtime:draining` while they run. Stops early if the runtime stops responding.
+async fn drain_runtime_jobs(
+    app: &tauri::AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    workbook_id: &str,
+    timeout: Duration,
+) {
+    let deadline = std::time::Instant::now() + timeout;
+    let client = reqwest::Client::new();
+
+    loop {
+        let (remaining_jobs, runtime_port) = {
+            let state_guard = state.lock().await;
+            let remaining = state_guard.job_registry.list_active_for_workbook(workbook_id).len();
+            let port = state_guard.runtime_manager.get(workbook_id).map(|r| r.runtime_port);
+            (remaining, port)
+        };
+        let Some(runtime_port) = runtime_port else { return };
+        if remaining_jobs == 0 {
+            return;
+        }
+        if std::time::Instant::now() >= deadline {
+            println!("[runtime] Drain timeout for {} with {} job(s) still active", workbook_id, remaining_jobs);
+            return;
+        }
+
+        let _ = app.emit("runtime:draining", serde_json::json!({
+            "workbook_id": workbook_id,
+            "remaining_jobs": remaining_jobs,
+        }));
+
+        // Nothing left to drain if the runtime has already gone away
+        let alive = client
+            .get(format!("http://localhost:{}/status", runtime_port))
+            .timeout(DRAIN_POLL_INTERVAL)
+            .send()
+            .await
+            .is_ok_and(|resp| resp.status().is_success());
+        if !alive {
+            println!("[runtime] Runtime for {} stopped responding while draining", workbook_id);
+            return;
+        }
+
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
+/// Stop the runtime for a workbook (shared by the command and the tray).
+/// With `drain_timeout_secs`, active jobs get that long to finish first.
+pub(crate) async fn stop_runtime_internal(
+    app: &tauri::AppHandle,
+    state: &Arc<Mutex<AppState>>,
     workbook_id: String,
+    drain_timeout_secs: Option<u64>,
 ) -> Result<DevServerStatus, String> {
+    if let Some(timeout_secs) = drain_timeout_secs {
+        if state.lock().await.job_registry.has_active_jobs(&workbook_id) {
+            drain_runtime_jobs(app, state, &workbook_id, Duration::from_secs(timeout_secs)).await;
+        }
+    }
+
     let mut state_guard = state.lock().await;
 
-    if let Some(mut runtime) = state_guard.workbook_servers.remove(&workbook_id) {
+    if let Some(mut runtime) = state_guard.runtime_manager.remove(&workbook_id) {
         // Try graceful shutdown via /stop endpoint
         let stop_url = format!("http://localhost:{}/stop", runtime.runtime_port);
         let _ = reqwest::Client::new()
@@ -981,7 +3427,7 @@ async fn stop_runtime(
             .await;
 
         // Force kill if still running
-        let _ = runtime.child.kill().await;
+        let _ = runtime.process.kill().await;
 
         println!("Runtime stopped for workbook {}", workbook_id);
 
@@ -991,6 +3437,8 @@ async fn stop_runtime(
             directory: String::new(),
             runtime_port: 0,
             message: "Runtime stopped".to_string(),
+            next_restart_in_ms: None,
+            target_os: std::env::consts::OS.to_string(),
         });
     }
 
@@ -1000,9 +3448,23 @@ async fn stop_runtime(
         directory: String::new(),
         runtime_port: 0,
         message: "Runtime was not running".to_string(),
+        next_restart_in_ms: None,
+        target_os: std::env::consts::OS.to_string(),
     })
 }
 
+/// Stop the runtime for a workbook, optionally waiting up to
+/// `drain_timeout_secs` for its active jobs to finish
+#[tauri::command]
+async fn stop_runtime(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+    drain_timeout_secs: Option<u64>,
+) -> Result<DevServerStatus, String> {
+    stop_runtime_internal(&app, state.inner(), workbook_id, drain_timeout_secs).await
+}
+
 /// Get the currently active runtime (if any)
 #[tauri::command]
 async fn get_active_runtime(
@@ -1015,13 +3477,15 @@ async fn get_active_runtime(
         None => return Ok(None),
     };
 
-    if let Some(runtime) = state_guard.workbook_servers.get(&workbook_id) {
+    if let Some(runtime) = state_guard.runtime_manager.get(&workbook_id) {
         return Ok(Some(DevServerStatus {
             running: true,
             workbook_id,
             directory: runtime.directory.clone(),
             runtime_port: runtime.runtime_port,
             message: "Runtime is running".to_string(),
+            next_restart_in_ms: None,
+            target_os: std::env::consts::OS.to_string(),
         }));
     }
 
@@ -1031,12 +3495,13 @@ async fn get_active_runtime(
 /// Get runtime status for a workbook
 #[tauri::command]
 async fn get_runtime_status(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     workbook_id: String,
 ) -> Result<DevServerStatus, String> {
     let state_guard = state.lock().await;
 
-    if let Some(runtime) = state_guard.workbook_servers.get(&workbook_id) {
+    if let Some(runtime) = state_guard.runtime_manager.get(&workbook_id) {
         // Ping the runtime to verify it's still alive
         let status_url = format!("http://localhost:{}/status", runtime.runtime_port);
         let is_running = match reqwest::get(&status_url).await {
@@ -1055,6 +3520,21 @@ async fn get_runtime_status(
             } else {
                 "Runtime is starting...".to_string()
             },
+            next_restart_in_ms: None,
+            target_os: std::env::consts::OS.to_string(),
+        });
+    }
+
+    // Runtime crashed and is waiting out its restart backoff
+    if let Some(delay) = state_guard.runtime_manager.next_restart_in(&workbook_id) {
+        return Ok(DevServerStatus {
+            running: false,
+            workbook_id,
+            directory: String::new(),
+            runtime_port: 0,
+            message: format!("Runtime crashed, restarting in {}s", delay.as_secs()),
+            next_restart_in_ms: Some(delay.as_millis() as u64),
+            target_os: std::env::consts::OS.to_string(),
         });
     }
 
@@ -1062,7 +3542,7 @@ async fn get_runtime_status(
     drop(state_guard);
 
     // Fallback: Check if runtime is running on default port (started externally)
-    let default_runtime_port: u16 = PORT_PREFIX as u16 * 1000;
+    let default_runtime_port: u16 = port_prefix(&app) * 1000;
 
     let status_url = format!("http://localhost:{}/status", default_runtime_port);
     if let Ok(resp) = reqwest::get(&status_url).await {
@@ -1074,6 +3554,8 @@ async fn get_runtime_status(
                 directory: String::new(),
                 runtime_port: default_runtime_port,
                 message: "Runtime detected on default port".to_string(),
+                next_restart_in_ms: None,
+                target_os: std::env::consts::OS.to_string(),
             });
         }
     }
@@ -1084,6 +3566,8 @@ async fn get_runtime_status(
         directory: String::new(),
         runtime_port: 0,
         message: "Runtime is not running".to_string(),
+        next_restart_in_ms: None,
+        target_os: std::env::consts::OS.to_string(),
     })
 }
 
@@ -1096,7 +3580,7 @@ async fn runtime_query(
 ) -> Result<serde_json::Value, String> {
     let state_guard = state.lock().await;
 
-    let runtime = state_guard.workbook_servers.get(&workbook_id)
+    let runtime = state_guard.runtime_manager.get(&workbook_id)
         .ok_or("Runtime not running for this workbook")?;
 
     // Use tRPC endpoint (db.query is a mutation)
@@ -1134,20 +3618,74 @@ async fn runtime_query(
 }
 
 /// Trigger eval on runtime
+///
+/// * `eval_path` - evaluate only this file instead of the whole workspace
+/// * `watch_mode` - stream results from `/eval/watch` as `runtime:eval-output` events;
+///   returns `{ "stream_id": ... }` which can be passed to `stop_eval_watch`
 #[tauri::command]
 async fn runtime_eval(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     workbook_id: String,
+    eval_path: Option<String>,
+    watch_mode: Option<bool>,
 ) -> Result<serde_json::Value, String> {
-    let state_guard = state.lock().await;
+    let runtime_port = {
+        let state_guard = state.lock().await;
+        state_guard.runtime_manager.get(&workbook_id)
+            .ok_or("Runtime not running for this workbook")?
+            .runtime_port
+    };
 
-    let runtime = state_guard.workbook_servers.get(&workbook_id)
-        .ok_or("Runtime not running for this workbook")?;
+    let body = match &eval_path {
+        Some(path) => serde_json::json!({ "path": path }),
+        None => serde_json::json!({}),
+    };
 
-    let url = format!("http://localhost:{}/eval", runtime.runtime_port);
+    if watch_mode.unwrap_or(false) {
+        let url = format!("http://localhost:{}/eval/watch", runtime_port);
+        let stream_id = uuid::Uuid::new_v4().to_string();
 
-    let resp = reqwest::Client::new()
-        .post(&url)
+        let resp = reqwest::Client::new()
+            .post(&url)
+            .header("Accept", "text/event-stream")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to start eval watch: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("Eval watch failed: HTTP {}", resp.status()));
+        }
+
+        let task_app = app.clone();
+        let task_stream_id = stream_id.clone();
+        let task_state = state.inner().clone();
+
+        // Hold the lock while spawning so the task can't finish (and remove itself)
+        // before it has been registered
+        let mut state_guard = state.lock().await;
+        let handle = tauri::async_runtime::spawn(async move {
+            stream_eval_output(&task_app, &task_stream_id, resp).await;
+            task_state.lock().await.eval_watches.remove(&task_stream_id);
+        });
+
+        state_guard.eval_watches.insert(stream_id.clone(), EvalWatch {
+            workbook_id,
+            task: handle,
+        });
+
+        return Ok(serde_json::json!({ "stream_id": stream_id }));
+    }
+
+    let url = format!("http://localhost:{}/eval", runtime_port);
+
+    let mut request = reqwest::Client::new().post(&url);
+    if eval_path.is_some() {
+        request = request.json(&body);
+    }
+
+    let resp = request
         .send()
         .await
         .map_err(|e| format!("Failed to run eval: {}", e))?;
@@ -1155,6 +3693,83 @@ async fn runtime_eval(
     resp.json().await.map_err(|e| format!("Failed to parse response: {}", e))
 }
 
+/// An in-flight `/eval/watch` stream
+pub struct EvalWatch {
+    pub workbook_id: String,
+    pub task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Forward SSE events from an eval watch response as `runtime:eval-output` events
+async fn stream_eval_output(app: &tauri::AppHandle, stream_id: &str, response: reqwest::Response) {
+    use futures_util::StreamExt;
+
+    let mut stream = response.bytes_stream();
+    let mut buffer = String::new();
+
+    while let Some(chunk) = stream.next().await {
+        let bytes = match chunk {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("[eval] Watch stream error: {}", e);
+                break;
+            }
+        };
+        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+        while let Some(event_end) = buffer.find("\n\n") {
+            let event_str = buffer[..event_end].to_string();
+            buffer = buffer[event_end + 2..].to_string();
+
+            let Some(data_line) = event_str.lines().find(|l| l.starts_with("data: ")) else {
+                continue;
+            };
+            let data = &data_line[6..];
+
+            // Runtime sends { type, data }; anything else is treated as plain stdout
+            let (output_type, output) = match serde_json::from_str::<serde_json::Value>(data) {
+                Ok(json) => (
+                    json.get("type").and_then(|t| t.as_str()).unwrap_or("stdout").to_string(),
+                    json.get("data")
+                        .map(|d| d.as_str().map(|s| s.to_string()).unwrap_or_else(|| d.to_string()))
+                        .unwrap_or_default(),
+                ),
+                Err(_) => ("stdout".to_string(), data.to_string()),
+            };
+
+            let _ = app.emit("runtime:eval-output", serde_json::json!({
+                "stream_id": stream_id,
+                "type": output_type,
+                "data": output,
+            }));
+        }
+    }
+
+    println!("[eval] Watch stream {} ended", stream_id);
+}
+
+/// Stop an eval watch started by `runtime_eval` with `watch_mode`
+#[tauri::command]
+async fn stop_eval_watch(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+    stream_id: String,
+) -> Result<(), String> {
+    let mut state_guard = state.lock().await;
+
+    match state_guard.eval_watches.get(&stream_id) {
+        Some(watch) if watch.workbook_id == workbook_id => {}
+        Some(_) => return Err(format!("Eval watch {} does not belong to workbook {}", stream_id, workbook_id)),
+        None => return Err(format!("Eval watch {} not found", stream_id)),
+    }
+
+    if let Some(watch) = state_guard.eval_watches.remove(&stream_id) {
+        watch.task.abort();
+        println!("[eval] Stopped watch stream {}", stream_id);
+    }
+
+    Ok(())
+}
+
 // OpenCode server management
 #[tauri::command]
 async fn check_server_health(port: u16) -> Result<HealthCheck, String> {
@@ -1189,57 +3804,207 @@ async fn check_server_health(port: u16) -> Result<HealthCheck, String> {
     }
 }
 
-fn get_api_keys_from_store(app: &tauri::AppHandle) -> HashMap<String, String> {
+/// Parse a dotenv-style file into key/value pairs.
+/// Blank values and `#` comments are skipped; a missing file yields an empty map.
+fn load_env_file(path: &std::path::Path) -> HashMap<String, String> {
     let mut env_vars = HashMap::new();
 
-    // In dev mode, try to read from .env.local in the desktop package
-    #[cfg(debug_assertions)]
-    {
-        let env_local_path = format!("{}/../.env.local", env!("CARGO_MANIFEST_DIR"));
-        println!("[env] Dev mode: checking {}", env_local_path);
-        if let Ok(contents) = std::fs::read_to_string(&env_local_path) {
-            for line in contents.lines() {
-                if let Some((key, value)) = line.split_once('=') {
-                    let key = key.trim();
-                    let value = value.trim();
-                    if !key.is_empty() && !value.is_empty() && !key.starts_with('#') {
-                        println!("[env] Found {} from .env.local", key);
-                        env_vars.insert(key.to_string(), value.to_string());
-                    }
+    if let Ok(contents) = std::fs::read_to_string(path) {
+        for line in contents.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim();
+                let value = value.trim();
+                if !key.is_empty() && !value.is_empty() && !key.starts_with('#') {
+                    env_vars.insert(key.to_string(), value.to_string());
                 }
             }
         }
     }
 
-    // In production, try ~/.hands/.env for user-level config (optional fallback)
+    env_vars
+}
+
+/// Env files to load, in increasing order of precedence (later files override earlier ones)
+fn env_file_paths(app: &tauri::AppHandle, workbook_dir: Option<&str>) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // Dev mode: .env.local in the desktop package (path is baked in at compile time,
+    // so it only makes sense on the developer's machine)
+    #[cfg(debug_assertions)]
+    {
+        paths.push(PathBuf::from(format!("{}/../.env.local", env!("CARGO_MANIFEST_DIR"))));
+    }
+
+    // Production: user-level config in ~/.hands/.env
     #[cfg(not(debug_assertions))]
     {
         if let Some(home) = dirs::home_dir() {
-            let env_path = home.join(".hands").join(".env");
-            println!("[env] Production mode: checking {:?}", env_path);
-            if let Ok(contents) = std::fs::read_to_string(&env_path) {
-                for line in contents.lines() {
-                    if let Some((key, value)) = line.split_once('=') {
-                        let key = key.trim();
-                        let value = value.trim();
-                        if !key.is_empty() && !value.is_empty() && !key.starts_with('#') {
-                            println!("[env] Found {} from ~/.hands/.env", key);
-                            env_vars.insert(key.to_string(), value.to_string());
-                        }
-                    }
-                }
-            } else {
-                println!("[env] No ~/.hands/.env file found (this is fine, using store)");
-            }
+            paths.push(home.join(".hands").join(".env"));
         }
     }
 
-    // Store values take precedence (set by settings UI)
-    // OpenRouter is the primary key - provides access to all models
-    if let Ok(store) = app.store("settings.json") {
-        if let Some(value) = store.get("openrouter_api_key") {
+    // App config dir (valid in both dev and production builds)
+    if let Ok(config_dir) = app.path().app_config_dir() {
+        paths.push(config_dir.join(".env.local"));
+    }
+
+    // Workbook-level overrides
+    if let Some(dir) = workbook_dir {
+        paths.push(PathBuf::from(dir).join(".env.local"));
+    }
+
+    paths
+}
+
+/// Settings/keychain key for the OpenRouter API key
+const OPENROUTER_KEY: &str = "openrouter_api_key";
+
+/// Settings key for configured AI providers
+const PROVIDERS_KEY: &str = "providers";
+
+/// An AI provider's credentials (`settings.json["providers"]`). The key is kept in
+/// the keychain where available, with `keychain::SENTINEL` stored in its place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    /// Lowercase provider name, e.g. `openai` - exported as `OPENAI_API_KEY`
+    pub name: String,
+    pub api_key: String,
+    /// Exported as `<NAME>_BASE_URL` when set
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl ProviderConfig {
+    fn env_prefix(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect()
+    }
+}
+
+fn provider_keychain_key(name: &str) -> String {
+    format!("provider.{}", name)
+}
+
+/// Providers as stored (keys may be `keychain::SENTINEL`)
+fn load_providers(app: &tauri::AppHandle) -> Vec<ProviderConfig> {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(PROVIDERS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save_providers(app: &tauri::AppHandle, providers: &[ProviderConfig]) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(PROVIDERS_KEY, serde_json::json!(providers));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Replace the stored sentinel with the real key from the keychain
+fn resolve_provider_key(mut provider: ProviderConfig) -> Option<ProviderConfig> {
+    if provider.api_key == keychain::SENTINEL {
+        provider.api_key = keychain::get_secret(keychain::SERVICE, &provider_keychain_key(&provider.name))?;
+    }
+    Some(provider)
+}
+
+/// Add or replace an AI provider's credentials
+#[tauri::command]
+async fn set_provider(app: tauri::AppHandle, config: ProviderConfig) -> Result<(), String> {
+    let name = config.name.trim().to_lowercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid provider name: {}", config.name));
+    }
+    let api_key = config.api_key.trim().to_string();
+    if api_key.is_empty() {
+        return Err("API key is empty".to_string());
+    }
+
+    let stored_key = keychain::store_secret(&provider_keychain_key(&name), &api_key);
+
+    let mut providers = load_providers(&app);
+    providers.retain(|p| p.name != name);
+    providers.push(ProviderConfig {
+        name,
+        api_key: stored_key,
+        base_url: config.base_url.filter(|url| !url.trim().is_empty()),
+    });
+    save_providers(&app, &providers)
+}
+
+/// Hide all but the last 4 characters of a key stored in settings. Keychain keys
+/// keep the sentinel so the UI can show that a key is set without reading it.
+fn mask_provider_key(mut provider: ProviderConfig) -> ProviderConfig {
+    if provider.api_key != keychain::SENTINEL {
+        let chars: Vec<char> = provider.api_key.chars().collect();
+        let last4: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+        provider.api_key = format!("****{}", last4);
+    }
+    provider
+}
+
+/// Configured AI providers, with keys masked (real keys never leave the backend)
+#[tauri::command]
+async fn list_providers(app: tauri::AppHandle) -> Result<Vec<ProviderConfig>, String> {
+    Ok(load_providers(&app).into_iter().map(mask_provider_key).collect())
+}
+
+/// Remove a provider. Returns whether it was configured.
+#[tauri::command]
+async fn remove_provider(app: tauri::AppHandle, name: String) -> Result<bool, String> {
+    let name = name.trim().to_lowercase();
+    let mut providers = load_providers(&app);
+    let before = providers.len();
+    providers.retain(|p| p.name != name);
+    if providers.len() == before {
+        return Ok(false);
+    }
+
+    keychain::delete_secret(keychain::SERVICE, &provider_keychain_key(&name))?;
+    save_providers(&app, &providers)?;
+    Ok(true)
+}
+
+/// Collect env vars (API keys etc.) to pass to sidecars.
+///
+/// Precedence, lowest to highest:
+/// 1. Dev: `packages/desktop/.env.local` / Production: `~/.hands/.env`
+/// 2. `<app config dir>/.env.local`
+/// 3. `<workbook dir>/.env.local` for the given workbook
+/// 4. Values from the Tauri settings store (set by the settings UI)
+fn get_api_keys_from_store(app: &tauri::AppHandle, workbook_dir: Option<&str>) -> HashMap<String, String> {
+    let mut env_vars = HashMap::new();
+
+    for path in env_file_paths(app, workbook_dir) {
+        let vars = load_env_file(&path);
+        if !vars.is_empty() {
+            println!("[env] Loaded {} vars from {:?}", vars.len(), path);
+        }
+        env_vars.extend(vars);
+    }
+
+    // Keychain and store values take precedence (set by settings UI)
+    for provider in load_providers(app).into_iter().filter_map(resolve_provider_key) {
+        let prefix = provider.env_prefix();
+        println!("[env] Found {}_API_KEY from providers", prefix);
+        env_vars.insert(format!("{}_API_KEY", prefix), provider.api_key);
+        if let Some(base_url) = provider.base_url {
+            env_vars.insert(format!("{}_BASE_URL", prefix), base_url);
+        }
+    }
+
+    // OpenRouter is the primary key - provides access to all models. The legacy
+    // `openrouter_api_key` setting wins over an `openrouter` provider entry.
+    if let Some(key) = keychain::get_secret(keychain::SERVICE, OPENROUTER_KEY) {
+        println!("[env] Found OPENROUTER_API_KEY in keychain");
+        env_vars.insert("OPENROUTER_API_KEY".to_string(), key);
+    } else if let Ok(store) = app.store("settings.json") {
+        if let Some(value) = store.get(OPENROUTER_KEY) {
             if let Some(s) = value.as_str() {
-                if !s.is_empty() {
+                if !s.is_empty() && s != keychain::SENTINEL {
                     println!("[env] Found OPENROUTER_API_KEY from Tauri store");
                     env_vars.insert("OPENROUTER_API_KEY".to_string(), s.to_string());
                 }
@@ -1256,11 +4021,73 @@ fn get_api_keys_from_store(app: &tauri::AppHandle) -> HashMap<String, String> {
     env_vars
 }
 
+/// Per-workbook environment variables (`settings.json["workbook_env.<workbook_id>"]`),
+/// applied on top of the global keys when the workbook's runtime starts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkbookEnvOverrides {
+    pub workbook_id: String,
+    pub env: HashMap<String, String>,
+}
+
+fn workbook_env_key(workbook_id: &str) -> String {
+    format!("workbook_env.{}", workbook_id)
+}
+
+fn load_workbook_env(app: &tauri::AppHandle, workbook_id: &str) -> WorkbookEnvOverrides {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(workbook_env_key(workbook_id)))
+        .and_then(|v| serde_json::from_value::<WorkbookEnvOverrides>(v).ok())
+        .unwrap_or_else(|| WorkbookEnvOverrides {
+            workbook_id: workbook_id.to_string(),
+            env: HashMap::new(),
+        })
+}
+
+/// Get a workbook's environment variable overrides
+#[tauri::command]
+fn get_workbook_env(app: tauri::AppHandle, workbook_id: String) -> HashMap<String, String> {
+    load_workbook_env(&app, &workbook_id).env
+}
+
+/// Set a workbook-level environment variable (an empty value removes it).
+/// Takes effect the next time the workbook's runtime starts.
+#[tauri::command]
+fn set_workbook_env(
+    app: tauri::AppHandle,
+    workbook_id: String,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    let key = key.trim().to_string();
+    if key.is_empty() || key.contains('=') || key.contains('\0') {
+        return Err(format!("Invalid environment variable name: {:?}", key));
+    }
+
+    let mut overrides = load_workbook_env(&app, &workbook_id);
+    if value.is_empty() {
+        overrides.env.remove(&key);
+    } else {
+        overrides.env.insert(key, value);
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    if overrides.env.is_empty() {
+        store.delete(workbook_env_key(&workbook_id));
+    } else {
+        store.set(workbook_env_key(&workbook_id), serde_json::json!(overrides));
+    }
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
 /// Check if OpenRouter API key is configured
 fn has_openrouter_api_key(app: &tauri::AppHandle) -> bool {
-    // Check store first (always available, set by settings UI)
+    // Check store first (always available, set by settings UI - holds the
+    // keychain sentinel when the key itself is in the keychain)
     if let Ok(store) = app.store("settings.json") {
-        if let Some(value) = store.get("openrouter_api_key") {
+        if let Some(value) = store.get(OPENROUTER_KEY) {
             if let Some(s) = value.as_str() {
                 if !s.is_empty() {
                     return true;
@@ -1269,39 +4096,44 @@ fn has_openrouter_api_key(app: &tauri::AppHandle) -> bool {
         }
     }
 
-    // Dev mode: also check .env.local as fallback
-    #[cfg(debug_assertions)]
-    {
-        let env_local_path = format!("{}/../.env.local", env!("CARGO_MANIFEST_DIR"));
-        if let Ok(contents) = std::fs::read_to_string(&env_local_path) {
-            for line in contents.lines() {
-                if let Some((key, value)) = line.split_once('=') {
-                    if key.trim() == "OPENROUTER_API_KEY" && !value.trim().is_empty() {
-                        return true;
-                    }
-                }
-            }
-        }
+    // Fall back to env files (no workbook is active yet at this point)
+    env_file_paths(app, None)
+        .iter()
+        .any(|path| load_env_file(path).contains_key("OPENROUTER_API_KEY"))
+}
+
+/// Check an OpenRouter key against the API. `/models` is public, so this
+/// uses `/key`, which rejects unknown keys with 401.
+async fn validate_openrouter_key(api_key: &str) -> Result<bool, String> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err("API key is empty".to_string());
     }
 
-    // Production: also check ~/.hands/.env as fallback
-    #[cfg(not(debug_assertions))]
-    {
-        if let Some(home) = dirs::home_dir() {
-            let env_path = home.join(".hands").join(".env");
-            if let Ok(contents) = std::fs::read_to_string(&env_path) {
-                for line in contents.lines() {
-                    if let Some((key, value)) = line.split_once('=') {
-                        if key.trim() == "OPENROUTER_API_KEY" && !value.trim().is_empty() {
-                            return true;
-                        }
-                    }
-                }
-            }
+    let resp = reqwest::Client::new()
+        .get("https://openrouter.ai/api/v1/key")
+        .bearer_auth(api_key)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach OpenRouter to validate API key: {}", e))?;
+
+    match resp.status() {
+        status if status.is_success() => Ok(true),
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            Err("Invalid OpenRouter API key".to_string())
         }
+        status => Err(format!("OpenRouter rejected the API key (HTTP {})", status)),
     }
+}
 
-    false
+/// Validate an API key with its provider before it is saved
+#[tauri::command]
+async fn validate_api_key(provider: String, api_key: String) -> Result<bool, String> {
+    match provider.as_str() {
+        "openrouter" => validate_openrouter_key(&api_key).await,
+        other => Err(format!("Unsupported API key provider: {}", other)),
+    }
 }
 
 /// Save OpenRouter API key and launch main app
@@ -1310,22 +4142,20 @@ async fn save_api_key_and_launch(
     app: tauri::AppHandle,
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     api_key: String,
-) -> Result<(), String> {
-    // Save to store
+) -> Result<(), HandsError> {
+    validate_openrouter_key(&api_key).await?;
+    let api_key = api_key.trim().to_string();
+
+    // Save to the keychain where available, leaving only a sentinel in the store
     let store = app.store("settings.json")
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
-    store.set("openrouter_api_key", serde_json::json!(api_key));
+    store.set(OPENROUTER_KEY, serde_json::json!(keychain::store_secret(OPENROUTER_KEY, &api_key)));
     store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
 
-    // Close setup window
-    if let Some(setup_window) = app.get_webview_window("setup") {
-        let _ = setup_window.close();
-    }
-
     // Get the workbook to open (for both workbook window and floating chat)
     let workbook = {
-        let workbooks = list_workbooks().await.unwrap_or_default();
+        let workbooks = scan_workbooks(false).await.unwrap_or_default();
         if let Some(last_id) = window_manager::get_last_workbook(&app) {
             workbooks.into_iter().find(|w| w.id == last_id)
         } else {
@@ -1337,13 +4167,20 @@ async fn save_api_key_and_launch(
     let workbook = match workbook {
         Some(wb) => wb,
         None => {
-            create_workbook(CreateWorkbookRequest {
+            create_workbook(app.clone(), CreateWorkbookRequest {
                 name: "My Notebook".to_string(),
                 description: None,
+                template_id: None,
             }).await?
         }
     };
 
+    // Close setup window only once we have a workbook, so errors (e.g. runtime
+    // not installed) are still shown there
+    if let Some(setup_window) = app.get_webview_window("setup") {
+        let _ = setup_window.close();
+    }
+
     // Start the workbook runtime first
     let state_arc = state.inner().clone();
     if let Err(e) = start_workbook_server_internal(&app, &state_arc, &workbook.id, &workbook.directory).await {
@@ -1362,26 +4199,22 @@ async fn save_api_key_and_launch(
     // the workbook editor closes (see on_window_event handler)
 
     // Restart opencode server with new API key
-    let env_vars = get_api_keys_from_store(&app);
+    let env_vars = get_api_keys_from_store(&app, Some(&workbook.directory));
 
     // Clone the Arc for use in spawn
     let state_clone = state.inner().clone();
+    let agent_app = app.clone();
 
-    // Kill existing server
-    {
-        let mut s = state_clone.lock().await;
-        if let Some(ref mut child) = s.server {
-            let _ = child.kill().await;
-        }
-        s.server = None;
-    }
-
-    // Start new server with API key (model defaults to OpenRouter in agent)
+    // Restart server with API key (model defaults to OpenRouter in agent)
     tauri::async_runtime::spawn(async move {
-        match start_opencode_server(PORT_OPENCODE, None, env_vars, None).await {
+        let agent_lock = agent_app.state::<AgentServerMutex>();
+        let _agent_guard = agent_lock.0.lock().await;
+
+        stop_agent_server(&state_clone).await;
+
+        match start_opencode_server(&agent_app, port_opencode(&agent_app), None, env_vars, None).await {
             Ok(child) => {
-                let mut s = state_clone.lock().await;
-                s.server = Some(child);
+                state_clone.lock().await.server = Some(child);
                 println!("Hands agent restarted with new API key");
             }
             Err(e) => {
@@ -1391,7 +4224,7 @@ async fn save_api_key_and_launch(
     });
 
     // Play startup sound
-    sfx::play("startup");
+    sfx::play(&app, "startup");
 
     Ok(())
 }
@@ -1400,35 +4233,87 @@ async fn kill_process_on_port(port: u16) -> Result<(), String> {
     // Get our own process ID to avoid killing ourselves
     let our_pid = std::process::id();
 
-    // Use lsof to find processes on the port and kill them
-    let output = std::process::Command::new("lsof")
-        .args(["-ti", &format!(":{}", port)])
-        .output();
+    let pids = pids_on_port(port);
+    if pids.is_empty() {
+        return Ok(());
+    }
 
-    if let Ok(output) = output {
-        if output.status.success() {
-            let pids = String::from_utf8_lossy(&output.stdout);
-            for pid in pids.lines() {
-                if let Ok(pid_num) = pid.trim().parse::<u32>() {
-                    // Don't kill ourselves!
-                    if pid_num == our_pid {
-                        println!("Skipping kill of our own process {} on port {}", pid_num, port);
-                        continue;
-                    }
-                    println!("Killing existing process {} on port {}", pid_num, port);
-                    let _ = std::process::Command::new("kill")
-                        .args(["-9", &pid_num.to_string()])
-                        .output();
-                }
-            }
-            // Give a moment for the port to be released
-            tokio::time::sleep(Duration::from_millis(500)).await;
+    for pid in pids {
+        // Don't kill ourselves!
+        if pid == our_pid {
+            println!("Skipping kill of our own process {} on port {}", pid, port);
+            continue;
         }
+        println!("Killing existing process {} on port {}", pid, port);
+        force_kill_pid(pid);
     }
+    // Give a moment for the port to be released
+    tokio::time::sleep(Duration::from_millis(500)).await;
     Ok(())
 }
 
+/// Where agent stdout/stderr goes in production builds (`settings.json["agent_log"]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct AgentLogConfig {
+    enabled: bool,
+    path: PathBuf,
+    #[cfg_attr(debug_assertions, allow(dead_code))]
+    max_size_mb: u32,
+}
+
+impl Default for AgentLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: dirs::home_dir()
+                .unwrap_or_default()
+                .join(".hands")
+                .join("logs")
+                .join("agent.log"),
+            max_size_mb: 10,
+        }
+    }
+}
+
+fn get_agent_log_config(app: &tauri::AppHandle) -> AgentLogConfig {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("agent_log"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Open the agent log file for appending, rotating it to `<name>.1.log` first
+/// if it has grown past `max_size_mb`
+#[cfg(not(debug_assertions))]
+fn open_agent_log(config: &AgentLogConfig) -> std::io::Result<fs::File> {
+    if let Some(parent) = config.path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let max_bytes = config.max_size_mb as u64 * 1024 * 1024;
+    if fs::metadata(&config.path).map(|m| m.len() > max_bytes).unwrap_or(false) {
+        let stem = config.path.file_stem().and_then(|s| s.to_str()).unwrap_or("agent");
+        let backup = config.path.with_file_name(format!("{}.1.log", stem));
+        fs::rename(&config.path, &backup)?;
+    }
+
+    fs::OpenOptions::new().create(true).append(true).open(&config.path)
+}
+
+/// Path of the agent log file, if agent output is being written to one
+#[tauri::command]
+fn get_agent_log_path(app: tauri::AppHandle) -> Option<String> {
+    if cfg!(debug_assertions) {
+        return None;
+    }
+    let config = get_agent_log_config(&app);
+    config.enabled.then(|| config.path.to_string_lossy().to_string())
+}
+
 async fn start_opencode_server(
+    app: &tauri::AppHandle,
     port: u16,
     model: Option<String>,
     env_vars: HashMap<String, String>,
@@ -1441,6 +4326,7 @@ async fn start_opencode_server(
 
     // Set port and model for the agent server
     all_env.insert("HANDS_AGENT_PORT".to_string(), port.to_string());
+    all_env.insert("HANDS_PORT_PREFIX".to_string(), port_prefix(app).to_string());
     if let Some(ref m) = model {
         all_env.insert("HANDS_MODEL".to_string(), m.clone());
     }
@@ -1452,12 +4338,29 @@ async fn start_opencode_server(
     }
 
     // Build command - run from the workbook directory if provided
-    let mut cmd = sidecar::command(sidecar::Sidecar::Agent);
+    let mut cmd = sidecar::command(app, sidecar::Sidecar::Agent);
     cmd.envs(&all_env)
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .kill_on_drop(true);
 
+    // Production: send agent output to a rotating log file instead of our stdout
+    #[cfg(not(debug_assertions))]
+    {
+        let log_config = get_agent_log_config(app);
+        if log_config.enabled {
+            match open_agent_log(&log_config).and_then(|file| Ok((file.try_clone()?, file))) {
+                Ok((stdout, stderr)) => {
+                    cmd.stdout(Stdio::from(stdout)).stderr(Stdio::from(stderr));
+                    println!("Hands agent logging to {}", log_config.path.display());
+                }
+                Err(e) => eprintln!("Failed to open agent log {}: {}", log_config.path.display(), e),
+            }
+        }
+    }
+    #[cfg(debug_assertions)]
+    let _ = app;
+
     if let Some(ref dir) = working_dir {
         cmd.current_dir(dir);
         println!("Hands agent working directory: {}", dir);
@@ -1492,6 +4395,15 @@ async fn wait_for_server(port: u16, timeout_secs: u64) -> bool {
     false
 }
 
+/// Take the agent server out of `AppState` and kill it.
+/// Callers hold `AgentServerMutex` until the replacement is stored.
+async fn stop_agent_server(state: &Arc<Mutex<AppState>>) {
+    let old_server = state.lock().await.server.take();
+    if let Some(mut server) = old_server {
+        let _ = server.kill().await;
+    }
+}
+
 /// Restart OpenCode server with explicit workbook directory
 /// This is the core function that ensures OpenCode runs in the correct directory
 async fn restart_server_with_dir(
@@ -1500,29 +4412,35 @@ async fn restart_server_with_dir(
     workbook_id: String,
     workbook_dir: String,
 ) -> Result<HealthCheck, String> {
-    let mut state_guard = state.lock().await;
+    // Serialize agent server restarts for the whole stop -> start -> store sequence
+    let agent_lock = app.state::<AgentServerMutex>();
+    let _agent_guard = agent_lock.0.lock().await;
 
-    if let Some(ref mut server) = state_guard.server {
-        let _ = server.kill().await;
-    }
+    stop_agent_server(&state).await;
+    let runtime_port = state.lock().await.runtime_manager.get(&workbook_id).map(|r| r.runtime_port);
 
-    let mut env_vars = get_api_keys_from_store(&app);
+    let mut env_vars = get_api_keys_from_store(&app, Some(&workbook_dir));
 
     // Add database URL if runtime is available (optional - AI works without DB)
     // Set runtime port for agent tools to access SQLite via tRPC
-    if let Some(runtime) = state_guard.workbook_servers.get(&workbook_id) {
-        env_vars.insert("HANDS_RUNTIME_PORT".to_string(), runtime.runtime_port.to_string());
-        println!("Setting HANDS_RUNTIME_PORT for workbook {}: {}", workbook_id, runtime.runtime_port);
+    if let Some(runtime_port) = runtime_port {
+        env_vars.insert("HANDS_RUNTIME_PORT".to_string(), runtime_port.to_string());
+        println!("Setting HANDS_RUNTIME_PORT for workbook {}: {}", workbook_id, runtime_port);
     }
 
     println!("Restarting OpenCode server with working directory: {}", workbook_dir);
 
     // Model defaults to OpenRouter in agent
-    match start_opencode_server(PORT_OPENCODE, None, env_vars, Some(workbook_dir)).await {
+    match start_opencode_server(&app, port_opencode(&app), None, env_vars, Some(workbook_dir)).await {
         Ok(child) => {
-            state_guard.server = Some(child);
+            {
+                let mut state_guard = state.lock().await;
+                state_guard.server = Some(child);
+                // Tell the SSE listener to reconnect to the (possibly new) port
+                state_guard.agent_port_tx.send_replace(port_opencode(&app));
+            }
 
-            if wait_for_server(PORT_OPENCODE, 30).await {
+            if wait_for_server(port_opencode(&app), 30).await {
                 Ok(HealthCheck {
                     healthy: true,
                     message: "Server restarted successfully".to_string(),
@@ -1551,7 +4469,7 @@ async fn restart_server(
     // Get active workbook info
     let (workbook_id, workbook_dir) = if let Some(ref id) = state_guard.active_workbook_id {
         // Try to get directory from runtime first, fall back to computing it
-        let dir = if let Some(runtime) = state_guard.workbook_servers.get(id) {
+        let dir = if let Some(runtime) = state_guard.runtime_manager.get(id) {
             runtime.directory.clone()
         } else {
             get_workbook_dir(id)?.to_string_lossy().to_string()
@@ -1569,20 +4487,19 @@ async fn restart_server(
     } else {
         // No active workbook - start without a working directory (legacy behavior)
         println!("WARNING: Restarting OpenCode without active workbook - sessions will be isolated");
-        let mut state_guard = state.lock().await;
+        let agent_lock = app.state::<AgentServerMutex>();
+        let _agent_guard = agent_lock.0.lock().await;
 
-        if let Some(ref mut server) = state_guard.server {
-            let _ = server.kill().await;
-        }
+        stop_agent_server(&state).await;
 
-        let env_vars = get_api_keys_from_store(&app);
+        let env_vars = get_api_keys_from_store(&app, None);
 
         // Model defaults to OpenRouter in agent
-        match start_opencode_server(PORT_OPENCODE, None, env_vars, None).await {
+        match start_opencode_server(&app, port_opencode(&app), None, env_vars, None).await {
             Ok(child) => {
-                state_guard.server = Some(child);
+                state.lock().await.server = Some(child);
 
-                if wait_for_server(PORT_OPENCODE, 30).await {
+                if wait_for_server(port_opencode(&app), 30).await {
                     Ok(HealthCheck {
                         healthy: true,
                         message: "Server restarted successfully (no workbook)".to_string(),
@@ -1670,6 +4587,271 @@ async fn copy_files_to_workbook(
     })
 }
 
+/// A file or directory in a workbook, for the sidebar file explorer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileNode {
+    pub name: String,
+    /// Path relative to the workbook root
+    pub path: String,
+    pub is_dir: bool,
+    pub size_bytes: Option<u64>,
+    pub children: Option<Vec<FileNode>>,
+}
+
+/// Skip dependency, git object store, and database directories when walking a workbook
+fn is_hidden_from_file_tree(relative: &std::path::Path) -> bool {
+    let mut components = relative.components().map(|c| c.as_os_str());
+    match (components.next(), components.next()) {
+        (Some(first), _) if first == "node_modules" || first == "postgres" => true,
+        (Some(first), Some(second)) if first == ".git" && second == "objects" => true,
+        _ => relative.components().any(|c| c.as_os_str() == "node_modules"),
+    }
+}
+
+/// Resolve a path relative to a workbook, rejecting paths that escape the workbook directory
+fn resolve_workbook_path(workbook_dir: &std::path::Path, relative_path: &str) -> Result<PathBuf, String> {
+    let root = workbook_dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve workbook directory: {}", e))?;
+    let path = root.join(relative_path).canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", relative_path, e))?;
+
+    if !path.starts_with(&root) {
+        return Err(format!("Path {} is outside the workbook", relative_path));
+    }
+    if path == root {
+        return Err(format!("Path {} is the workbook directory, not a file", relative_path));
+    }
+
+    Ok(path)
+}
+
+/// Get the workbook's file structure as a tree (paths relative to the workbook root)
+#[tauri::command]
+async fn workbook_file_tree(
+    workbook_id: String,
+    max_depth: Option<u8>,
+) -> Result<Vec<FileNode>, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+
+    let max_depth = max_depth.unwrap_or(3) as usize;
+
+    tokio::task::spawn_blocking(move || {
+        let walker = walkdir::WalkDir::new(&workbook_dir)
+            .min_depth(1)
+            .max_depth(max_depth)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.path()
+                    .strip_prefix(&workbook_dir)
+                    .map(|relative| !is_hidden_from_file_tree(relative))
+                    .unwrap_or(false)
+            });
+
+        // walkdir yields entries depth-first, so a stack of open directories is enough
+        // to rebuild the tree
+        let mut roots: Vec<FileNode> = Vec::new();
+        let mut stack: Vec<FileNode> = Vec::new();
+
+        fn attach(node: FileNode, stack: &mut [FileNode], roots: &mut Vec<FileNode>) {
+            match stack.last_mut() {
+                Some(parent) => parent.children.get_or_insert_with(Vec::new).push(node),
+                None => roots.push(node),
+            }
+        }
+
+        for entry in walker.filter_map(|e| e.ok()) {
+            let relative = entry.path()
+                .strip_prefix(&workbook_dir)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .to_string();
+            let is_dir = entry.file_type().is_dir();
+            let size_bytes = if is_dir {
+                None
+            } else {
+                fs::metadata(entry.path()).ok().map(|m| m.len())
+            };
+
+            while stack.len() >= entry.depth() {
+                let done = stack.pop().unwrap();
+                attach(done, &mut stack, &mut roots);
+            }
+
+            stack.push(FileNode {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: relative,
+                is_dir,
+                size_bytes,
+                children: if is_dir { Some(Vec::new()) } else { None },
+            });
+        }
+
+        while let Some(done) = stack.pop() {
+            attach(done, &mut stack, &mut roots);
+        }
+
+        roots
+    })
+    .await
+    .map_err(|e| format!("Failed to walk workbook directory: {}", e))
+}
+
+/// Read a text file from a workbook (UTF-8, up to 1 MB; see `read_workbook_file`)
+#[tauri::command]
+async fn workbook_read_file(
+    workbook_id: String,
+    relative_path: String,
+) -> Result<String, String> {
+    read_workbook_file(workbook_id, relative_path).await.map(|content| content.text)
+}
+
+/// Largest file `read_workbook_file` will load for in-app editing
+const MAX_EDITABLE_FILE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileContent {
+    pub text: String,
+    pub encoding: String,
+    pub size_bytes: u64,
+    pub last_modified_ms: u64,
+}
+
+/// Resolve a path that may not exist yet (for writes), rejecting paths that escape
+/// the workbook directory. The deepest existing ancestor is canonicalized so symlinks
+/// can't be used to break out.
+fn resolve_new_workbook_path(workbook_dir: &std::path::Path, relative_path: &str) -> Result<PathBuf, String> {
+    use std::path::Component;
+
+    let relative = std::path::Path::new(relative_path);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Path {} is outside the workbook", relative_path));
+    }
+
+    // "" and "." would resolve to the workbook directory itself
+    if !relative.components().any(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Path {} is the workbook directory, not a file", relative_path));
+    }
+
+    let root = workbook_dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve workbook directory: {}", e))?;
+    let path = root.join(relative);
+
+    let mut existing = path.as_path();
+    while !existing.exists() {
+        existing = existing.parent().unwrap_or(&root);
+    }
+    let existing = existing.canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", relative_path, e))?;
+    if !existing.starts_with(&root) {
+        return Err(format!("Path {} is outside the workbook", relative_path));
+    }
+
+    Ok(path)
+}
+
+/// Write a file by writing a temporary sibling and renaming it over the target,
+/// so readers never see a partially written file
+async fn atomic_write(path: &std::path::Path, content: &str) -> Result<(), String> {
+    let file_name = path.file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    tokio::fs::write(&tmp_path, content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Read a workbook file for in-app editing (UTF-8 text, up to 1 MB)
+#[tauri::command]
+async fn read_workbook_file(
+    workbook_id: String,
+    relative_path: String,
+) -> Result<FileContent, String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    let path = resolve_workbook_path(&workbook_dir, &relative_path)?;
+
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", relative_path, e))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", relative_path));
+    }
+    if metadata.len() > MAX_EDITABLE_FILE_BYTES {
+        return Err(format!("{} is too large to edit ({} bytes)", relative_path, metadata.len()));
+    }
+
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", relative_path, e))?;
+    let text = String::from_utf8(bytes)
+        .map_err(|_| format!("{} is not a UTF-8 text file", relative_path))?;
+
+    let last_modified_ms = metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    Ok(FileContent {
+        text,
+        encoding: "utf-8".to_string(),
+        size_bytes: metadata.len(),
+        last_modified_ms,
+    })
+}
+
+/// Write a workbook file, optionally creating missing parent directories
+#[tauri::command]
+async fn write_workbook_file(
+    workbook_id: String,
+    relative_path: String,
+    content: String,
+    create_dirs: bool,
+) -> Result<(), String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    let path = resolve_new_workbook_path(&workbook_dir, &relative_path)?;
+
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            if !create_dirs {
+                return Err(format!("Directory for {} does not exist", relative_path));
+            }
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create directories for {}: {}", relative_path, e))?;
+        }
+    }
+
+    atomic_write(&path, &content).await
+}
+
+/// Delete a file from a workbook
+#[tauri::command]
+async fn delete_workbook_file(
+    workbook_id: String,
+    relative_path: String,
+) -> Result<(), String> {
+    let workbook_dir = get_workbook_dir(&workbook_id)?;
+    let path = resolve_workbook_path(&workbook_dir, &relative_path)?;
+
+    if path.is_dir() {
+        return Err(format!("{} is a directory", relative_path));
+    }
+
+    tokio::fs::remove_file(&path)
+        .await
+        .map_err(|e| format!("Failed to delete {}: {}", relative_path, e))
+}
+
 #[tauri::command]
 async fn open_webview(
     app: tauri::AppHandle,
@@ -1785,6 +4967,13 @@ async fn pick_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
         .map_err(|e| format!("Failed to receive folder path: {}", e))
 }
 
+/// Pick a folder and import it as a new workbook. Returns `None` if the picker was cancelled.
+#[tauri::command]
+async fn pick_and_import_workbook(app: tauri::AppHandle) -> Result<Option<Workbook>, HandsError> {
+    let Some(src_dir) = pick_folder(app.clone()).await? else { return Ok(None) };
+    import_workbook_from_directory(app, src_dir, None).await.map(Some)
+}
+
 /// Open a workbook in its own window
 /// Delegates to centralized window_manager for consistent behavior
 #[tauri::command]
@@ -1849,6 +5038,8 @@ async fn close_workbook_window(
         window.close().map_err(|e| e.to_string())?;
     }
 
+    hotkeys::unregister_workbook_hotkeys_internal(&app, state.inner(), &workbook_id).await;
+
     // Unregister window and check if runtime should be stopped
     {
         let mut state_guard = state.lock().await;
@@ -1874,6 +5065,140 @@ async fn list_workbook_windows(
     Ok(state_guard.runtime_manager.workbook_ids())
 }
 
+/// Snapshot all managed runtimes, ping each one's `/status` endpoint concurrently
+/// without holding the state lock, then write the results back.
+/// `running` reflects the live health check rather than just process presence.
+async fn collect_running_runtimes(state: &Arc<Mutex<AppState>>) -> Vec<runtime_manager::RuntimeStatus> {
+    let snapshots = state.lock().await.runtime_manager.snapshot();
+
+    let client = reqwest::Client::new();
+    let health_checks = snapshots.iter().map(|snapshot| {
+        let url = format!("http://localhost:{}/status", snapshot.runtime_port);
+        let client = client.clone();
+        async move {
+            matches!(
+                client.get(&url).timeout(Duration::from_secs(2)).send().await,
+                Ok(resp) if resp.status().is_success()
+            )
+        }
+    });
+    let healthy = futures_util::future::join_all(health_checks).await;
+
+    let mut statuses = {
+        let mut state_guard = state.lock().await;
+        for (snapshot, is_healthy) in snapshots.iter().zip(healthy) {
+            state_guard.runtime_manager.set_health(snapshot, is_healthy);
+        }
+        state_guard.runtime_manager.statuses()
+    };
+
+    statuses.sort_by(|a, b| a.workbook_id.cmp(&b.workbook_id));
+    statuses
+}
+
+/// List all running runtimes with live health, restart count, and uptime.
+/// Shares `get_all_runtime_statuses`'s health-checked snapshot.
+#[tauri::command]
+async fn list_running_runtimes(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<runtime_manager::RuntimeStatus>, String> {
+    get_all_runtime_statuses(state).await
+}
+
+/// Recent HTTP requests logged by a workbook's runtime (oldest first, up to 200)
+#[tauri::command]
+async fn get_workbook_network_activity(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+) -> Result<Vec<runtime_manager::NetworkRequest>, String> {
+    let state_guard = state.lock().await;
+    let runtime = state_guard.runtime_manager.get(&workbook_id)
+        .ok_or_else(|| format!("No runtime running for workbook {}", workbook_id))?;
+    let log = runtime.network_log.lock().unwrap();
+    Ok(log.iter().cloned().collect())
+}
+
+/// Last `lines` lines (default: 200) of the workbook's most recent runtime log (stderr)
+#[tauri::command]
+async fn get_runtime_log(
+    app: tauri::AppHandle,
+    workbook_id: String,
+    lines: Option<u32>,
+) -> Result<Vec<String>, String> {
+    let log_dir = runtime_log_dir(&app, &workbook_id)?;
+    let lines = lines.unwrap_or(DEFAULT_RUNTIME_LOG_LINES) as usize;
+
+    tokio::task::spawn_blocking(move || {
+        let Some(path) = runtime_log_files(&log_dir).pop() else { return Ok(Vec::new()) };
+        let content = fs::read(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let content = String::from_utf8_lossy(&content);
+        let all: Vec<&str> = content.lines().collect();
+        Ok(all[all.len().saturating_sub(lines)..].iter().map(|l| l.to_string()).collect())
+    })
+    .await
+    .map_err(|e| format!("Failed to read runtime log: {}", e))?
+}
+
+/// Recent stdout lines from a workbook's runtime (oldest first, up to 1000)
+#[tauri::command]
+async fn get_runtime_output(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+) -> Result<Vec<String>, String> {
+    let state_guard = state.lock().await;
+    let runtime = state_guard.runtime_manager.get(&workbook_id)
+        .ok_or_else(|| format!("No runtime running for workbook {}", workbook_id))?;
+    let output = runtime.stdout_lines.lock().unwrap();
+    Ok(output.iter().cloned().collect())
+}
+
+/// Current CPU and memory usage of a workbook's runtime process
+#[tauri::command]
+async fn get_runtime_metrics(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+) -> Result<runtime_manager::RuntimeMetrics, String> {
+    let (pid, uptime) = {
+        let state_guard = state.lock().await;
+        let runtime = state_guard.runtime_manager.get(&workbook_id)
+            .ok_or_else(|| format!("No runtime running for workbook {}", workbook_id))?;
+        let pid = runtime.process.id()
+            .ok_or_else(|| format!("Runtime for workbook {} has exited", workbook_id))?;
+        (pid, runtime.uptime())
+    };
+
+    // Sampling takes a moment, so don't hold the state lock
+    runtime_manager::sample_process_metrics(&workbook_id, pid, uptime).await
+}
+
+/// Sample metrics for every running runtime (used by the monitor's periodic event)
+async fn collect_runtime_metrics(state: &Arc<Mutex<AppState>>) -> Vec<runtime_manager::RuntimeMetrics> {
+    let processes: Vec<(String, u32, Duration)> = {
+        let state_guard = state.lock().await;
+        state_guard.runtime_manager.iter()
+            .filter_map(|(id, r)| r.process.id().map(|pid| (id.clone(), pid, r.uptime())))
+            .collect()
+    };
+
+    let mut metrics = Vec::new();
+    for (workbook_id, pid, uptime) in processes {
+        match runtime_manager::sample_process_metrics(&workbook_id, pid, uptime).await {
+            Ok(m) => metrics.push(m),
+            Err(e) => eprintln!("[monitor] Failed to sample metrics for {}: {}", workbook_id, e),
+        }
+    }
+    metrics
+}
+
+/// Get status (ports, jobs, uptime, live health) of all managed runtimes
+#[tauri::command]
+async fn get_all_runtime_statuses(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<runtime_manager::RuntimeStatus>, String> {
+    Ok(collect_running_runtimes(state.inner()).await)
+}
+
 /// Check if a workbook has active jobs
 #[tauri::command]
 async fn has_active_jobs(
@@ -1987,18 +5312,49 @@ pub fn run() {
             check_server_health,
             restart_server,
             create_workbook,
+            list_workbook_templates,
+            create_workbook_from_directory,
             list_workbooks,
             get_workbook,
             update_workbook,
+            rename_workbook,
+            archive_workbook,
+            tag_workbook,
+            unarchive_workbook,
+            check_workbook_integrity,
+            get_workbook_disk_usage,
+            get_all_workbooks_disk_usage,
+            search_workbook_files,
+            search_all_workbooks,
+            search_all_workbooks_files,
             delete_workbook,
+            restore_workbook_from_trash,
+            workbook_git_commit,
+            get_workbook_git_log,
+            get_workbook_git_status,
+            get_agent_log_path,
+            shell_ext::show_workbook_in_file_manager,
+            get_job_tool_calls,
+            list_workbook_sessions,
+            set_runtime_memory_limit,
+            set_runtime_max_uptime,
+            cancel_job,
+            window_manager::get_recent_workbook_ids,
+            window_manager::open_workbook_devtools,
+            window_manager::close_workbook_devtools,
+            window_manager::get_developer_mode,
+            window_manager::set_developer_mode,
             start_workbook_server,
             stop_runtime,
             get_runtime_status,
             get_active_runtime,
             runtime_query,
             runtime_eval,
+            stop_eval_watch,
             copy_files_to_workbook,
             write_file_to_workbook,
+            workbook_file_tree,
+            workbook_read_file,
             open_webview,
             open_db_browser,
             open_docs,
@@ -2010,14 +5366,57 @@ pub fn run() {
             navigate_in_workbook,
             close_workbook_window,
             list_workbook_windows,
+            get_all_runtime_statuses,
+            list_running_runtimes,
             has_active_jobs,
             get_active_jobs,
+            hotkeys::simulate_shortcut,
+            hotkeys::list_registered_shortcuts,
+            hotkeys::register_workbook_hotkey,
+            hotkeys::unregister_workbook_hotkeys,
+            settings_transfer::export_settings,
+            settings_transfer::import_settings,
+            workbook_watcher::watch_workbook,
+            workbook_watcher::unwatch_workbook,
             capture::start_capture_command,
             capture::capture_region,
+            capture::capture_to_clipboard,
+            capture::save_capture_preset,
+            capture::list_capture_presets,
+            capture::delete_capture_preset,
+            capture::capture_preset,
             capture::cancel_capture,
+            capture::check_screen_recording_permission,
             capture::close_capture_panel,
             capture::set_ignore_cursor_events,
+            capture::get_capture_format,
+            capture::set_capture_format,
+            capture::capture_ocr_text,
+            capture::capture_ocr_result,
+            capture::capture_set_ocr_enabled,
+            capture::capture_set_ocr_language,
+            capture::start_capture_delayed,
+            capture::cancel_capture_countdown,
             save_api_key_and_launch,
+            validate_api_key,
+            set_provider,
+            list_providers,
+            remove_provider,
+            check_runtime_installed,
+            read_workbook_file,
+            write_workbook_file,
+            delete_workbook_file,
+            get_workbook_network_activity,
+            get_runtime_log,
+            get_runtime_output,
+            get_runtime_metrics,
+            get_workbook_env,
+            set_workbook_env,
+            export_workbook,
+            import_workbook,
+            import_workbook_from_directory,
+            pick_and_import_workbook,
+            duplicate_workbook,
             floating_chat::open_floating_chat,
             floating_chat::open_floating_chat_with_prompt,
             floating_chat::hide_floating_chat,
@@ -2026,28 +5425,62 @@ pub fn run() {
             floating_chat::toggle_floating_chat,
             floating_chat::expand_floating_chat,
             floating_chat::collapse_floating_chat,
+            floating_chat::set_floating_chat_animation_duration,
+            floating_chat::update_floating_chat_dimensions,
             floating_chat::has_open_workbook_windows,
+            floating_chat::update_floating_chat_context,
+            floating_chat::get_floating_chat_workbook,
             stt::stt_model_available,
             stt::stt_model_path,
             stt::stt_download_model,
+            stt::stt_check_for_model_update,
+            stt::stt_update_model,
             stt::stt_start_recording,
             stt::stt_stop_recording,
             stt::stt_cancel_recording,
             stt::stt_is_recording,
+            stt::stt_list_audio_devices,
+            stt::stt_set_audio_device,
             sfx::play_sfx,
+            sfx::sfx_set_volume,
+            sfx::sfx_get_volume,
+            sfx::sfx_set_muted,
+            sidecar::set_custom_sidecar,
+            sidecar::check_sidecars,
+            sidecar::get_missing_sidecars,
             websearch::websearch_query,
-            websearch::websearch_batch
+            websearch::websearch_batch,
+            websearch::search_with_context,
+            websearch::set_search_domain_whitelist,
+            websearch::websearch_fetch_page,
+            websearch::clear_search_cache,
+            websearch::set_search_provider,
+            telemetry::get_telemetry_config,
+            telemetry::set_telemetry_enabled,
+            set_port_prefix,
+            get_port_prefix
         ])
         .setup(|app| {
-            let state = Arc::new(Mutex::new(AppState {
-                server: None,
-                workbook_servers: HashMap::new(),
-                runtime_manager: RuntimeManager::new(),
-                job_registry: JobRegistry::new(),
-                active_workbook_id: None,
-                should_quit: false,
-            }));
+            let port_prefix = load_port_prefix(app.handle());
+            app.manage(PortPrefix(port_prefix));
+            println!("[ports] Using port prefix {}", port_prefix);
+
+            let telemetry_counts = telemetry::TelemetryCounts::default();
+            let (agent_port_tx, agent_port_rx) = tokio::sync::watch::channel(port_opencode(app.handle()));
+            let state = Arc::new(Mutex::new(AppState::new(
+                port_prefix,
+                agent_port_tx,
+                telemetry::load_config(app.handle()).enabled,
+                telemetry_counts.clone(),
+            )));
             app.manage(state.clone());
+            app.manage(AgentServerMutex(Mutex::new(())));
+            app.manage(stt::SttManagedState(Arc::new(std::sync::Mutex::new(
+                stt::SttState::new(stt::default_model_path(app.handle())),
+            ))));
+
+            let missing_sidecars = sidecar::report_missing(app.handle());
+            app.manage(sidecar::MissingSidecars(missing_sidecars));
 
             // Set up system tray
             if let Err(e) = tray::create_tray(app.handle()) {
@@ -2066,7 +5499,10 @@ pub fn run() {
             start_workbook_server_monitor(state.clone(), app.handle().clone());
 
             // Start SSE listener for job tracking
-            start_sse_job_listener(state.clone(), app.handle().clone());
+            start_sse_job_listener(state.clone(), app.handle().clone(), agent_port_rx);
+
+            // Periodically send opt-in usage counts
+            telemetry::start_flush_task(app.handle().clone(), telemetry_counts);
 
             // Check if API key is configured - show setup window if not
             let startup_app = app.handle().clone();
@@ -2079,16 +5515,17 @@ pub fn run() {
                     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
                     // Get first workbook, or create one if none exist
-                    let workbook = match list_workbooks().await {
+                    let workbook = match scan_workbooks(false).await {
                         Ok(workbooks) => {
                             if let Some(wb) = workbooks.into_iter().next() {
                                 wb
                             } else {
                                 // No workbooks exist - create a default one
                                 println!("[startup] No workbooks found, creating default");
-                                match create_workbook(CreateWorkbookRequest {
+                                match create_workbook(startup_app.clone(), CreateWorkbookRequest {
                                     name: "My Notebook".to_string(),
                                     description: None,
+                                    template_id: None,
                                 }).await {
                                     Ok(wb) => wb,
                                     Err(e) => {
@@ -2135,7 +5572,7 @@ pub fn run() {
                     // the workbook editor closes (see on_window_event handler)
 
                     // Play startup sound after windows are ready
-                    sfx::play("startup");
+                    sfx::play(&startup_app, "startup");
                 });
             } else {
                 // No API key - show setup window
@@ -2225,16 +5662,18 @@ pub fn run() {
             // with the correct workbook directory, avoiding a wasteful restart
             if !has_api_key {
                 let app_handle = app.handle().clone();
-                let env_vars = get_api_keys_from_store(&app_handle);
+                let env_vars = get_api_keys_from_store(&app_handle, None);
 
                 // Start Hands agent server without workbook for setup flow
                 tauri::async_runtime::spawn(async move {
-                    match start_opencode_server(PORT_OPENCODE, None, env_vars, None).await {
+                    let agent_lock = app_handle.state::<AgentServerMutex>();
+                    let _agent_guard = agent_lock.0.lock().await;
+
+                    match start_opencode_server(&app_handle, port_opencode(&app_handle), None, env_vars, None).await {
                         Ok(child) => {
-                            let mut s = state.lock().await;
-                            s.server = Some(child);
+                            state.lock().await.server = Some(child);
 
-                            if wait_for_server(PORT_OPENCODE, 30).await {
+                            if wait_for_server(port_opencode(&app_handle), 30).await {
                                 println!("Hands agent is ready!");
                             } else {
                                 eprintln!("Hands agent started but health check timed out");
@@ -2294,6 +5733,11 @@ pub fn run() {
                     }
                     // Other windows (preview, docs, chat widgets, capture overlay) can close normally
                 }
+                tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_)
+                    if window.label() == floating_chat::FLOATING_CHAT_LABEL =>
+                {
+                    floating_chat::save_geometry(window);
+                }
                 tauri::WindowEvent::Destroyed => {
                     // Only cleanup when main window is actually destroyed (from quit)
                     if window.label() == "main" {
@@ -2308,7 +5752,7 @@ pub fn run() {
                         });
 
                         // Kill OpenCode server port
-                        kill_processes_on_port(PORT_OPENCODE);
+                        kill_processes_on_port(port_opencode(window.app_handle()));
 
                         println!("[shutdown] Cleanup complete");
                     }
@@ -2319,3 +5763,144 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A runtime entry backed by a long-running dummy process
+    fn dummy_runtime(workbook_id: &str, runtime_port: u16) -> RuntimeInfo {
+        let process = tokio::process::Command::new("sleep")
+            .arg("30")
+            .kill_on_drop(true)
+            .spawn()
+            .expect("spawn dummy runtime");
+        RuntimeInfo::new(
+            workbook_id.to_string(),
+            format!("/tmp/{}", workbook_id),
+            process,
+            RuntimePorts { runtime_port, ..Default::default() },
+        )
+    }
+
+    fn test_state() -> Arc<Mutex<AppState>> {
+        let (agent_port_tx, _) = tokio::sync::watch::channel(0);
+        Arc::new(Mutex::new(AppState::new(DEFAULT_PORT_PREFIX, agent_port_tx, false, Default::default())))
+    }
+
+    #[tokio::test]
+    async fn starting_a_workbook_leaves_other_runtimes_running() {
+        let state = test_state();
+        {
+            let mut guard = state.lock().await;
+            for (workbook_id, expected_port) in [("wb-a", 55001), ("wb-b", 55002)] {
+                let port = guard.runtime_manager.allocate_port().unwrap();
+                assert_eq!(port, expected_port);
+                guard.runtime_manager.insert(workbook_id.to_string(), dummy_runtime(workbook_id, port));
+            }
+        }
+
+        // A third workbook has no previous runtime to replace
+        assert!(stop_previous_runtime(&state, "wb-c").await.is_none());
+
+        let mut guard = state.lock().await;
+        assert_eq!(guard.runtime_manager.allocate_port(), Some(55003));
+        for (workbook_id, port) in [("wb-a", 55001), ("wb-b", 55002)] {
+            let runtime = guard.runtime_manager.get_mut(workbook_id).unwrap();
+            assert_eq!(runtime.runtime_port, port);
+            assert!(runtime.process.try_wait().unwrap().is_none(), "{} was stopped", workbook_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn restarting_a_workbook_only_stops_its_own_runtime() {
+        let state = test_state();
+        {
+            let mut guard = state.lock().await;
+            for workbook_id in ["wb-a", "wb-b"] {
+                let port = guard.runtime_manager.allocate_port().unwrap();
+                let mut runtime = dummy_runtime(workbook_id, port);
+                runtime.windows.insert(format!("workbook_{}", workbook_id));
+                guard.runtime_manager.insert(workbook_id.to_string(), runtime);
+            }
+        }
+
+        let (port, windows) = stop_previous_runtime(&state, "wb-a").await.unwrap();
+        assert_eq!(port, 55001);
+        assert!(windows.contains("workbook_wb-a"));
+
+        let mut guard = state.lock().await;
+        assert!(!guard.runtime_manager.has_runtime("wb-a"));
+        let other = guard.runtime_manager.get_mut("wb-b").unwrap();
+        assert!(other.process.try_wait().unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_agent_restarts_leave_a_single_server() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let state = test_state();
+        let agent_lock = Arc::new(AgentServerMutex(Mutex::new(())));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let spawned = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let restarts: Vec<_> = (0..8)
+            .map(|_| {
+                let state = state.clone();
+                let agent_lock = agent_lock.clone();
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                let spawned = spawned.clone();
+                tokio::spawn(async move {
+                    let _agent_guard = agent_lock.0.lock().await;
+                    let running = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(running, Ordering::SeqCst);
+
+                    stop_agent_server(&state).await;
+                    // Stand-in for the agent's startup time
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    let child = tokio::process::Command::new("sleep")
+                        .arg("30")
+                        .kill_on_drop(true)
+                        .spawn()
+                        .expect("spawn dummy agent");
+                    spawned.lock().unwrap().push(child.id().unwrap());
+                    state.lock().await.server = Some(child);
+
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for restart in restarts {
+            restart.await.unwrap();
+        }
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+
+        let mut server = state.lock().await.server.take().expect("an agent server is stored");
+        assert!(server.try_wait().unwrap().is_none());
+        let live_pid = server.id().unwrap();
+
+        let spawned = spawned.lock().unwrap();
+        assert_eq!(spawned.len(), 8);
+        for pid in spawned.iter().filter(|pid| **pid != live_pid) {
+            assert!(runtime_manager::process_rss_bytes(*pid).is_none(), "agent {} was left running", pid);
+        }
+    }
+
+    #[test]
+    fn workbook_paths_reject_the_workbook_root() {
+        let workbook_dir = std::env::temp_dir().join(format!("hands-resolve-test-{}", std::process::id()));
+        fs::create_dir_all(workbook_dir.join("pages")).unwrap();
+
+        for relative_path in ["", ".", "./"] {
+            assert!(resolve_workbook_path(&workbook_dir, relative_path).is_err());
+            assert!(resolve_new_workbook_path(&workbook_dir, relative_path).is_err());
+        }
+        assert!(resolve_workbook_path(&workbook_dir, "pages").is_ok());
+        assert!(resolve_new_workbook_path(&workbook_dir, "./pages/new.md").is_ok());
+
+        fs::remove_dir_all(&workbook_dir).unwrap();
+    }
+}
@@ -2,12 +2,14 @@
 //!
 //! Handles dynamic port allocation and lifecycle management.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::process::Child;
 use serde::{Deserialize, Serialize};
 
-/// Port allocation scheme:
+/// Port allocation scheme (shown for the default prefix 55, i.e. `prefix * 1000 + n`):
 /// - 55000: Reserved (launcher/legacy)
 /// - 55001-55049: Dynamic runtime ports (workbook servers)
 /// - 55050-55099: Reserved for future use
@@ -15,8 +17,193 @@ use serde::{Deserialize, Serialize};
 /// - 55150-55199: Reserved for future use
 /// - 55200-55249: Worker ports
 /// - 55300: OpenCode server (shared)
-const RUNTIME_PORT_START: u16 = 55001;
-const RUNTIME_PORT_END: u16 = 55049;
+const RUNTIME_PORT_OFFSET_START: u16 = 1;
+const RUNTIME_PORT_OFFSET_END: u16 = 49;
+
+/// Number of recent HTTP requests kept per runtime
+const MAX_NETWORK_REQUESTS: usize = 200;
+
+/// Number of recent stdout lines kept per runtime
+const MAX_STDOUT_LINES: usize = 1000;
+
+const HTTP_METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// An HTTP request parsed from a runtime's request log output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkRequest {
+    pub method: String,
+    pub url: String,
+    pub status_code: Option<u16>,
+    pub duration_ms: Option<u64>,
+    pub timestamp_ms: u64,
+}
+
+/// Recent requests for a runtime, shared with its stdout reader task
+pub type NetworkLog = Arc<Mutex<VecDeque<NetworkRequest>>>;
+
+/// Append a request to a network log, dropping the oldest beyond the limit
+pub fn push_network_request(log: &NetworkLog, request: NetworkRequest) {
+    let mut log = log.lock().unwrap();
+    if log.len() >= MAX_NETWORK_REQUESTS {
+        log.pop_front();
+    }
+    log.push_back(request);
+}
+
+/// Recent stdout lines for a runtime, shared with its stdout reader task
+pub type OutputLog = Arc<Mutex<VecDeque<String>>>;
+
+pub fn new_output_log() -> OutputLog {
+    Arc::new(Mutex::new(VecDeque::with_capacity(MAX_STDOUT_LINES)))
+}
+
+/// Append a stdout line, dropping the oldest beyond the limit
+pub fn push_output_line(log: &OutputLog, line: String) {
+    let mut log = log.lock().unwrap();
+    if log.len() >= MAX_STDOUT_LINES {
+        log.pop_front();
+    }
+    log.push_back(line);
+}
+
+/// Remove ANSI color escapes (e.g. from morgan's `dev` format)
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip until the final byte of the escape sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parse a request log line. Handles Express/morgan style (`GET /path 200 12.3 ms`)
+/// and Apache common/combined style (`... "GET /path HTTP/1.1" 200 512 ...`).
+pub fn parse_request_line(line: &str) -> Option<NetworkRequest> {
+    let line = strip_ansi(line);
+
+    // Apache style: the request is quoted, status follows the closing quote
+    let tokens: Vec<&str> = if let Some(start) = line.find('"') {
+        let rest = &line[start + 1..];
+        let end = rest.find('"')?;
+        let mut tokens: Vec<&str> = rest[..end].split_whitespace().take(2).collect();
+        tokens.extend(rest[end + 1..].split_whitespace());
+        tokens
+    } else {
+        line.split_whitespace().collect()
+    };
+
+    let method_idx = tokens.iter().position(|t| HTTP_METHODS.contains(t))?;
+    let url = tokens.get(method_idx + 1)?;
+    if !(url.starts_with('/') || url.starts_with("http://") || url.starts_with("https://")) {
+        return None;
+    }
+
+    let rest = &tokens[method_idx + 2..];
+    let status_code = rest.first()
+        .and_then(|t| t.parse::<u16>().ok())
+        .filter(|code| (100..600).contains(code));
+    let duration_ms = rest.iter()
+        .position(|t| *t == "ms" || t.ends_with("ms"))
+        .and_then(|i| {
+            let token = rest[i].trim_end_matches("ms");
+            let value = if token.is_empty() { rest.get(i.checked_sub(1)?)? } else { token };
+            value.parse::<f64>().ok()
+        })
+        .map(|ms| ms.round() as u64);
+
+    Some(NetworkRequest {
+        method: tokens[method_idx].to_string(),
+        url: url.to_string(),
+        status_code,
+        duration_ms,
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    })
+}
+
+/// Policy used by the runtime monitor to decide when to restart a runtime
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Give up after this many consecutive restarts
+    pub max_restarts: u32,
+    /// Delay before the first restart; doubles with each consecutive restart
+    pub restart_delay: Duration,
+    /// Upper bound on the backoff delay
+    pub max_restart_delay: Duration,
+    /// A runtime that ran at least this long before crashing gets its restart count reset
+    pub stable_uptime: Duration,
+    /// Treat runtimes running longer than this as unhealthy and restart them
+    pub max_uptime_hours: Option<u32>,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            restart_delay: Duration::from_millis(2000),
+            max_restart_delay: Duration::from_millis(60_000),
+            stable_uptime: Duration::from_secs(5 * 60),
+            max_uptime_hours: None,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before the next restart of a runtime that has already been
+    /// restarted `restart_count` times: `restart_delay * 2^restart_count`, capped
+    pub fn backoff_delay(&self, restart_count: u32) -> Duration {
+        let factor = 1u32.checked_shl(restart_count).unwrap_or(u32::MAX);
+        self.restart_delay.saturating_mul(factor).min(self.max_restart_delay)
+    }
+
+    /// Check if a runtime with the given uptime has exceeded `max_uptime_hours`
+    pub fn exceeds_max_uptime(&self, uptime: Duration) -> bool {
+        self.max_uptime_hours
+            .map(|hours| uptime > Duration::from_secs(hours as u64 * 60 * 60))
+            .unwrap_or(false)
+    }
+}
+
+/// Ports reported by a workbook runtime once it is ready
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuntimePorts {
+    pub runtime_port: u16,
+    pub postgres_port: u16,
+    pub worker_port: u16,
+}
+
+/// A crashed runtime waiting out its backoff delay before being restarted
+#[derive(Debug)]
+pub struct PendingRestart {
+    pub directory: String,
+    /// Restart count the new runtime will carry
+    pub restart_count: u32,
+    /// Windows registered against the crashed runtime
+    pub windows: HashSet<String>,
+    pub crashed_at: Instant,
+    pub restart_at: Instant,
+}
+
+/// Lock-free copy of a runtime's identifying data, for work (like HTTP health
+/// checks) that must not hold the `AppState` lock across `.await`
+#[derive(Debug, Clone)]
+pub struct RuntimeSnapshot {
+    pub workbook_id: String,
+    pub runtime_port: u16,
+    pub directory: String,
+    pub restart_count: u32,
+}
 
 /// Information about a running workbook runtime
 #[derive(Debug)]
@@ -30,9 +217,35 @@ pub struct RuntimeInfo {
     pub restart_count: u32,
     pub active_jobs: AtomicUsize,
     pub windows: HashSet<String>, // window labels using this runtime
+    pub started_at: Instant,
+    pub healthy: bool,            // result of the last /status check
+    pub network_log: NetworkLog,  // recent HTTP requests seen in stdout
+    pub stdout_lines: OutputLog,  // last 1000 stdout lines
+    pub last_crash_at: Option<Instant>, // when the runtime this one replaced crashed
+    pub memory_limit_mb: Option<u64>,   // RSS limit enforced by the monitor, if any
 }
 
 impl RuntimeInfo {
+    pub fn new(workbook_id: String, directory: String, process: Child, ready: RuntimePorts) -> Self {
+        Self {
+            workbook_id,
+            runtime_port: ready.runtime_port,
+            postgres_port: ready.postgres_port,
+            worker_port: ready.worker_port,
+            process,
+            directory,
+            restart_count: 0,
+            active_jobs: AtomicUsize::new(0),
+            windows: HashSet::new(),
+            started_at: Instant::now(),
+            healthy: true,
+            network_log: NetworkLog::default(),
+            stdout_lines: new_output_log(),
+            last_crash_at: None,
+            memory_limit_mb: None,
+        }
+    }
+
     pub fn has_active_jobs(&self) -> bool {
         self.active_jobs.load(Ordering::Relaxed) > 0
     }
@@ -44,30 +257,45 @@ impl RuntimeInfo {
     pub fn decrement_jobs(&self) {
         self.active_jobs.fetch_sub(1, Ordering::Relaxed);
     }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
 }
 
 /// Manages multiple concurrent workbook runtimes
 pub struct RuntimeManager {
     /// Map of workbook_id -> RuntimeInfo
     runtimes: HashMap<String, RuntimeInfo>,
+    /// Crashed runtimes waiting to be restarted, by workbook_id
+    pending_restarts: HashMap<String, PendingRestart>,
     /// Set of allocated runtime ports
     allocated_ports: HashSet<u16>,
     /// Next port to try
     next_port: AtomicU16,
+    /// First dynamic runtime port (inclusive)
+    port_start: u16,
+    /// Last dynamic runtime port (inclusive)
+    port_end: u16,
 }
 
 impl Default for RuntimeManager {
     fn default() -> Self {
-        Self::new()
+        Self::new(crate::DEFAULT_PORT_PREFIX)
     }
 }
 
 impl RuntimeManager {
-    pub fn new() -> Self {
+    pub fn new(port_prefix: u16) -> Self {
+        let port_start = port_prefix * 1000 + RUNTIME_PORT_OFFSET_START;
+        let port_end = port_prefix * 1000 + RUNTIME_PORT_OFFSET_END;
         Self {
             runtimes: HashMap::new(),
+            pending_restarts: HashMap::new(),
             allocated_ports: HashSet::new(),
-            next_port: AtomicU16::new(RUNTIME_PORT_START),
+            next_port: AtomicU16::new(port_start),
+            port_start,
+            port_end,
         }
     }
 
@@ -81,13 +309,13 @@ impl RuntimeManager {
                 self.allocated_ports.insert(port);
                 // Move to next port for next allocation
                 self.next_port.store(
-                    if port >= RUNTIME_PORT_END { RUNTIME_PORT_START } else { port + 1 },
+                    if port >= self.port_end { self.port_start } else { port + 1 },
                     Ordering::Relaxed
                 );
                 return Some(port);
             }
 
-            port = if port >= RUNTIME_PORT_END { RUNTIME_PORT_START } else { port + 1 };
+            port = if port >= self.port_end { self.port_start } else { port + 1 };
 
             // If we've checked all ports, none available
             if port == start {
@@ -96,11 +324,37 @@ impl RuntimeManager {
         }
     }
 
+    /// Allocate a runtime port that is also free at the OS level.
+    /// Ports another process has bound are skipped (and left for later allocations).
+    pub fn allocate_available_port(&mut self) -> Option<u16> {
+        let mut busy = Vec::new();
+        let mut result = None;
+
+        while let Some(port) = self.allocate_port() {
+            if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
+                result = Some(port);
+                break;
+            }
+            // Keep it reserved until the scan finishes so we don't retry it
+            busy.push(port);
+        }
+
+        for port in busy {
+            self.release_port(port);
+        }
+        result
+    }
+
     /// Release a port back to the pool
     pub fn release_port(&mut self, port: u16) {
         self.allocated_ports.remove(&port);
     }
 
+    /// PIDs of the runtime processes this manager owns
+    pub fn owned_pids(&self) -> HashSet<u32> {
+        self.runtimes.values().filter_map(|r| r.process.id()).collect()
+    }
+
     /// Get runtime for a workbook
     pub fn get(&self, workbook_id: &str) -> Option<&RuntimeInfo> {
         self.runtimes.get(workbook_id)
@@ -117,13 +371,17 @@ impl RuntimeManager {
     }
 
     /// Insert a new runtime
-    pub fn insert(&mut self, workbook_id: String, info: RuntimeInfo) {
+    pub fn insert(&mut self, workbook_id: String, mut info: RuntimeInfo) {
+        info.started_at = Instant::now();
+        self.pending_restarts.remove(&workbook_id);
         self.allocated_ports.insert(info.runtime_port);
         self.runtimes.insert(workbook_id, info);
     }
 
     /// Remove a runtime and release its port
     pub fn remove(&mut self, workbook_id: &str) -> Option<RuntimeInfo> {
+        // An explicit stop also cancels a scheduled crash restart
+        self.pending_restarts.remove(workbook_id);
         if let Some(info) = self.runtimes.remove(workbook_id) {
             self.release_port(info.runtime_port);
             Some(info)
@@ -132,6 +390,67 @@ impl RuntimeManager {
         }
     }
 
+    /// Queue a crashed runtime to be restarted once its backoff delay elapses
+    pub fn schedule_restart(&mut self, workbook_id: String, pending: PendingRestart) {
+        self.pending_restarts.insert(workbook_id, pending);
+    }
+
+    /// Remove and return the pending restarts that are due at `now`
+    pub fn take_due_restarts(&mut self, now: Instant) -> Vec<(String, PendingRestart)> {
+        let due: Vec<String> = self.pending_restarts
+            .iter()
+            .filter(|(_, p)| p.restart_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        due.into_iter()
+            .filter_map(|id| self.pending_restarts.remove(&id).map(|p| (id, p)))
+            .collect()
+    }
+
+    /// Earliest scheduled restart time, if any
+    pub fn next_restart_at(&self) -> Option<Instant> {
+        self.pending_restarts.values().map(|p| p.restart_at).min()
+    }
+
+    /// Time until a workbook's scheduled restart, if one is pending
+    pub fn next_restart_in(&self, workbook_id: &str) -> Option<Duration> {
+        self.pending_restarts
+            .get(workbook_id)
+            .map(|p| p.restart_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Get how long a workbook's runtime has been running
+    pub fn get_runtime_uptime(&self, workbook_id: &str) -> Option<Duration> {
+        self.runtimes.get(workbook_id).map(|r| r.uptime())
+    }
+
+    /// Get status of all runtimes
+    pub fn statuses(&self) -> Vec<RuntimeStatus> {
+        self.runtimes.values().map(RuntimeStatus::from).collect()
+    }
+
+    /// Copy out the data needed to check runtimes without holding the lock
+    pub fn snapshot(&self) -> Vec<RuntimeSnapshot> {
+        self.runtimes
+            .values()
+            .map(|r| RuntimeSnapshot {
+                workbook_id: r.workbook_id.clone(),
+                runtime_port: r.runtime_port,
+                directory: r.directory.clone(),
+                restart_count: r.restart_count,
+            })
+            .collect()
+    }
+
+    /// Record a health check result, ignoring runtimes that were replaced since the snapshot
+    pub fn set_health(&mut self, snapshot: &RuntimeSnapshot, healthy: bool) {
+        if let Some(runtime) = self.runtimes.get_mut(&snapshot.workbook_id) {
+            if runtime.runtime_port == snapshot.runtime_port {
+                runtime.healthy = healthy;
+            }
+        }
+    }
+
     /// Get all workbook IDs with running runtimes
     pub fn workbook_ids(&self) -> Vec<String> {
         self.runtimes.keys().cloned().collect()
@@ -194,18 +513,137 @@ pub struct RuntimeStatus {
     pub worker_port: u16,
     pub active_jobs: usize,
     pub window_count: usize,
+    pub restart_count: u32,
+    pub uptime_secs: u64,
 }
 
 impl From<&RuntimeInfo> for RuntimeStatus {
     fn from(info: &RuntimeInfo) -> Self {
         Self {
             workbook_id: info.workbook_id.clone(),
-            running: true,
+            running: info.healthy,
             runtime_port: info.runtime_port,
             postgres_port: info.postgres_port,
             worker_port: info.worker_port,
             active_jobs: info.active_jobs.load(Ordering::Relaxed),
             window_count: info.windows.len(),
+            restart_count: info.restart_count,
+            uptime_secs: info.uptime().as_secs(),
+        }
+    }
+}
+
+/// Window over which CPU usage is measured
+const CPU_SAMPLE_WINDOW: Duration = Duration::from_millis(200);
+
+/// Resource usage of a runtime process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeMetrics {
+    pub workbook_id: String,
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub virtual_bytes: u64,
+    pub uptime_secs: u64,
+}
+
+/// Sample CPU and memory usage of a process.
+/// Takes ~200ms, since CPU usage needs two measurements.
+pub async fn sample_process_metrics(workbook_id: &str, pid: u32, uptime: Duration) -> Result<RuntimeMetrics, String> {
+    #[cfg(target_os = "linux")]
+    let (cpu_percent, rss_bytes, virtual_bytes) = {
+        // Kernel clock ticks per second (USER_HZ), fixed at 100 on every mainstream Linux
+        const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+        let ticks_before = read_proc_cpu_ticks(pid)?;
+        tokio::time::sleep(CPU_SAMPLE_WINDOW).await;
+        let ticks_after = read_proc_cpu_ticks(pid)?;
+
+        let cpu_secs = ticks_after.saturating_sub(ticks_before) as f64 / CLOCK_TICKS_PER_SEC;
+        let cpu_percent = cpu_secs / CPU_SAMPLE_WINDOW.as_secs_f64() * 100.0;
+
+        let (rss_bytes, virtual_bytes) = read_proc_memory(pid)?;
+        (cpu_percent, rss_bytes, virtual_bytes)
+    };
+
+    #[cfg(not(target_os = "linux"))]
+    let (cpu_percent, rss_bytes, virtual_bytes) = {
+        use sysinfo::{Pid, System};
+
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+        // CPU usage is computed from the difference between two refreshes
+        system.refresh_process(pid);
+        tokio::time::sleep(CPU_SAMPLE_WINDOW.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL)).await;
+        if !system.refresh_process(pid) {
+            return Err(format!("Process {} not found", pid));
+        }
+
+        let process = system.process(pid).ok_or_else(|| format!("Process {} not found", pid))?;
+        (process.cpu_usage() as f64, process.memory(), process.virtual_memory())
+    };
+
+    Ok(RuntimeMetrics {
+        workbook_id: workbook_id.to_string(),
+        cpu_percent,
+        rss_bytes,
+        virtual_bytes,
+        uptime_secs: uptime.as_secs(),
+    })
+}
+
+/// Current resident memory of a process in bytes, without the CPU sampling delay
+pub fn process_rss_bytes(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        read_proc_memory(pid).ok().map(|(rss_bytes, _)| rss_bytes)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        use sysinfo::{Pid, System};
+
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+        if !system.refresh_process(pid) {
+            return None;
         }
+        system.process(pid).map(|process| process.memory())
+    }
+}
+
+/// Total user + system CPU time of a process, in clock ticks (`/proc/<pid>/stat`)
+#[cfg(target_os = "linux")]
+fn read_proc_cpu_ticks(pid: u32) -> Result<u64, String> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map_err(|e| format!("Failed to read /proc/{}/stat: {}", pid, e))?;
+
+    // The command name (field 2) may contain spaces, so count fields after its closing paren.
+    // utime and stime are fields 14 and 15, i.e. the 12th and 13th after the paren.
+    let after_comm = stat.rsplit_once(')').map(|(_, rest)| rest).unwrap_or("");
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+    let parse = |i: usize| fields.get(i).and_then(|f| f.parse::<u64>().ok());
+
+    match (parse(11), parse(12)) {
+        (Some(utime), Some(stime)) => Ok(utime + stime),
+        _ => Err(format!("Failed to parse /proc/{}/stat", pid)),
     }
 }
+
+/// Resident and virtual memory of a process in bytes (`/proc/<pid>/status`)
+#[cfg(target_os = "linux")]
+fn read_proc_memory(pid: u32) -> Result<(u64, u64), String> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid))
+        .map_err(|e| format!("Failed to read /proc/{}/status: {}", pid, e))?;
+
+    // Lines look like "VmRSS:    123456 kB"
+    let read_kb = |key: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+
+    Ok((read_kb("VmRSS:") * 1024, read_kb("VmSize:") * 1024))
+}
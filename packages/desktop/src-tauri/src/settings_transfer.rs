@@ -0,0 +1,250 @@
+//! Settings import/export.
+//!
+//! Bundles the app's store files into one JSON envelope so settings can be
+//! moved between machines. Secrets (API keys, provider keys, secret-looking
+//! workbook env vars) are replaced with `"<redacted>"` on export and only
+//! imported when explicitly requested.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::keychain;
+
+/// Store files included in an export
+const STORE_FILES: [&str; 2] = ["settings.json", "window-state.json"];
+
+/// Envelope format version
+const EXPORT_VERSION: u32 = 1;
+
+/// Placeholder written in place of secret values
+const REDACTED: &str = "<redacted>";
+
+/// Exported settings file
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsExport {
+    version: u32,
+    exported_at: u64,
+    /// Store file name -> key/value entries
+    stores: HashMap<String, serde_json::Map<String, Value>>,
+}
+
+/// Top-level settings keys holding a secret string
+fn is_secret_key(key: &str) -> bool {
+    key == crate::OPENROUTER_KEY || key.ends_with("_api_key")
+}
+
+/// Env var names that likely hold credentials
+fn is_secret_env_var(name: &str) -> bool {
+    let name = name.to_uppercase();
+    ["KEY", "SECRET", "TOKEN", "PASSWORD"].iter().any(|word| name.contains(word))
+}
+
+fn is_workbook_env_key(key: &str) -> bool {
+    key.starts_with("workbook_env.")
+}
+
+/// Replace secrets within a store entry with `REDACTED`
+fn redact(key: &str, mut value: Value) -> Value {
+    if is_secret_key(key) {
+        return Value::String(REDACTED.to_string());
+    }
+
+    if key == crate::PROVIDERS_KEY {
+        if let Some(providers) = value.as_array_mut() {
+            for provider in providers {
+                if provider.get("api_key").is_some() {
+                    provider["api_key"] = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+    } else if key == crate::websearch::SEARCH_PROVIDER_KEY {
+        if value.get("api_key").is_some() {
+            value["api_key"] = Value::String(REDACTED.to_string());
+        }
+    } else if is_workbook_env_key(key) {
+        if let Some(env) = value.get_mut("env").and_then(|env| env.as_object_mut()) {
+            for (name, env_value) in env.iter_mut() {
+                if is_secret_env_var(name) {
+                    *env_value = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+    }
+
+    value
+}
+
+/// Write all store files to `dest_path` as one JSON document, with secrets redacted
+#[tauri::command]
+pub async fn export_settings(app: AppHandle, dest_path: String) -> Result<(), String> {
+    let mut stores = HashMap::new();
+    for file in STORE_FILES {
+        let store = app.store(file)
+            .map_err(|e| format!("Failed to open {}: {}", file, e))?;
+        let entries = store.entries()
+            .into_iter()
+            .map(|(key, value)| {
+                let value = redact(&key, value);
+                (key, value)
+            })
+            .collect();
+        stores.insert(file.to_string(), entries);
+    }
+
+    let export = SettingsExport {
+        version: EXPORT_VERSION,
+        exported_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        stores,
+    };
+
+    let content = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    std::fs::write(&dest_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", dest_path, e))?;
+
+    println!("[settings] Exported settings to {}", dest_path);
+    Ok(())
+}
+
+/// Store a secret in the keychain where available, returning the value to keep in the store
+fn store_secret(account: &str, secret: &str) -> Value {
+    Value::String(keychain::store_secret(account, secret))
+}
+
+/// Merge imported providers into the existing list. Redacted keys (or all keys
+/// when secrets aren't imported) keep the existing provider's key; providers
+/// with no key to keep are skipped.
+fn merge_providers(imported: &Value, existing: Option<Value>, overwrite_secrets: bool) -> Value {
+    let mut merged: Vec<Value> = existing
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default();
+
+    for provider in imported.as_array().into_iter().flatten() {
+        let Some(name) = provider.get("name").and_then(|n| n.as_str()) else { continue };
+        let api_key = provider.get("api_key").and_then(|k| k.as_str()).unwrap_or(REDACTED);
+        let position = merged.iter().position(|p| p.get("name").and_then(|n| n.as_str()) == Some(name));
+
+        let api_key = if overwrite_secrets && api_key != REDACTED && !api_key.is_empty() {
+            store_secret(&crate::provider_keychain_key(name), api_key)
+        } else {
+            match position.and_then(|i| merged[i].get("api_key").cloned()) {
+                Some(existing_key) => existing_key,
+                None => {
+                    println!("[settings] Skipping provider {} without an API key", name);
+                    continue;
+                }
+            }
+        };
+
+        let mut provider = provider.clone();
+        provider["api_key"] = api_key;
+        match position {
+            Some(i) => merged[i] = provider,
+            None => merged.push(provider),
+        }
+    }
+
+    Value::Array(merged)
+}
+
+/// Keep the existing search provider API key unless importing secrets.
+/// Returns `None` (skip the entry) if there's no key to keep.
+fn merge_search_provider(imported: &Value, existing: Option<Value>, overwrite_secrets: bool) -> Option<Value> {
+    let Some(api_key) = imported.get("api_key").and_then(|k| k.as_str()) else {
+        return Some(imported.clone());
+    };
+
+    let mut merged = imported.clone();
+    merged["api_key"] = if overwrite_secrets && api_key != REDACTED && !api_key.is_empty() {
+        store_secret("search_provider.api_key", api_key)
+    } else {
+        existing.and_then(|v| v.get("api_key").cloned())?
+    };
+    Some(merged)
+}
+
+/// Merge imported workbook env overrides, keeping existing values for redacted
+/// (or, unless importing secrets, secret-looking) variables
+fn merge_workbook_env(imported: &Value, existing: Option<Value>, overwrite_secrets: bool) -> Value {
+    let existing_env = existing
+        .as_ref()
+        .and_then(|v| v.get("env"))
+        .and_then(|env| env.as_object().cloned())
+        .unwrap_or_default();
+
+    let mut merged = imported.clone();
+    if let Some(env) = merged.get_mut("env").and_then(|env| env.as_object_mut()) {
+        env.retain(|name, value| {
+            let keep_existing = value.as_str() == Some(REDACTED) || (!overwrite_secrets && is_secret_env_var(name));
+            if !keep_existing {
+                return true;
+            }
+            match existing_env.get(name) {
+                Some(existing_value) => {
+                    *value = existing_value.clone();
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+    merged
+}
+
+/// Merge settings exported by `export_settings` into the stores.
+///
+/// Secrets are only imported when `overwrite_secrets` is set (and never from
+/// redacted values); API keys go to the keychain where available.
+#[tauri::command]
+pub async fn import_settings(app: AppHandle, src_path: String, overwrite_secrets: bool) -> Result<(), String> {
+    let content = std::fs::read_to_string(&src_path)
+        .map_err(|e| format!("Failed to read {}: {}", src_path, e))?;
+    let import: SettingsExport = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid settings file: {}", e))?;
+
+    if import.version != EXPORT_VERSION {
+        return Err(format!("Unsupported settings file version: {}", import.version));
+    }
+    if let Some(unknown) = import.stores.keys().find(|file| !STORE_FILES.contains(&file.as_str())) {
+        return Err(format!("Invalid settings file: unknown store {}", unknown));
+    }
+
+    for (file, entries) in &import.stores {
+        let store = app.store(file.as_str())
+            .map_err(|e| format!("Failed to open {}: {}", file, e))?;
+
+        for (key, value) in entries {
+            let merged = if is_secret_key(key) {
+                match value.as_str() {
+                    Some(secret) if overwrite_secrets && secret != REDACTED && !secret.is_empty() => {
+                        store_secret(key, secret)
+                    }
+                    _ => continue,
+                }
+            } else if key == crate::PROVIDERS_KEY {
+                merge_providers(value, store.get(key), overwrite_secrets)
+            } else if key == crate::websearch::SEARCH_PROVIDER_KEY {
+                match merge_search_provider(value, store.get(key), overwrite_secrets) {
+                    Some(merged) => merged,
+                    None => continue,
+                }
+            } else if is_workbook_env_key(key) {
+                merge_workbook_env(value, store.get(key), overwrite_secrets)
+            } else {
+                value.clone()
+            };
+            store.set(key.clone(), merged);
+        }
+
+        store.save().map_err(|e| format!("Failed to save {}: {}", file, e))?;
+    }
+
+    println!("[settings] Imported settings from {} (secrets: {})", src_path, overwrite_secrets);
+    Ok(())
+}
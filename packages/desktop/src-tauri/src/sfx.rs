@@ -1,45 +1,89 @@
 //! Sound effects playback using rodio.
 //!
-//! Plays bundled MP3 files for UI feedback.
+//! Plays bundled MP3 files for UI feedback. Per-sound volumes and a global
+//! mute are read from the settings store.
 
 use rodio::{Decoder, OutputStream, Sink};
 use std::io::Cursor;
 use std::thread;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
 
 // Embed the sound files at compile time
 const STARTUP_MP3: &[u8] = include_bytes!("../resources/sfx/hands-startup.mp3");
 const CONFIRM_MP3: &[u8] = include_bytes!("../resources/sfx/hands-confirm.mp3");
 const ERROR_MP3: &[u8] = include_bytes!("../resources/sfx/hands-error.mp3");
+/// Placeholder (a copy of the confirm sound) until a dedicated sound is recorded
+const PROCESSING_MP3: &[u8] = include_bytes!("../resources/sfx/hands-processing.mp3");
 
-/// Play a sound effect by name
-pub fn play(name: &str) {
-    let data: &'static [u8] = match name {
-        "startup" => STARTUP_MP3,
-        "confirm" => CONFIRM_MP3,
-        "error" => ERROR_MP3,
-        _ => {
-            eprintln!("[sfx] Unknown sound: {}", name);
-            return;
-        }
+/// Volume used when no per-sound volume is set
+const DEFAULT_VOLUME: f32 = 0.7;
+
+/// Settings key prefix for per-sound volumes (`sfx_volumes.<name>`)
+const VOLUME_KEY_PREFIX: &str = "sfx_volumes.";
+
+/// Settings key for the global mute toggle
+const MUTED_KEY: &str = "sfx_muted";
+
+fn sound_data(name: &str) -> Option<&'static [u8]> {
+    match name {
+        "startup" => Some(STARTUP_MP3),
+        "confirm" => Some(CONFIRM_MP3),
+        "error" => Some(ERROR_MP3),
+        "processing" => Some(PROCESSING_MP3),
+        _ => None,
+    }
+}
+
+fn volume_key(name: &str) -> String {
+    format!("{}{}", VOLUME_KEY_PREFIX, name)
+}
+
+fn get_volume(app: &AppHandle, name: &str) -> f32 {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(volume_key(name)))
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(DEFAULT_VOLUME)
+}
+
+fn is_muted(app: &AppHandle) -> bool {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(MUTED_KEY))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Play a sound effect by name, honoring the mute and volume settings
+pub fn play(app: &AppHandle, name: &str) {
+    let Some(data) = sound_data(name) else {
+        eprintln!("[sfx] Unknown sound: {}", name);
+        return;
     };
 
+    if is_muted(app) {
+        return;
+    }
+    let volume = get_volume(app, name);
+
     // Clone the name for the error message
     let name_owned = name.to_string();
 
     // Play in background thread to not block
     thread::spawn(move || {
-        if let Err(e) = play_bytes(data) {
+        if let Err(e) = play_bytes(data, volume) {
             eprintln!("[sfx] Failed to play {}: {}", name_owned, e);
         }
     });
 }
 
-fn play_bytes(data: &'static [u8]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+fn play_bytes(data: &'static [u8], volume: f32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let (_stream, stream_handle) = OutputStream::try_default()?;
     let sink = Sink::try_new(&stream_handle)?;
 
-    // Set volume to 70%
-    sink.set_volume(0.7);
+    sink.set_volume(volume);
 
     let cursor = Cursor::new(data);
     let source = Decoder::new(cursor)?;
@@ -52,6 +96,41 @@ fn play_bytes(data: &'static [u8]) -> Result<(), Box<dyn std::error::Error + Sen
 
 /// Tauri command to play sfx from frontend
 #[tauri::command]
-pub fn play_sfx(name: String) {
-    play(&name);
+pub fn play_sfx(app: AppHandle, name: String) {
+    play(&app, &name);
+}
+
+/// Set the playback volume (0.0 - 1.0) for a sound
+#[tauri::command]
+pub fn sfx_set_volume(app: AppHandle, name: String, volume: f32) -> Result<(), String> {
+    if sound_data(&name).is_none() {
+        return Err(format!("Unknown sound: {}", name));
+    }
+    if !(0.0..=1.0).contains(&volume) {
+        return Err(format!("Volume must be between 0.0 and 1.0, got {}", volume));
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(volume_key(&name), serde_json::json!(volume));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    Ok(())
+}
+
+/// Playback volume for a sound (defaults to 0.7)
+#[tauri::command]
+pub fn sfx_get_volume(app: AppHandle, name: String) -> f32 {
+    get_volume(&app, &name)
+}
+
+/// Mute or unmute all sound effects
+#[tauri::command]
+pub fn sfx_set_muted(app: AppHandle, muted: bool) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(MUTED_KEY, serde_json::json!(muted));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    Ok(())
 }
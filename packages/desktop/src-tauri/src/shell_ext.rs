@@ -0,0 +1,47 @@
+//! OS shell integration helpers.
+//!
+//! Reveals workbook directories in the platform file manager.
+
+use std::process::Command;
+
+/// Menu label for revealing a path in the platform file manager
+pub fn file_manager_label() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Show in Finder"
+    } else if cfg!(target_os = "windows") {
+        "Show in Explorer"
+    } else {
+        "Open Files"
+    }
+}
+
+/// Reveal `path` in the OS file manager
+pub fn show_in_file_manager(path: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let result = Command::new("open").arg("-R").arg(path).spawn();
+
+    #[cfg(target_os = "windows")]
+    let result = Command::new("explorer").arg(format!("/select,{}", path)).spawn();
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let result = {
+        // xdg-open can't select a file, so open the containing directory
+        let path = std::path::Path::new(path);
+        let dirname = path.parent().unwrap_or(path);
+        Command::new("xdg-open").arg(dirname).spawn()
+    };
+
+    result
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open file manager: {}", e))
+}
+
+/// Reveal a workbook's directory in the OS file manager
+#[tauri::command]
+pub fn show_workbook_in_file_manager(workbook_id: String) -> Result<(), String> {
+    let workbook_dir = crate::get_workbook_dir(&workbook_id)?;
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+    show_in_file_manager(&workbook_dir.to_string_lossy())
+}
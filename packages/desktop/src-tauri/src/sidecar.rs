@@ -3,11 +3,18 @@
 //! Always uses pre-compiled standalone binaries to ensure dev/prod parity.
 //! Run `bun run build:sidecars` to compile the TypeScript sidecars.
 
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
 use tokio::process::Command;
 
+/// Settings key holding user-provided sidecar overrides (`{ "<sidecar_type>": "<path>" }`)
+const CUSTOM_SIDECARS_KEY: &str = "custom_sidecars";
+
 /// Available sidecar binaries
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Sidecar {
     /// Workbook initialization CLI
     Cli,
@@ -17,6 +24,8 @@ pub enum Sidecar {
     Agent,
     /// Bun runtime (for vite dev server)
     Bun,
+    /// User-provided external binary
+    Custom(PathBuf),
 }
 
 impl Sidecar {
@@ -27,12 +36,69 @@ impl Sidecar {
             Sidecar::WorkbookServer => "hands-workbook-server",
             Sidecar::Agent => "hands-agent",
             Sidecar::Bun => "bun",
+            Sidecar::Custom(_) => "custom",
+        }
+    }
+
+    /// Parse a sidecar type key as used in `custom_sidecars` settings
+    pub fn from_type(sidecar_type: &str) -> Option<Sidecar> {
+        match sidecar_type {
+            "cli" => Some(Sidecar::Cli),
+            "workbook-server" => Some(Sidecar::WorkbookServer),
+            "agent" => Some(Sidecar::Agent),
+            "bun" => Some(Sidecar::Bun),
+            _ => None,
+        }
+    }
+
+    /// Key used for this sidecar in `custom_sidecars` settings
+    fn type_key(&self) -> Option<&'static str> {
+        match self {
+            Sidecar::Cli => Some("cli"),
+            Sidecar::WorkbookServer => Some("workbook-server"),
+            Sidecar::Agent => Some("agent"),
+            Sidecar::Bun => Some("bun"),
+            Sidecar::Custom(_) => None,
         }
     }
 }
 
+/// Built-in sidecars that must be present for the app to work
+const REQUIRED_SIDECARS: [Sidecar; 4] = [Sidecar::Cli, Sidecar::WorkbookServer, Sidecar::Agent, Sidecar::Bun];
+
+/// Resolved location and state of a sidecar binary
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarInfo {
+    pub path: PathBuf,
+    pub exists: bool,
+    pub size_bytes: u64,
+    /// Unix timestamp (seconds) of the last modification, 0 if unknown
+    pub modified_at: u64,
+}
+
+/// Look up a user-configured override for a built-in sidecar in settings.json
+fn get_custom_sidecar_path(app: &AppHandle, sidecar: &Sidecar) -> Option<PathBuf> {
+    let key = sidecar.type_key()?;
+    let store = app.store("settings.json").ok()?;
+    let overrides = store.get(CUSTOM_SIDECARS_KEY)?;
+    let path = overrides.get(key)?.as_str()?;
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
 /// Get the path to a sidecar binary
-fn get_sidecar_path(sidecar: Sidecar) -> PathBuf {
+fn get_sidecar_path(app: &AppHandle, sidecar: &Sidecar) -> PathBuf {
+    if let Sidecar::Custom(path) = sidecar {
+        return path.clone();
+    }
+
+    if let Some(path) = get_custom_sidecar_path(app, sidecar) {
+        return path;
+    }
+
     #[cfg(debug_assertions)]
     {
         // Dev mode - binaries are in src-tauri/binaries/
@@ -108,9 +174,86 @@ pub fn get_sidecar_dir() -> PathBuf {
     }
 }
 
+/// Resolve a sidecar's binary and report whether it exists.
+/// A missing binary is reported via `exists`; only other I/O failures are errors.
+pub fn check(app: &AppHandle, sidecar: Sidecar) -> Result<SidecarInfo, String> {
+    let path = get_sidecar_path(app, &sidecar);
+
+    let metadata = match std::fs::metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(SidecarInfo { path, exists: false, size_bytes: 0, modified_at: 0 });
+        }
+        Err(e) => return Err(format!("Failed to inspect {}: {}", path.display(), e)),
+    };
+
+    let modified_at = metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(SidecarInfo {
+        exists: metadata.is_file(),
+        size_bytes: metadata.len(),
+        modified_at,
+        path,
+    })
+}
+
+/// Check every built-in sidecar, keyed by sidecar type (as in `custom_sidecars`)
+#[tauri::command]
+pub fn check_sidecars(app: AppHandle) -> Result<HashMap<String, SidecarInfo>, String> {
+    REQUIRED_SIDECARS
+        .iter()
+        .map(|sidecar| {
+            let key = sidecar.type_key().unwrap_or(sidecar.name()).to_string();
+            check(&app, sidecar.clone()).map(|info| (key, info))
+        })
+        .collect()
+}
+
+/// Types of the sidecar binaries found missing at startup.
+/// Managed so windows that open after `sidecar:missing` was emitted can still read it.
+pub struct MissingSidecars(pub Vec<String>);
+
+/// Log and emit `sidecar:missing` with the types of any missing sidecar binaries.
+/// Returns the missing types for `get_missing_sidecars`.
+pub fn report_missing(app: &AppHandle) -> Vec<String> {
+    let sidecars = match check_sidecars(app.clone()) {
+        Ok(sidecars) => sidecars,
+        Err(e) => {
+            eprintln!("[sidecar] Health check failed: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut missing: Vec<String> = sidecars
+        .iter()
+        .filter(|(_, info)| !info.exists)
+        .map(|(key, info)| {
+            eprintln!("[sidecar] ERROR: {} binary not found at {:?}", key, info.path);
+            key.clone()
+        })
+        .collect();
+
+    if !missing.is_empty() {
+        missing.sort();
+        eprintln!("[sidecar] Missing binaries: {:?} - run `bun run build:sidecars`", missing);
+        let _ = app.emit("sidecar:missing", &missing);
+    }
+    missing
+}
+
+/// Sidecar binaries found missing at startup (the `sidecar:missing` payload)
+#[tauri::command]
+pub fn get_missing_sidecars(missing: tauri::State<'_, MissingSidecars>) -> Vec<String> {
+    missing.0.clone()
+}
+
 /// Create a command for running a sidecar with PATH set to include sidecar directory
-pub fn command(sidecar: Sidecar) -> Command {
-    let binary_path = get_sidecar_path(sidecar);
+pub fn command(app: &AppHandle, sidecar: Sidecar) -> Command {
+    let binary_path = get_sidecar_path(app, &sidecar);
     println!("[sidecar] Running {:?} from: {:?}", sidecar, binary_path);
 
     // Check if binary exists and is executable
@@ -130,8 +273,8 @@ pub fn command(sidecar: Sidecar) -> Command {
 }
 
 /// Create a synchronous command for running a sidecar with PATH set to include sidecar directory
-pub fn command_sync(sidecar: Sidecar) -> std::process::Command {
-    let binary_path = get_sidecar_path(sidecar);
+pub fn command_sync(app: &AppHandle, sidecar: Sidecar) -> std::process::Command {
+    let binary_path = get_sidecar_path(app, &sidecar);
     println!("[sidecar] Running {:?} from: {:?}", sidecar, binary_path);
 
     if !binary_path.exists() {
@@ -148,3 +291,59 @@ pub fn command_sync(sidecar: Sidecar) -> std::process::Command {
 
     cmd
 }
+
+/// Check that a path points to an executable file
+fn validate_executable(path: &std::path::Path) -> Result<(), String> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("Cannot access {}: {}", path.display(), e))?;
+
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("{} is not executable", path.display()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Substitute a user-provided binary for a built-in sidecar (e.g. a local hands-agent fork).
+/// Pass an empty path to restore the bundled binary.
+#[tauri::command]
+pub async fn set_custom_sidecar(
+    app: AppHandle,
+    sidecar_type: String,
+    path: String,
+) -> Result<(), String> {
+    if Sidecar::from_type(&sidecar_type).is_none() {
+        return Err(format!("Unknown sidecar type: {}", sidecar_type));
+    }
+
+    if !path.is_empty() {
+        validate_executable(std::path::Path::new(&path))?;
+    }
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+
+    let mut overrides = store.get(CUSTOM_SIDECARS_KEY)
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    overrides[&sidecar_type] = serde_json::json!(path);
+
+    store.set(CUSTOM_SIDECARS_KEY, overrides);
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    println!("[sidecar] Custom {} sidecar set to {:?}", sidecar_type, path);
+    let _ = app.emit("sidecar:custom-set", serde_json::json!({
+        "sidecar_type": sidecar_type,
+        "path": path,
+    }));
+
+    Ok(())
+}
@@ -1,38 +1,167 @@
 //! Speech-to-text using Parakeet TDT model.
 //!
 //! Hold Option key to record, release to transcribe.
-//! Uses batch transcription for accuracy (no streaming preview).
+//! While recording, the buffered audio is re-transcribed periodically and
+//! emitted as `stt:partial` previews; the final transcript is a batch pass
+//! over the complete recording.
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use futures_util::StreamExt;
 use parakeet_rs::{ParakeetTDT, Transcriber};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::{Arc, Mutex};
-use std::sync::OnceLock;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 
-/// Global STT state
-static STT_STATE: OnceLock<Arc<Mutex<SttState>>> = OnceLock::new();
+/// How long to wait for a previous capture thread to wind down
+const CAPTURE_JOIN_TIMEOUT: Duration = Duration::from_millis(200);
 
-struct SttState {
-    model: Option<ParakeetTDT>,
+/// Minimum interval between download progress events
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often a partial transcript is produced while recording
+const PARTIAL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Partial transcripts cover only the most recent audio (10 s at 16kHz), so their
+/// cost doesn't grow with the length of the recording
+const PARTIAL_WINDOW_SAMPLES: usize = 16_000 * 10;
+
+/// HuggingFace API for the model repo (revision and file listing)
+/// https://huggingface.co/altunenes/parakeet-rs/tree/main/tdt
+const MODEL_REPO_API: &str = "https://huggingface.co/api/models/altunenes/parakeet-rs";
+/// TDT model from parakeet-rs author (compatible with the library)
+const MODEL_BASE_URL: &str = "https://huggingface.co/altunenes/parakeet-rs/resolve/main/tdt";
+const MODEL_REPO_SUBDIR: &str = "tdt";
+
+/// TDT int8 quantized model files (~670 MB total)
+/// parakeet-rs looks for: encoder-model.onnx/encoder.onnx, decoder_joint-model.onnx/decoder_joint.onnx
+const MODEL_FILES: [&str; 3] = ["encoder-model.int8.onnx", "decoder_joint-model.int8.onnx", "vocab.txt"];
+
+/// Metadata for the downloaded model, kept next to the model files
+const MODEL_MANIFEST_FILE: &str = "models.json";
+
+/// A model file with its checksum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelFileInfo {
+    pub name: String,
+    /// Hex SHA-256 (empty in a remote manifest when HuggingFace doesn't publish one)
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Contents of `models.json`. `version` is the HuggingFace repo revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub version: String,
+    pub downloaded_at: u64,
+    pub files: Vec<ModelFileInfo>,
+}
+
+impl ModelManifest {
+    fn file(&self, name: &str) -> Option<&ModelFileInfo> {
+        self.files.iter().find(|f| f.name == name)
+    }
+
+    /// Files in `remote` that are missing or differ from this manifest
+    fn changed_files(&self, remote: &ModelManifest) -> Vec<String> {
+        remote.files
+            .iter()
+            .filter(|r| match self.file(&r.name) {
+                Some(local) => local.size != r.size || (!r.sha256.is_empty() && local.sha256 != r.sha256),
+                None => true,
+            })
+            .map(|r| r.name.clone())
+            .collect()
+    }
+}
+
+/// Result of `stt_check_for_model_update`
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUpdateInfo {
+    /// Installed revision (None if the model predates version tracking or isn't downloaded)
+    pub current_version: Option<String>,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub changed_files: Vec<String>,
+}
+
+/// Payload of the `stt:download-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub file: String,
+    pub file_index: usize,
+    pub file_count: usize,
+    pub file_bytes_done: u64,
+    pub file_bytes_total: u64,
+    pub total_bytes_done: u64,
+    pub total_bytes_total: u64,
+    pub elapsed_secs: f64,
+    pub eta_secs: Option<f64>,
+}
+
+impl DownloadProgress {
+    fn eta(total_bytes_done: u64, total_bytes_total: u64, elapsed_secs: f64) -> Option<f64> {
+        if total_bytes_done == 0 || elapsed_secs <= 0.0 {
+            return None;
+        }
+        let rate = total_bytes_done as f64 / elapsed_secs;
+        Some(total_bytes_total.saturating_sub(total_bytes_done) as f64 / rate)
+    }
+}
+
+/// STT state registered with Tauri via `app.manage`
+pub struct SttManagedState(pub Arc<Mutex<SttState>>);
+
+/// Loaded model, locked separately from `SttState` so transcription doesn't
+/// block the audio callback
+type SharedModel = Arc<Mutex<Option<ParakeetTDT>>>;
+
+pub struct SttState {
+    model: SharedModel,
     model_path: String,
     is_recording: bool,
     /// Audio samples buffer (16kHz mono)
     audio_buffer: Vec<f32>,
+    /// Handle to the audio capture thread (owns the cpal stream)
+    capture_thread: Option<JoinHandle<()>>,
+    /// Stops the partial transcription task for the current recording
+    partial_cancel: Option<tokio::sync::watch::Sender<bool>>,
+    /// Selected input device name (None = system default)
+    input_device: Option<String>,
 }
 
 impl SttState {
-    fn new(model_path: String) -> Self {
+    pub fn new(model_path: String) -> Self {
         Self {
-            model: None,
+            model: Arc::new(Mutex::new(None)),
             model_path,
             is_recording: false,
             audio_buffer: Vec::new(),
+            capture_thread: None,
+            partial_cancel: None,
+            input_device: None,
+        }
+    }
+
+    /// Append captured 16kHz mono samples (ignored unless recording)
+    fn push_audio(&mut self, samples: &[f32]) {
+        if self.is_recording {
+            self.audio_buffer.extend_from_slice(samples);
+        }
+    }
+
+    /// Signal the partial transcription task (if any) to stop
+    fn cancel_partials(&mut self) {
+        if let Some(cancel) = self.partial_cancel.take() {
+            let _ = cancel.send(true);
         }
     }
 
-    fn ensure_model(&mut self) -> Result<(), String> {
-        if self.model.is_none() {
+    fn ensure_model(&mut self, app: &AppHandle) -> Result<(), String> {
+        let mut model_slot = self.model.lock().unwrap();
+        if model_slot.is_none() {
             println!("[stt] Loading Parakeet TDT model from: {}", self.model_path);
 
             // Check files exist (parakeet-rs looks for encoder-model*.onnx, decoder_joint*.onnx)
@@ -50,9 +179,9 @@ impl SttState {
 
             match ParakeetTDT::from_pretrained(&self.model_path, None) {
                 Ok(model) => {
-                    self.model = Some(model);
+                    *model_slot = Some(model);
                     println!("[stt] Model loaded successfully");
-                    crate::sfx::play("confirm");
+                    crate::sfx::play(app, "confirm");
                 }
                 Err(e) => {
                     let err_msg = format!("Failed to load model: {}", e);
@@ -65,29 +194,122 @@ impl SttState {
     }
 }
 
-fn get_state(app: &AppHandle) -> Arc<Mutex<SttState>> {
-    STT_STATE
-        .get_or_init(|| {
-            // Model path in app data directory
-            let model_path = app
-                .path()
-                .app_data_dir()
-                .unwrap_or_else(|_| std::path::PathBuf::from("."))
-                .join("models")
-                .join("parakeet-tdt");
+/// Run the model over `audio` (16kHz mono). Blocks while the model is busy,
+/// so call from a blocking task.
+fn transcribe(model: &SharedModel, audio: Vec<f32>) -> Result<String, String> {
+    let mut guard = model.lock().unwrap();
+    let model = guard.as_mut().ok_or("Model not loaded")?;
+    // transcribe_samples(audio, sample_rate, channels, timestamp_mode)
+    let result = model
+        .transcribe_samples(audio, 16000, 1, None)
+        .map_err(|e| format!("Transcription failed: {}", e))?;
+    // Clean up SentencePiece markers (▁ -> space)
+    Ok(result.text.replace('▁', " ").trim().to_string())
+}
 
-            Arc::new(Mutex::new(SttState::new(
-                model_path.to_string_lossy().to_string(),
-            )))
-        })
-        .clone()
+/// Wait for the in-flight partial transcription, or forever if there is none
+async fn wait_for_partial(
+    task: &mut Option<tokio::task::JoinHandle<Result<String, String>>>,
+) -> Result<Result<String, String>, tokio::task::JoinError> {
+    match task {
+        Some(task) => task.await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Periodically transcribe the most recent audio (`PARTIAL_WINDOW_SAMPLES`) and
+/// emit `stt:partial` until cancelled or recording stops. A tick is skipped while
+/// the previous partial is still running.
+fn spawn_partial_task(
+    app: AppHandle,
+    state: Arc<Mutex<SttState>>,
+    model: SharedModel,
+    mut cancel_rx: tokio::sync::watch::Receiver<bool>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(PARTIAL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // The first tick fires immediately - there's no audio yet
+        interval.tick().await;
+
+        let mut last_len = 0;
+        let mut in_flight = None;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = cancel_rx.changed() => break,
+                result = wait_for_partial(&mut in_flight) => {
+                    in_flight = None;
+                    // Don't emit a stale partial after the final transcript was requested
+                    if *cancel_rx.borrow() {
+                        break;
+                    }
+                    match result {
+                        Ok(Ok(text)) if !text.is_empty() => {
+                            let _ = app.emit("stt:partial", text);
+                        }
+                        Ok(Ok(_)) => {}
+                        Ok(Err(e)) => eprintln!("[stt] Partial transcription error: {}", e),
+                        Err(e) => eprintln!("[stt] Partial transcription task failed: {}", e),
+                    }
+                    continue;
+                }
+            }
+
+            if in_flight.is_some() {
+                continue;
+            }
+
+            // Snapshot the tail of the buffer so the model runs without the state lock
+            let audio = {
+                let guard = state.lock().unwrap();
+                if !guard.is_recording {
+                    break;
+                }
+                let len = guard.audio_buffer.len();
+                if len == last_len {
+                    continue;
+                }
+                last_len = len;
+                guard.audio_buffer[len.saturating_sub(PARTIAL_WINDOW_SAMPLES)..].to_vec()
+            };
+
+            let model = model.clone();
+            in_flight = Some(tokio::task::spawn_blocking(move || transcribe(&model, audio)));
+        }
+    });
+}
+
+/// Join a capture thread, giving up after `CAPTURE_JOIN_TIMEOUT`.
+/// Must be called without holding the state lock, since the thread locks it to exit.
+fn join_capture_thread(handle: JoinHandle<()>) {
+    let deadline = Instant::now() + CAPTURE_JOIN_TIMEOUT;
+    while !handle.is_finished() {
+        let now = Instant::now();
+        if now >= deadline {
+            eprintln!("[stt] Capture thread did not stop within {:?}, detaching", CAPTURE_JOIN_TIMEOUT);
+            return;
+        }
+        std::thread::park_timeout((deadline - now).min(Duration::from_millis(10)));
+    }
+    let _ = handle.join();
+}
+
+/// Default model location in the app data directory
+pub fn default_model_path(app: &AppHandle) -> String {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::path::PathBuf::from("."))
+        .join("models")
+        .join("parakeet-tdt")
+        .to_string_lossy()
+        .to_string()
 }
 
 /// Check if the STT model is available
 #[tauri::command]
-pub async fn stt_model_available(app: AppHandle) -> bool {
-    let state = get_state(&app);
-    let guard = state.lock().unwrap();
+pub fn stt_model_available(state: tauri::State<'_, SttManagedState>) -> bool {
+    let guard = state.0.lock().unwrap();
 
     let model_path = std::path::Path::new(&guard.model_path);
     // parakeet-rs TDT looks for encoder-model*.onnx and decoder_joint*.onnx
@@ -100,124 +322,391 @@ pub async fn stt_model_available(app: AppHandle) -> bool {
 
 /// Get the model directory path
 #[tauri::command]
-pub async fn stt_model_path(app: AppHandle) -> String {
-    let state = get_state(&app);
-    let guard = state.lock().unwrap();
+pub fn stt_model_path(state: tauri::State<'_, SttManagedState>) -> String {
+    let guard = state.0.lock().unwrap();
     guard.model_path.clone()
 }
 
-/// Download the STT model from HuggingFace
-#[tauri::command]
-pub async fn stt_download_model(app: AppHandle) -> Result<(), String> {
-    let state = get_state(&app);
-    let model_path = {
-        let guard = state.lock().unwrap();
-        guard.model_path.clone()
-    };
+fn read_manifest(model_dir: &std::path::Path) -> Option<ModelManifest> {
+    let content = std::fs::read_to_string(model_dir.join(MODEL_MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
 
-    let model_dir = std::path::Path::new(&model_path);
-    std::fs::create_dir_all(model_dir)
-        .map_err(|e| format!("Failed to create model directory: {}", e))?;
+fn write_manifest(model_dir: &std::path::Path, manifest: &ModelManifest) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize {}: {}", MODEL_MANIFEST_FILE, e))?;
+    std::fs::write(model_dir.join(MODEL_MANIFEST_FILE), content)
+        .map_err(|e| format!("Failed to write {}: {}", MODEL_MANIFEST_FILE, e))
+}
 
-    // TDT model from parakeet-rs author (compatible with the library)
-    // https://huggingface.co/altunenes/parakeet-rs/tree/main/tdt
-    let base_url = "https://huggingface.co/altunenes/parakeet-rs/resolve/main/tdt";
+/// Hex SHA-256 of a file (blocking - model files are hundreds of MB)
+fn sha256_file(path: &std::path::Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
 
-    // TDT int8 quantized model files (~670 MB total)
-    // parakeet-rs looks for: encoder-model.onnx/encoder.onnx, decoder_joint-model.onnx/decoder_joint.onnx
-    let files = [
-        ("encoder-model.int8.onnx", "encoder-model.int8.onnx"),
-        ("decoder_joint-model.int8.onnx", "decoder_joint-model.int8.onnx"),
-        ("vocab.txt", "vocab.txt"),
-    ];
+/// Build the latest manifest from the HuggingFace API. LFS files (the ONNX
+/// models) come with a SHA-256; plain git files like vocab.txt don't.
+async fn fetch_remote_manifest(client: &reqwest::Client) -> Result<ModelManifest, String> {
+    #[derive(Deserialize)]
+    struct RepoInfo {
+        sha: String,
+    }
+    #[derive(Deserialize)]
+    struct LfsInfo {
+        oid: String,
+        size: u64,
+    }
+    #[derive(Deserialize)]
+    struct TreeEntry {
+        path: String,
+        #[serde(default)]
+        size: u64,
+        lfs: Option<LfsInfo>,
+    }
 
-    let client = reqwest::Client::new();
+    let repo: RepoInfo = client.get(MODEL_REPO_API)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch model info: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse model info: {}", e))?;
+
+    let tree: Vec<TreeEntry> = client.get(format!("{}/tree/main/{}", MODEL_REPO_API, MODEL_REPO_SUBDIR))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch model file list: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse model file list: {}", e))?;
+
+    let files = MODEL_FILES
+        .iter()
+        .map(|name| {
+            let path = format!("{}/{}", MODEL_REPO_SUBDIR, name);
+            let entry = tree.iter()
+                .find(|e| e.path == path)
+                .ok_or_else(|| format!("{} is missing from the model repository", name))?;
+            Ok(match &entry.lfs {
+                Some(lfs) => ModelFileInfo { name: name.to_string(), sha256: lfs.oid.clone(), size: lfs.size },
+                None => ModelFileInfo { name: name.to_string(), sha256: String::new(), size: entry.size },
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(ModelManifest { version: repo.sha, downloaded_at: 0, files })
+}
 
+/// Download `names` into `model_dir`, replacing existing files, and verify them
+/// against `remote` where it has checksums.
+///
+/// Emits `stt:download-progress` with a [`DownloadProgress`] payload (throttled to
+/// one event per 250ms per file), plus a final event once every file is present.
+async fn download_model_files(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    model_dir: &std::path::Path,
+    names: &[String],
+    remote: Option<&ModelManifest>,
+) -> Result<(), String> {
     // Calculate total size for progress
-    let mut total_size: u64 = 0;
     let mut file_sizes: Vec<u64> = Vec::new();
-
-    for (remote_name, local_name) in &files {
-        let local_path = model_dir.join(local_name);
-        if local_path.exists() {
-            file_sizes.push(0); // Already downloaded
-            continue;
-        }
-
-        let url = format!("{}/{}", base_url, remote_name);
-        let head_response = client.head(&url).send().await.ok();
-        let size = head_response
-            .and_then(|r| r.headers().get("content-length")?.to_str().ok()?.parse().ok())
-            .unwrap_or(0);
+    for name in names {
+        let size = match remote.and_then(|m| m.file(name)) {
+            Some(info) => info.size,
+            None => {
+                let url = format!("{}/{}", MODEL_BASE_URL, name);
+                let head_response = client.head(&url).send().await.ok();
+                head_response
+                    .and_then(|r| r.headers().get("content-length")?.to_str().ok()?.parse().ok())
+                    .unwrap_or(0)
+            }
+        };
         file_sizes.push(size);
-        total_size += size;
     }
+    let total_size: u64 = file_sizes.iter().sum();
 
     let mut downloaded: u64 = 0;
+    let started = Instant::now();
 
-    for (remote_name, local_name) in files.iter() {
-        let local_path = model_dir.join(local_name);
-        if local_path.exists() {
-            println!("[stt] {} already exists, skipping", local_name);
-            continue;
-        }
-
-        let url = format!("{}/{}", base_url, remote_name);
-        println!("[stt] Downloading {} -> {}", remote_name, local_name);
+    for (file_index, name) in names.iter().enumerate() {
+        let local_path = model_dir.join(name);
+        let url = format!("{}/{}", MODEL_BASE_URL, name);
+        println!("[stt] Downloading {}", name);
 
         let response = client
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Failed to download {}: {}", remote_name, e))?;
+            .map_err(|e| format!("Failed to download {}: {}", name, e))?;
 
         if !response.status().is_success() {
-            return Err(format!("Failed to download {}: HTTP {}", remote_name, response.status()));
+            return Err(format!("Failed to download {}: HTTP {}", name, response.status()));
         }
 
-        // Stream download with progress
-        let mut file = std::fs::File::create(&local_path)
-            .map_err(|e| format!("Failed to create {}: {}", local_name, e))?;
+        // Stream to a temp file so an interrupted download doesn't look complete
+        let partial_path = model_dir.join(format!("{}.part", name));
+        let mut file = std::fs::File::create(&partial_path)
+            .map_err(|e| format!("Failed to create {}: {}", name, e))?;
 
         let mut stream = response.bytes_stream();
         let mut file_downloaded: u64 = 0;
+        let mut last_emit: Option<Instant> = None;
+        let mut hasher = Sha256::new();
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
             std::io::Write::write_all(&mut file, &chunk)
                 .map_err(|e| format!("Write error: {}", e))?;
+            hasher.update(&chunk);
 
             file_downloaded += chunk.len() as u64;
             downloaded += chunk.len() as u64;
 
-            // Emit progress (0.0 to 1.0)
-            if total_size > 0 {
-                let progress = downloaded as f64 / total_size as f64;
-                let _ = app.emit("stt:download-progress", progress);
+            if !last_emit.is_some_and(|t| t.elapsed() < PROGRESS_EMIT_INTERVAL) {
+                last_emit = Some(Instant::now());
+                let elapsed_secs = started.elapsed().as_secs_f64();
+                let _ = app.emit("stt:download-progress", DownloadProgress {
+                    file: name.clone(),
+                    file_index,
+                    file_count: names.len(),
+                    file_bytes_done: file_downloaded,
+                    file_bytes_total: file_sizes[file_index],
+                    total_bytes_done: downloaded,
+                    total_bytes_total: total_size,
+                    elapsed_secs,
+                    eta_secs: DownloadProgress::eta(downloaded, total_size, elapsed_secs),
+                });
+            }
+        }
+        drop(file);
+
+        let sha256 = format!("{:x}", hasher.finalize());
+        if let Some(expected) = remote.and_then(|m| m.file(name)).filter(|f| !f.sha256.is_empty()) {
+            if expected.sha256 != sha256 {
+                let _ = std::fs::remove_file(&partial_path);
+                return Err(format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    name, expected.sha256, sha256
+                ));
             }
         }
 
-        println!("[stt] Downloaded {} ({} bytes)", local_name, file_downloaded);
+        std::fs::rename(&partial_path, &local_path)
+            .map_err(|e| format!("Failed to move {} into place: {}", name, e))?;
+        println!("[stt] Downloaded {} ({} bytes)", name, file_downloaded);
     }
 
     // Emit complete
-    let _ = app.emit("stt:download-progress", 1.0_f64);
+    if let Some(last) = names.last() {
+        let _ = app.emit("stt:download-progress", DownloadProgress {
+            file: last.clone(),
+            file_index: names.len() - 1,
+            file_count: names.len(),
+            file_bytes_done: 0,
+            file_bytes_total: 0,
+            total_bytes_done: total_size,
+            total_bytes_total: total_size,
+            elapsed_secs: started.elapsed().as_secs_f64(),
+            eta_secs: Some(0.0),
+        });
+    }
 
-    // Generate tokenizer.json from vocab.txt (parakeet-rs needs HuggingFace tokenizer format)
-    let tokenizer_path = model_dir.join("tokenizer.json");
-    if !tokenizer_path.exists() {
-        let vocab_path = model_dir.join("vocab.txt");
-        if vocab_path.exists() {
-            println!("[stt] Generating tokenizer.json from vocab.txt...");
-            generate_tokenizer_json(&vocab_path, &tokenizer_path)?;
-            println!("[stt] Generated tokenizer.json");
+    Ok(())
+}
+
+/// Record the installed files in `models.json`. Checksums from `previous` are
+/// reused for files of the same size that weren't just `downloaded`.
+async fn update_manifest(
+    model_dir: &std::path::Path,
+    version: String,
+    previous: Option<ModelManifest>,
+    downloaded: &[String],
+) -> Result<ModelManifest, String> {
+    let downloaded = downloaded.to_vec();
+    let model_dir = model_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut files = Vec::new();
+        for name in MODEL_FILES {
+            let path = model_dir.join(name);
+            let size = std::fs::metadata(&path)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?
+                .len();
+            let reusable = previous.as_ref()
+                .and_then(|m| m.file(name))
+                .filter(|_| !downloaded.iter().any(|d| d == name));
+            let sha256 = match reusable {
+                Some(info) if info.size == size && !info.sha256.is_empty() => info.sha256.clone(),
+                _ => sha256_file(&path)?,
+            };
+            files.push(ModelFileInfo { name: name.to_string(), sha256, size });
         }
+
+        let downloaded_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let manifest = ModelManifest { version, downloaded_at, files };
+        write_manifest(&model_dir, &manifest)?;
+        Ok(manifest)
+    })
+    .await
+    .map_err(|e| format!("Failed to update {}: {}", MODEL_MANIFEST_FILE, e))?
+}
+
+/// Generate tokenizer.json from vocab.txt (parakeet-rs needs HuggingFace tokenizer format)
+fn ensure_tokenizer(model_dir: &std::path::Path, regenerate: bool) -> Result<(), String> {
+    let tokenizer_path = model_dir.join("tokenizer.json");
+    if tokenizer_path.exists() && !regenerate {
+        return Ok(());
+    }
+    let vocab_path = model_dir.join("vocab.txt");
+    if vocab_path.exists() {
+        println!("[stt] Generating tokenizer.json from vocab.txt...");
+        generate_tokenizer_json(&vocab_path, &tokenizer_path)?;
+        println!("[stt] Generated tokenizer.json");
     }
+    Ok(())
+}
+
+/// Download the STT model from HuggingFace.
+///
+/// Files that already exist are kept. Downloads are verified against the
+/// published SHA-256 checksums and recorded in `models.json`.
+#[tauri::command]
+pub async fn stt_download_model(
+    app: AppHandle,
+    state: tauri::State<'_, SttManagedState>,
+) -> Result<(), String> {
+    let state = state.0.clone();
+    let model_path = {
+        let guard = state.lock().unwrap();
+        guard.model_path.clone()
+    };
+
+    let model_dir = std::path::Path::new(&model_path);
+    std::fs::create_dir_all(model_dir)
+        .map_err(|e| format!("Failed to create model directory: {}", e))?;
+
+    let client = reqwest::Client::new();
+
+    // Checksums are best-effort - the download itself works without the API
+    let remote = match fetch_remote_manifest(&client).await {
+        Ok(remote) => Some(remote),
+        Err(e) => {
+            eprintln!("[stt] {}, downloading without checksum verification", e);
+            None
+        }
+    };
+
+    let missing: Vec<String> = MODEL_FILES
+        .iter()
+        .filter(|name| {
+            let exists = model_dir.join(name).exists();
+            if exists {
+                println!("[stt] {} already exists, skipping", name);
+            }
+            !exists
+        })
+        .map(|name| name.to_string())
+        .collect();
+
+    download_model_files(&app, &client, model_dir, &missing, remote.as_ref()).await?;
+    ensure_tokenizer(model_dir, false)?;
+
+    let previous = read_manifest(model_dir);
+    let version = remote.map(|m| m.version)
+        .or_else(|| previous.as_ref().map(|m| m.version.clone()))
+        .unwrap_or_else(|| "unknown".to_string());
+    update_manifest(model_dir, version, previous, &missing).await?;
 
     println!("[stt] Model download complete!");
     Ok(())
 }
 
+/// Compare the installed model against the latest files on HuggingFace
+#[tauri::command]
+pub async fn stt_check_for_model_update(app: AppHandle) -> Result<ModelUpdateInfo, String> {
+    let model_path = app.state::<SttManagedState>().0.lock().unwrap().model_path.clone();
+    let local = read_manifest(std::path::Path::new(&model_path));
+    let remote = fetch_remote_manifest(&reqwest::Client::new()).await?;
+
+    let changed_files = match &local {
+        Some(local) => local.changed_files(&remote),
+        None => remote.files.iter().map(|f| f.name.clone()).collect(),
+    };
+
+    Ok(ModelUpdateInfo {
+        current_version: local.map(|m| m.version),
+        latest_version: remote.version,
+        update_available: !changed_files.is_empty(),
+        changed_files,
+    })
+}
+
+/// Re-download model files that changed upstream, then reload the model on next use
+#[tauri::command]
+pub async fn stt_update_model(
+    app: AppHandle,
+    state: tauri::State<'_, SttManagedState>,
+) -> Result<ModelUpdateInfo, String> {
+    let state = state.0.clone();
+    let (model_path, model) = {
+        let guard = state.lock().unwrap();
+        if guard.is_recording {
+            return Err("Cannot update the model while recording".to_string());
+        }
+        (guard.model_path.clone(), guard.model.clone())
+    };
+    let model_dir = std::path::Path::new(&model_path);
+    std::fs::create_dir_all(model_dir)
+        .map_err(|e| format!("Failed to create model directory: {}", e))?;
+
+    let client = reqwest::Client::new();
+    let remote = fetch_remote_manifest(&client).await?;
+    let local = read_manifest(model_dir);
+
+    // Without a manifest, hash what's on disk to find what actually changed
+    let local = match local {
+        Some(local) => Some(local),
+        None if MODEL_FILES.iter().all(|name| model_dir.join(name).exists()) => {
+            Some(update_manifest(model_dir, "unknown".to_string(), None, &[]).await?)
+        }
+        None => None,
+    };
+    let changed_files = match &local {
+        Some(local) => local.changed_files(&remote),
+        None => MODEL_FILES.iter().map(|name| name.to_string()).collect(),
+    };
+
+    let info = ModelUpdateInfo {
+        current_version: local.as_ref().map(|m| m.version.clone()),
+        latest_version: remote.version.clone(),
+        update_available: !changed_files.is_empty(),
+        changed_files: changed_files.clone(),
+    };
+
+    if !changed_files.is_empty() {
+        println!("[stt] Updating model files: {:?}", changed_files);
+        download_model_files(&app, &client, model_dir, &changed_files, Some(&remote)).await?;
+        ensure_tokenizer(model_dir, changed_files.iter().any(|f| f == "vocab.txt"))?;
+
+        // Drop the loaded model so the new files are picked up
+        *model.lock().unwrap() = None;
+    }
+
+    update_manifest(model_dir, remote.version, local, &changed_files).await?;
+    println!("[stt] Model is up to date");
+    Ok(info)
+}
+
 /// Generate HuggingFace tokenizer.json from vocab.txt
 fn generate_tokenizer_json(vocab_path: &std::path::Path, output_path: &std::path::Path) -> Result<(), String> {
     let vocab_content = std::fs::read_to_string(vocab_path)
@@ -285,80 +774,118 @@ fn generate_tokenizer_json(vocab_path: &std::path::Path, output_path: &std::path
 
 /// Start recording audio for STT
 #[tauri::command]
-pub async fn stt_start_recording(app: AppHandle) -> Result<(), String> {
-    let state = get_state(&app);
+pub async fn stt_start_recording(
+    app: AppHandle,
+    state: tauri::State<'_, SttManagedState>,
+) -> Result<(), String> {
+    let state = state.0.clone();
+
+    // Wait for a previous capture thread that is still winding down
+    let previous_thread = {
+        let mut guard = state.lock().unwrap();
+
+        // Prevent double-start
+        if guard.is_recording {
+            println!("[stt] Already recording, ignoring start request");
+            return Ok(());
+        }
+
+        guard.capture_thread.take()
+    };
+    if let Some(handle) = previous_thread {
+        join_capture_thread(handle);
+    }
 
     // Ensure model is loaded
-    {
+    let (model, cancel_rx) = {
         let mut guard = state.lock().unwrap();
 
-        // Prevent double-start
+        // Re-check in case another start raced us while joining
         if guard.is_recording {
             println!("[stt] Already recording, ignoring start request");
             return Ok(());
         }
 
         println!("[stt] Loading model...");
-        guard.ensure_model()?;
+        guard.ensure_model(&app)?;
         println!("[stt] Model ready, starting recording");
         guard.is_recording = true;
         guard.audio_buffer.clear();
-    }
+
+        let (cancel_tx, cancel_rx) = tokio::sync::watch::channel(false);
+        guard.cancel_partials();
+        guard.partial_cancel = Some(cancel_tx);
+        (guard.model.clone(), cancel_rx)
+    };
 
     // Start audio capture in background
     let state_clone = state.clone();
-    std::thread::spawn(move || {
+    let handle = std::thread::spawn(move || {
         println!("[stt] Audio capture thread started");
         if let Err(e) = capture_audio(state_clone) {
             eprintln!("[stt] Audio capture error: {}", e);
         }
         println!("[stt] Audio capture thread ended");
     });
+    state.lock().unwrap().capture_thread = Some(handle);
+
+    spawn_partial_task(app.clone(), state.clone(), model, cancel_rx);
 
     println!("[stt] Recording started");
+    crate::telemetry::track(&app, "stt_used").await;
     Ok(())
 }
 
 /// Stop recording and return final transcription
 #[tauri::command]
-pub async fn stt_stop_recording(app: AppHandle) -> Result<String, String> {
-    let state = get_state(&app);
-    let mut guard = state.lock().unwrap();
+pub async fn stt_stop_recording(
+    state: tauri::State<'_, SttManagedState>,
+) -> Result<String, String> {
+    let state = state.0.clone();
+
+    // Stop capture and wait for the thread so the cpal stream is dropped
+    // before we drain the buffer
+    let capture_thread = {
+        let mut guard = state.lock().unwrap();
 
-    // Prevent double-stop
-    if !guard.is_recording {
-        println!("[stt] Not recording, ignoring stop request");
-        return Ok(String::new());
+        // Prevent double-stop
+        if !guard.is_recording {
+            println!("[stt] Not recording, ignoring stop request");
+            return Ok(String::new());
+        }
+
+        guard.is_recording = false;
+        guard.cancel_partials();
+        guard.capture_thread.take()
+    };
+    if let Some(handle) = capture_thread {
+        join_capture_thread(handle);
     }
 
-    guard.is_recording = false;
-    let total_samples = guard.audio_buffer.len();
-    let duration_ms = (total_samples as f32 / 16.0) as usize; // 16kHz
-    println!("[stt] Recording stopped: {} samples ({}ms)", total_samples, duration_ms);
+    let (model, audio) = {
+        let mut guard = state.lock().unwrap();
+        let total_samples = guard.audio_buffer.len();
+        let duration_ms = (total_samples as f32 / 16.0) as usize; // 16kHz
+        println!("[stt] Recording stopped: {} samples ({}ms)", total_samples, duration_ms);
+
+        let audio: Vec<f32> = guard.audio_buffer.drain(..).collect();
+        (guard.model.clone(), audio)
+    };
 
-    // Batch transcribe all audio
-    let audio: Vec<f32> = guard.audio_buffer.drain(..).collect();
     if audio.is_empty() {
         println!("[stt] No audio captured");
         return Ok(String::new());
     }
 
-    let final_text = if let Some(ref mut model) = guard.model {
-        println!("[stt] Transcribing {} samples...", audio.len());
-        // transcribe_samples(audio, sample_rate, channels, timestamp_mode)
-        match model.transcribe_samples(audio, 16000, 1, None) {
-            Ok(result) => {
-                // Clean up SentencePiece markers (▁ -> space)
-                result.text.replace('▁', " ").trim().to_string()
-            }
-            Err(e) => {
-                eprintln!("[stt] Transcription error: {}", e);
-                return Err(format!("Transcription failed: {}", e));
-            }
-        }
-    } else {
-        return Err("Model not loaded".to_string());
-    };
+    // Batch transcribe all audio (waits for any in-flight partial to release the model)
+    println!("[stt] Transcribing {} samples...", audio.len());
+    let final_text = tokio::task::spawn_blocking(move || transcribe(&model, audio))
+        .await
+        .map_err(|e| format!("Transcription task failed: {}", e))?
+        .map_err(|e| {
+            eprintln!("[stt] Transcription error: {}", e);
+            e
+        })?;
 
     println!("[stt] Final transcription: {}", final_text);
     Ok(final_text)
@@ -366,33 +893,190 @@ pub async fn stt_stop_recording(app: AppHandle) -> Result<String, String> {
 
 /// Cancel recording without transcribing (used when Option+other key is pressed)
 #[tauri::command]
-pub async fn stt_cancel_recording(app: AppHandle) -> Result<(), String> {
-    let state = get_state(&app);
-    let mut guard = state.lock().unwrap();
+pub async fn stt_cancel_recording(
+    state: tauri::State<'_, SttManagedState>,
+) -> Result<(), String> {
+    let state = state.0.clone();
+
+    let capture_thread = {
+        let mut guard = state.lock().unwrap();
+
+        if !guard.is_recording {
+            return Ok(());
+        }
+
+        println!("[stt] Recording cancelled");
+        guard.is_recording = false;
+        guard.cancel_partials();
+        guard.capture_thread.take()
+    };
+    if let Some(handle) = capture_thread {
+        join_capture_thread(handle);
+    }
+
+    state.lock().unwrap().audio_buffer.clear();
+    Ok(())
+}
+
+/// An audio input device available for recording
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// List available audio input devices
+#[tauri::command]
+pub fn stt_list_audio_devices() -> Vec<AudioDeviceInfo> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
 
-    if !guard.is_recording {
+    let Ok(devices) = host.input_devices() else {
+        return Vec::new();
+    };
+
+    devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| AudioDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+        })
+        .collect()
+}
+
+/// Select the input device used for recording (an empty name selects the system default).
+/// Takes effect on the next recording.
+#[tauri::command]
+pub fn stt_set_audio_device(
+    state: tauri::State<'_, SttManagedState>,
+    device_name: String,
+) -> Result<(), String> {
+    let mut guard = state.0.lock().unwrap();
+    if device_name.is_empty() {
+        guard.input_device = None;
         return Ok(());
     }
 
-    println!("[stt] Recording cancelled");
-    guard.is_recording = false;
-    guard.audio_buffer.clear();
+    let exists = cpal::default_host()
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {}", e))?
+        .any(|d| d.name().map(|n| n == device_name).unwrap_or(false));
+    if !exists {
+        return Err(format!("Input device not found: {}", device_name));
+    }
+
+    println!("[stt] Input device set to {}", device_name);
+    guard.input_device = Some(device_name);
     Ok(())
 }
 
 /// Check if currently recording
 #[tauri::command]
-pub async fn stt_is_recording(app: AppHandle) -> bool {
-    let state = get_state(&app);
-    let guard = state.lock().unwrap();
+pub fn stt_is_recording(state: tauri::State<'_, SttManagedState>) -> bool {
+    let guard = state.0.lock().unwrap();
     guard.is_recording
 }
 
+/// Input samples kept from the previous chunk for taps left of the current one
+const RESAMPLER_HISTORY: usize = 3;
+
+/// Streaming resampler from the input device rate to 16kHz.
+///
+/// Uses linear interpolation, or with `hq` a normalized 3-tap windowed sinc whose
+/// cutoff follows the ratio. The short kernel reduces aliasing when downsampling
+/// (e.g. 48kHz -> 16kHz) but doesn't remove it.
+///
+/// The tail of each chunk and the fractional read position carry over to the next
+/// call, so audio callback boundaries don't drop or repeat samples.
+struct Resampler {
+    /// Output rate / input rate
+    ratio: f64,
+    hq: bool,
+    /// Last input samples of the previous chunks
+    history: Vec<f32>,
+    /// Read position of the next output sample, relative to the start of the next chunk
+    pos: f64,
+}
+
+impl Resampler {
+    fn new(ratio: f64, hq: bool) -> Self {
+        Self { ratio, hq, history: Vec::new(), pos: 0.0 }
+    }
+
+    /// Resample one chunk. Output samples that need input past the end of `chunk`
+    /// are produced by the next call.
+    fn process(&mut self, chunk: &[f32]) -> Vec<f32> {
+        let len = chunk.len() as isize;
+        let step = 1.0 / self.ratio;
+        let history = &self.history;
+        // Negative indices read from the previous chunks; `None` before the stream start
+        let sample = |i: isize| -> Option<f32> {
+            if i >= 0 {
+                chunk.get(i as usize).copied()
+            } else {
+                let j = history.len() as isize + i;
+                (j >= 0).then(|| history[j as usize])
+            }
+        };
+
+        let cutoff = self.ratio.min(1.0);
+        let sinc = |x: f64| if x.abs() < 1e-9 { 1.0 } else { (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x) };
+
+        let mut output = Vec::with_capacity((chunk.len() as f64 * self.ratio) as usize + 1);
+        let mut pos = self.pos;
+        loop {
+            let base = if self.hq { pos.round() } else { pos.floor() } as isize;
+            if base + 1 >= len {
+                break;
+            }
+
+            if self.hq {
+                let mut acc = 0.0;
+                let mut weight_sum = 0.0;
+                for k in base - 1..=base + 1 {
+                    // Taps before the stream start don't count towards the normalization
+                    let Some(value) = sample(k) else { continue };
+                    let x = pos - k as f64;
+                    // Lanczos window over the 3 taps keeps the kernel compact
+                    let weight = sinc(cutoff * x) * sinc(x / 1.5);
+                    acc += weight * value as f64;
+                    weight_sum += weight;
+                }
+                output.push(if weight_sum.abs() < 1e-9 { 0.0 } else { (acc / weight_sum) as f32 });
+            } else {
+                let frac = (pos - base as f64) as f32;
+                let floor_val = sample(base).unwrap_or(0.0);
+                let ceil_val = sample(base + 1).unwrap_or(floor_val);
+                output.push(floor_val * (1.0 - frac) + ceil_val * frac);
+            }
+            pos += step;
+        }
+        self.pos = pos - len as f64;
+
+        let mut tail = std::mem::take(&mut self.history);
+        tail.extend_from_slice(chunk);
+        self.history = tail.split_off(tail.len().saturating_sub(RESAMPLER_HISTORY));
+
+        output
+    }
+}
+
 /// Capture audio (accumulates samples for batch transcription)
 fn capture_audio(state: Arc<Mutex<SttState>>) -> Result<(), String> {
     let host = cpal::default_host();
-    let device = host
-        .default_input_device()
+    let selected = state.lock().unwrap().input_device.clone();
+
+    // Use the selected device if it's still connected, otherwise the default
+    let device = selected
+        .and_then(|name| {
+            let device = host.input_devices().ok()?
+                .find(|d| d.name().map(|n| n == name).unwrap_or(false));
+            if device.is_none() {
+                eprintln!("[stt] Input device {} not found, using default", name);
+            }
+            device
+        })
+        .or_else(|| host.default_input_device())
         .ok_or("No input device available")?;
 
     println!("[stt] Using input device: {}", device.name().unwrap_or_default());
@@ -412,6 +1096,8 @@ fn capture_audio(state: Arc<Mutex<SttState>>) -> Result<(), String> {
 
     // Resampling ratio to 16kHz (what the model expects)
     let resample_ratio = 16000.0 / sample_rate as f64;
+    let hq_resample = std::env::var("STT_HQ_RESAMPLE").is_ok_and(|v| v == "1" || v == "true");
+    let mut resampler = Resampler::new(resample_ratio, hq_resample);
 
     let state_clone = state.clone();
     let err_fn = |err| eprintln!("[stt] Audio stream error: {}", err);
@@ -433,21 +1119,19 @@ fn capture_audio(state: Arc<Mutex<SttState>>) -> Result<(), String> {
                 };
 
                 // Resample to 16kHz
-                let resampled: Vec<f32> = (0..((mono.len() as f64 * resample_ratio) as usize))
-                    .map(|i| {
-                        let src_idx = (i as f64 / resample_ratio) as usize;
-                        mono.get(src_idx).copied().unwrap_or(0.0)
-                    })
-                    .collect();
-
-                guard.audio_buffer.extend_from_slice(&resampled);
+                let resampled = resampler.process(&mono);
+
+                guard.push_audio(&resampled);
             },
             err_fn,
             None,
         )
         .map_err(|e| format!("Failed to build input stream: {}", e))?;
 
-    println!("[stt] Audio stream built ({}Hz {}ch -> 16kHz mono)", sample_rate, channels);
+    println!(
+        "[stt] Audio stream built ({}Hz {}ch -> 16kHz mono, {} resampling)",
+        sample_rate, channels, if hq_resample { "sinc" } else { "linear" }
+    );
     stream.play().map_err(|e| format!("Failed to play stream: {}", e))?;
     println!("[stt] Recording...");
 
@@ -462,3 +1146,80 @@ fn capture_audio(state: Arc<Mutex<SttState>>) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn managed_state(model_path: &str) -> SttManagedState {
+        let state = SttManagedState(Arc::new(Mutex::new(SttState::new(model_path.to_string()))));
+        state.0.lock().unwrap().is_recording = true;
+        state
+    }
+
+    #[test]
+    fn managed_states_do_not_share_audio_buffers() {
+        let first = managed_state("/tmp/stt-model-a");
+        let second = managed_state("/tmp/stt-model-b");
+
+        first.0.lock().unwrap().push_audio(&[0.1, 0.2, 0.3]);
+        second.0.lock().unwrap().push_audio(&[0.9]);
+        assert_eq!(first.0.lock().unwrap().audio_buffer, vec![0.1, 0.2, 0.3]);
+        assert_eq!(second.0.lock().unwrap().audio_buffer, vec![0.9]);
+
+        // Clearing one recording leaves the other untouched
+        first.0.lock().unwrap().audio_buffer.clear();
+        assert_eq!(second.0.lock().unwrap().audio_buffer, vec![0.9]);
+
+        let (first, second) = (first.0.lock().unwrap(), second.0.lock().unwrap());
+        assert!(!Arc::ptr_eq(&first.model, &second.model));
+        assert_ne!(first.model_path, second.model_path);
+    }
+
+    #[test]
+    fn push_audio_ignores_samples_when_not_recording() {
+        let mut state = SttState::new("/tmp/stt-model".to_string());
+        state.push_audio(&[0.5; 160]);
+        assert!(state.audio_buffer.is_empty());
+    }
+
+    /// Resample `input` in chunks of the given sizes, cycling through them
+    fn resample_chunked(input: &[f32], ratio: f64, hq: bool, chunk_sizes: &[usize]) -> Vec<f32> {
+        let mut resampler = Resampler::new(ratio, hq);
+        let mut output = Vec::new();
+        let mut rest = input;
+        for size in chunk_sizes.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, tail) = rest.split_at((*size).min(rest.len()));
+            output.extend(resampler.process(chunk));
+            rest = tail;
+        }
+        output
+    }
+
+    #[test]
+    fn resampler_output_does_not_depend_on_chunk_boundaries() {
+        let input: Vec<f32> = (0..4800).map(|i| (i as f32 * 0.05).sin()).collect();
+        for hq in [false, true] {
+            let whole = Resampler::new(16000.0 / 48000.0, hq).process(&input);
+            assert!(whole.len() >= 1598, "only {} samples", whole.len());
+            for chunk_sizes in [&[480][..], &[1, 2, 7, 512, 3][..]] {
+                let chunked = resample_chunked(&input, 16000.0 / 48000.0, hq, chunk_sizes);
+                assert_eq!(chunked, whole, "hq={} chunks={:?}", hq, chunk_sizes);
+            }
+        }
+    }
+
+    #[test]
+    fn resampler_keeps_dc_level_at_stream_start_and_chunk_edges() {
+        for hq in [false, true] {
+            let output = resample_chunked(&[0.5; 4410], 16000.0 / 44100.0, hq, &[441]);
+            assert!(output.len() > 1590);
+            for (i, value) in output.iter().enumerate() {
+                assert!((value - 0.5).abs() < 1e-5, "hq={} sample {} = {}", hq, i, value);
+            }
+        }
+    }
+}
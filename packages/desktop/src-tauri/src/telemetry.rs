@@ -0,0 +1,159 @@
+//! Opt-in anonymous usage telemetry.
+//!
+//! Only event names and counts are recorded (e.g. `workbook_created: 3`) -
+//! never content, paths, or identifiers. Counts are batched in memory and
+//! POSTed to the configured endpoint by a background flush task.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use crate::AppState;
+
+const TELEMETRY_KEY: &str = "telemetry";
+
+/// In-memory event counters, flushed periodically
+pub type TelemetryCounts = Arc<std::sync::Mutex<HashMap<String, u64>>>;
+
+/// Telemetry settings (`settings.json["telemetry"]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub flush_interval_secs: u64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: String::new(),
+            flush_interval_secs: 300,
+        }
+    }
+}
+
+/// A single aggregated event in a flush batch
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryEvent {
+    pub event: String,
+    pub count: u64,
+    pub app_version: String,
+    pub os: String,
+}
+
+pub fn load_config(app: &AppHandle) -> TelemetryConfig {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get(TELEMETRY_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Count an event if telemetry is enabled
+pub async fn track(app: &AppHandle, event: &str) {
+    let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else {
+        return;
+    };
+    let state = state.lock().await;
+    record(&state, event);
+}
+
+/// Count an event using an already-held app state lock
+pub fn record(state: &AppState, event: &str) {
+    if !state.telemetry_enabled {
+        return;
+    }
+    *state.telemetry_counts.lock().unwrap().entry(event.to_string()).or_insert(0) += 1;
+}
+
+/// Periodically POST accumulated counts to the configured endpoint
+pub fn start_flush_task(app: AppHandle, counts: TelemetryCounts) {
+    tauri::async_runtime::spawn(async move {
+        let interval_secs = load_config(&app).flush_interval_secs.max(1);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        // The first tick fires immediately - skip it so we flush after a full interval
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let config = load_config(&app);
+            if !config.enabled || config.endpoint.is_empty() {
+                continue;
+            }
+
+            let batch: HashMap<String, u64> = std::mem::take(&mut *counts.lock().unwrap());
+            if batch.is_empty() {
+                continue;
+            }
+
+            let app_version = app.package_info().version.to_string();
+            let events: Vec<TelemetryEvent> = batch
+                .iter()
+                .map(|(event, count)| TelemetryEvent {
+                    event: event.clone(),
+                    count: *count,
+                    app_version: app_version.clone(),
+                    os: std::env::consts::OS.to_string(),
+                })
+                .collect();
+
+            let result = reqwest::Client::new()
+                .post(&config.endpoint)
+                .json(&events)
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await;
+
+            match result {
+                Ok(resp) if resp.status().is_success() => {}
+                other => {
+                    if let Err(e) = other {
+                        eprintln!("[telemetry] Flush failed: {}", e);
+                    }
+                    // Keep the counts for the next flush
+                    let mut current = counts.lock().unwrap();
+                    for (event, count) in batch {
+                        *current.entry(event).or_insert(0) += count;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_telemetry_config(app: AppHandle) -> TelemetryConfig {
+    load_config(&app)
+}
+
+#[tauri::command]
+pub async fn set_telemetry_enabled(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let config = TelemetryConfig {
+        enabled,
+        ..load_config(&app)
+    };
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(TELEMETRY_KEY, serde_json::json!(config));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    let mut state = state.lock().await;
+    state.telemetry_enabled = enabled;
+    if !enabled {
+        state.telemetry_counts.lock().unwrap().clear();
+    }
+
+    Ok(())
+}
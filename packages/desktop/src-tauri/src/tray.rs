@@ -4,13 +4,34 @@
 
 use tauri::{
     tray::{TrayIconEvent},
-    menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder, PredefinedMenuItem},
-    AppHandle, Manager, Wry, Emitter,
+    menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder},
+    AppHandle, Manager, Wry, Emitter, Listener,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::{Workbook, list_workbooks, create_workbook, CreateWorkbookRequest, AppState, window_manager};
+use crate::{Workbook, SessionSummary, scan_workbooks, create_workbook, CreateWorkbookRequest, AppState, window_manager, shell_ext};
+
+/// Countdown used by the "Capture Screen (3s delay...)" menu item
+const CAPTURE_DELAY_SECS: u8 = 3;
+
+/// Sessions shown in the "Recent Sessions" submenu
+const RECENT_SESSIONS_LIMIT: usize = 5;
+
+/// Sessions shown in each workbook's submenu
+const WORKBOOK_SESSIONS_LIMIT: usize = 3;
+
+/// Workbooks listed in the "Workbooks" submenu
+const MAX_TRAY_WORKBOOKS: usize = 10;
+
+/// Per-workbook status shown in the workbook's submenu
+#[derive(Debug, Default)]
+struct WorkbookMenuStatus {
+    runtime_running: bool,
+    active_jobs: usize,
+    sessions: Vec<SessionSummary>,
+}
 
 /// Configure the system tray (created from tauri.conf.json)
 pub fn create_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
@@ -20,7 +41,7 @@ pub fn create_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     println!("[tray] Found tray with id 'main'");
 
     // Build and set the menu (no active workbook initially)
-    let menu = build_tray_menu(app, &[], None)?;
+    let menu = build_tray_menu(app, &[], None, &[], &HashMap::new())?;
     tray.set_menu(Some(menu))?;
     println!("[tray] Menu set");
 
@@ -39,12 +60,81 @@ pub fn create_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         handle_menu_event(&app_handle, event.id.as_ref());
     });
 
+    // Keep the workbook list, active marker and capture presets in sync
+    for event in [
+        "workbook:created",
+        "workbook:deleted",
+        "workbook:updated",
+        "active-workbook-changed",
+        "capture:presets-changed",
+        "job:started",
+        "job:completed",
+    ] {
+        let app_handle = app.clone();
+        app.listen(event, move |_| {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = update_tray_menu(&app_handle).await {
+                    eprintln!("[tray] Failed to update tray menu: {}", e);
+                }
+            });
+        });
+    }
+
     println!("[tray] System tray configured with hands logo icon");
     Ok(())
 }
 
-/// Build the tray menu with current workbook list
-fn build_tray_menu(app: &AppHandle, workbooks: &[Workbook], active_workbook_id: Option<&str>) -> Result<Menu<Wry>, Box<dyn std::error::Error>> {
+/// Colored circle emoji closest to a `#rrggbb`/`#rgb` label color
+fn color_emoji(color: &str) -> Option<&'static str> {
+    const PALETTE: [(&str, [i32; 3]); 9] = [
+        ("🔴", [0xef, 0x44, 0x44]),
+        ("🟠", [0xf9, 0x73, 0x16]),
+        ("🟡", [0xea, 0xb3, 0x08]),
+        ("🟢", [0x22, 0xc5, 0x5e]),
+        ("🔵", [0x3b, 0x82, 0xf6]),
+        ("🟣", [0xa8, 0x55, 0xf7]),
+        ("🟤", [0x92, 0x40, 0x0e]),
+        ("⚫", [0x1f, 0x29, 0x37]),
+        ("⚪", [0xf3, 0xf4, 0xf6]),
+    ];
+
+    let hex = color.strip_prefix('#')?;
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| i32::from_str_radix(&hex[i..i + 2], 16).ok();
+    let rgb = [channel(0)?, channel(2)?, channel(4)?];
+
+    PALETTE
+        .iter()
+        .min_by_key(|(_, reference)| {
+            reference.iter().zip(rgb).map(|(a, b)| (a - b) * (a - b)).sum::<i32>()
+        })
+        .map(|(emoji, _)| *emoji)
+}
+
+/// Tray label for a session, marking running sessions with a dot
+fn session_label(session: &SessionSummary) -> String {
+    let title = session.title.as_deref().filter(|t| !t.is_empty()).unwrap_or("Untitled session");
+    if session.status == "running" {
+        format!("● {}", title)
+    } else {
+        format!("   {}", title)
+    }
+}
+
+/// Build the tray menu with current workbook list (with per-workbook runtime,
+/// job and session status) and the active workbook's recent sessions
+fn build_tray_menu(
+    app: &AppHandle,
+    workbooks: &[Workbook],
+    active_workbook_id: Option<&str>,
+    sessions: &[SessionSummary],
+    statuses: &HashMap<String, WorkbookMenuStatus>,
+) -> Result<Menu<Wry>, Box<dyn std::error::Error>> {
     let mut menu_builder = MenuBuilder::new(app);
 
     // Quick capture action
@@ -54,6 +144,30 @@ fn build_tray_menu(app: &AppHandle, workbooks: &[Workbook], active_workbook_id:
         .build(app)?;
     menu_builder = menu_builder.item(&capture_item);
 
+    let capture_delayed_item = MenuItemBuilder::new("Capture Screen (3s delay...)")
+        .id("capture_delayed")
+        .build(app)?;
+    menu_builder = menu_builder.item(&capture_delayed_item);
+
+    let capture_clipboard_item = MenuItemBuilder::new("Capture to Clipboard")
+        .id("capture_clipboard")
+        .accelerator("Super+Shift+Ctrl+H")
+        .build(app)?;
+    menu_builder = menu_builder.item(&capture_clipboard_item);
+
+    // Saved capture regions
+    let presets = crate::capture::load_capture_presets(app);
+    if !presets.is_empty() {
+        let mut presets_submenu = SubmenuBuilder::new(app, "Capture Preset");
+        for preset in &presets {
+            let item = MenuItemBuilder::new(&preset.name)
+                .id(format!("capture_preset:{}", preset.name))
+                .build(app)?;
+            presets_submenu = presets_submenu.item(&item);
+        }
+        menu_builder = menu_builder.item(&presets_submenu.build()?);
+    }
+
     menu_builder = menu_builder.separator();
 
     // Workbooks section
@@ -67,24 +181,80 @@ fn build_tray_menu(app: &AppHandle, workbooks: &[Workbook], active_workbook_id:
         // Build workbooks submenu
         let mut workbooks_submenu = SubmenuBuilder::new(app, "Workbooks");
 
-        for workbook in workbooks.iter().take(10) {
-            // Show checkmark for active workbook
+        for workbook in workbooks.iter().take(MAX_TRAY_WORKBOOKS) {
+            let status = statuses.get(&workbook.id);
+
+            // Show checkmark for active workbook and a count of running jobs
             let is_active = active_workbook_id == Some(&workbook.id);
+            let mut name = match workbook.color.as_deref().and_then(color_emoji) {
+                Some(emoji) => format!("{} {}", emoji, workbook.name),
+                None => workbook.name.clone(),
+            };
+            if let Some(count) = status.map(|s| s.active_jobs).filter(|&n| n > 0) {
+                name = format!("{} ({} active)", name, count);
+            }
             let label = if is_active {
-                format!("✓ {}", workbook.name)
+                format!("✓ {}", name)
             } else {
-                format!("   {}", workbook.name)
+                format!("   {}", name)
             };
-            let item = MenuItemBuilder::new(&label)
+
+            let open_window = MenuItemBuilder::new("Open Window")
+                .id(format!("open_window:{}", workbook.id))
+                .build(app)?;
+            let make_active = MenuItemBuilder::new("Make Active")
                 .id(format!("workbook:{}", workbook.id))
+                .enabled(!is_active)
+                .build(app)?;
+            let runtime_toggle = if status.is_some_and(|s| s.runtime_running) {
+                MenuItemBuilder::new("Stop Runtime")
+                    .id(format!("stop_runtime:{}", workbook.id))
+                    .build(app)?
+            } else {
+                MenuItemBuilder::new("Start Runtime")
+                    .id(format!("start_runtime:{}", workbook.id))
+                    .build(app)?
+            };
+            let show_in_files = MenuItemBuilder::new(shell_ext::file_manager_label())
+                .id(format!("show_in_files:{}", workbook.id))
                 .build(app)?;
-            workbooks_submenu = workbooks_submenu.item(&item);
+            let mut workbook_menu = SubmenuBuilder::new(app, &label)
+                .item(&open_window)
+                .item(&make_active)
+                .item(&runtime_toggle)
+                .item(&show_in_files);
+
+            // Last few agent sessions in this workbook
+            let workbook_sessions = status.map(|s| s.sessions.as_slice()).unwrap_or_default();
+            if !workbook_sessions.is_empty() {
+                workbook_menu = workbook_menu.separator();
+                for session in workbook_sessions {
+                    let item = MenuItemBuilder::new(session_label(session))
+                        .id(format!("workbook_session:{}:{}", workbook.id, session.session_id))
+                        .build(app)?;
+                    workbook_menu = workbook_menu.item(&item);
+                }
+            }
+
+            workbooks_submenu = workbooks_submenu.item(&workbook_menu.build()?);
         }
 
         let workbooks_menu = workbooks_submenu.build()?;
         menu_builder = menu_builder.item(&workbooks_menu);
     }
 
+    // Recent agent sessions in the active workbook
+    if !sessions.is_empty() {
+        let mut sessions_submenu = SubmenuBuilder::new(app, "Recent Sessions");
+        for session in sessions {
+            let item = MenuItemBuilder::new(session_label(session))
+                .id(format!("session:{}", session.session_id))
+                .build(app)?;
+            sessions_submenu = sessions_submenu.item(&item);
+        }
+        menu_builder = menu_builder.item(&sessions_submenu.build()?);
+    }
+
     // New workbook
     let new_workbook = MenuItemBuilder::new("New Workbook...")
         .id("new_workbook")
@@ -109,7 +279,10 @@ fn build_tray_menu(app: &AppHandle, workbooks: &[Workbook], active_workbook_id:
     menu_builder = menu_builder.separator();
 
     // Quit
-    let quit = PredefinedMenuItem::quit(app, Some("Quit Hands"))?;
+    let quit = MenuItemBuilder::new("Quit Hands")
+        .id("quit")
+        .accelerator("CmdOrCtrl+Q")
+        .build(app)?;
     menu_builder = menu_builder.item(&quit);
 
     Ok(menu_builder.build()?)
@@ -122,6 +295,22 @@ fn handle_menu_event(app: &AppHandle, menu_id: &str) {
             // Trigger screen capture flow
             start_capture_flow(app);
         }
+        "capture_delayed" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::capture::start_capture_delayed(app, CAPTURE_DELAY_SECS).await {
+                    eprintln!("[capture] Failed to start delayed capture: {}", e);
+                }
+            });
+        }
+        "capture_clipboard" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::capture::capture_to_clipboard(app).await {
+                    eprintln!("[capture] Failed to capture to clipboard: {}", e);
+                }
+            });
+        }
         "show_window" => {
             // Open/focus the workbook editor (the primary UI)
             show_or_open_workbook(app, None);
@@ -134,10 +323,54 @@ fn handle_menu_event(app: &AppHandle, menu_id: &str) {
             // Create a new workbook and open it
             create_and_open_workbook(app);
         }
+        "quit" => {
+            // Signal background tasks, then exit (see quit flow in lib.rs)
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::quit_app(&app).await;
+            });
+        }
+        id if id.starts_with("session:") => {
+            let session_id = id.strip_prefix("session:").unwrap().to_string();
+            open_session(app, None, session_id);
+        }
+        id if id.starts_with("workbook_session:") => {
+            let rest = id.strip_prefix("workbook_session:").unwrap();
+            if let Some((workbook_id, session_id)) = rest.split_once(':') {
+                open_session(app, Some(workbook_id.to_string()), session_id.to_string());
+            }
+        }
+        id if id.starts_with("open_window:") => {
+            let workbook_id = id.strip_prefix("open_window:").unwrap().to_string();
+            open_workbook_window(app, workbook_id);
+        }
+        id if id.starts_with("start_runtime:") => {
+            let workbook_id = id.strip_prefix("start_runtime:").unwrap().to_string();
+            toggle_runtime(app, workbook_id, true);
+        }
+        id if id.starts_with("stop_runtime:") => {
+            let workbook_id = id.strip_prefix("stop_runtime:").unwrap().to_string();
+            toggle_runtime(app, workbook_id, false);
+        }
         id if id.starts_with("workbook:") => {
             let workbook_id = id.strip_prefix("workbook:").unwrap();
             switch_active_workbook(app, workbook_id);
         }
+        id if id.starts_with("capture_preset:") => {
+            let name = id.strip_prefix("capture_preset:").unwrap().to_string();
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::capture::capture_preset(app, name).await {
+                    eprintln!("[capture] Failed to capture preset: {}", e);
+                }
+            });
+        }
+        id if id.starts_with("show_in_files:") => {
+            let workbook_id = id.strip_prefix("show_in_files:").unwrap();
+            if let Err(e) = shell_ext::show_workbook_in_file_manager(workbook_id.to_string()) {
+                eprintln!("[tray] {}", e);
+            }
+        }
         _ => {}
     }
 }
@@ -159,14 +392,77 @@ fn show_or_open_workbook(app: &AppHandle, event: Option<&'static str>) {
     });
 }
 
+/// Show a workbook's window (default: the active workbook) and ask it to open an agent session
+fn open_session(app: &AppHandle, workbook_id: Option<String>, session_id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else { return };
+        let workbook_id = match workbook_id {
+            Some(id) => id,
+            None => match state.lock().await.active_workbook_id.clone() {
+                Some(id) => id,
+                None => return,
+            },
+        };
+        match window_manager::open_workbook(&app, &state, &workbook_id).await {
+            Ok(label) => {
+                if let Some(window) = app.get_webview_window(&label) {
+                    let _ = window.emit("open-session", &session_id);
+                }
+            }
+            Err(e) => eprintln!("[tray] Failed to open workbook for session: {}", e),
+        }
+    });
+}
+
+/// Open (or focus) a workbook's window
+fn open_workbook_window(app: &AppHandle, workbook_id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else { return };
+        if let Err(e) = window_manager::open_workbook(&app, &state, &workbook_id).await {
+            eprintln!("[tray] Failed to open workbook: {}", e);
+        }
+    });
+}
+
+/// Start or stop a workbook's runtime, then refresh the menu to flip the toggle
+fn toggle_runtime(app: &AppHandle, workbook_id: String, start: bool) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else { return };
+        let result = if start {
+            match crate::get_workbook_dir(&workbook_id) {
+                Ok(dir) => {
+                    let directory = dir.to_string_lossy().to_string();
+                    crate::start_workbook_server_internal(&app, &state, &workbook_id, &directory)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                Err(e) => Err(e),
+            }
+        } else {
+            crate::stop_runtime_internal(&app, &state, workbook_id.clone(), None).await
+        };
+        if let Err(e) = result {
+            eprintln!("[tray] Failed to {} runtime for {}: {}", if start { "start" } else { "stop" }, workbook_id, e);
+        }
+
+        if let Err(e) = update_tray_menu(&app).await {
+            eprintln!("[tray] Failed to update tray menu: {}", e);
+        }
+    });
+}
+
 /// Create a new workbook and open it
 fn create_and_open_workbook(app: &AppHandle) {
     let app = app.clone();
     tauri::async_runtime::spawn(async move {
         // Create a new workbook
-        let workbook = match create_workbook(CreateWorkbookRequest {
+        let workbook = match create_workbook(app.clone(), CreateWorkbookRequest {
             name: "Untitled Notebook".to_string(),
             description: None,
+            template_id: None,
         }).await {
             Ok(wb) => wb,
             Err(e) => {
@@ -182,11 +478,6 @@ fn create_and_open_workbook(app: &AppHandle) {
         if let Err(e) = window_manager::open_workbook(&app, &state, &workbook.id).await {
             eprintln!("[tray] Failed to open workbook: {}", e);
         }
-
-        // Update tray menu to include new workbook
-        if let Err(e) = update_tray_menu(&app).await {
-            eprintln!("[tray] Failed to update tray menu: {}", e);
-        }
     });
 }
 
@@ -196,7 +487,7 @@ fn switch_active_workbook(app: &AppHandle, workbook_id: &str) {
     let workbook_id = workbook_id.to_string();
     tauri::async_runtime::spawn(async move {
         // Get workbook directory
-        let workbooks = match list_workbooks().await {
+        let workbooks = match scan_workbooks(false).await {
             Ok(w) => w,
             Err(e) => {
                 eprintln!("[tray] Failed to list workbooks: {}", e);
@@ -234,12 +525,12 @@ fn switch_active_workbook(app: &AppHandle, workbook_id: &str) {
             eprintln!("[tray] Failed to switch workbook: {}", e);
             return;
         }
-        println!("[tray] Switched to workbook: {}", workbook_id);
 
-        // 3. Update tray menu to show new active workbook
-        if let Err(e) = update_tray_menu(&app).await {
-            eprintln!("[tray] Failed to update menu: {}", e);
+        // 3. Point the floating chat (if open) at the new workbook
+        if let Err(e) = crate::floating_chat::update_floating_chat_context(app.clone(), workbook.directory.clone()).await {
+            eprintln!("[tray] Failed to update floating chat context: {}", e);
         }
+        println!("[tray] Switched to workbook: {}", workbook_id);
     });
 }
 
@@ -254,10 +545,59 @@ fn start_capture_flow(app: &AppHandle) {
     });
 }
 
+/// Base tray icon (same file as `trayIcon.iconPath` in tauri.conf.json)
+#[cfg(target_os = "macos")]
+const TRAY_ICON_PNG: &[u8] = include_bytes!("../icons/tray-icon.png");
+
+/// Reflect running background jobs in the tray tooltip and, on macOS, with a
+/// dot on the tray icon
+pub fn update_tray_badge(app: &AppHandle, count: usize) {
+    let Some(tray) = app.tray_by_id("main") else { return };
+
+    let tooltip = if count == 0 {
+        "Hands".to_string()
+    } else {
+        format!("Hands – {} job(s) running", count)
+    };
+    let _ = tray.set_tooltip(Some(&tooltip));
+
+    #[cfg(target_os = "macos")]
+    match tray_icon(count > 0) {
+        Ok(icon) => {
+            let _ = tray.set_icon(Some(icon));
+        }
+        Err(e) => eprintln!("[tray] Failed to update tray icon: {}", e),
+    }
+}
+
+/// The tray icon, optionally with an activity dot in the bottom-right corner
+#[cfg(target_os = "macos")]
+fn tray_icon(with_dot: bool) -> Result<tauri::image::Image<'static>, String> {
+    let mut rgba = image::load_from_memory(TRAY_ICON_PNG)
+        .map_err(|e| format!("Failed to decode tray icon: {}", e))?
+        .to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    if with_dot {
+        let radius = (width.min(height) as f32 / 6.0).max(2.0);
+        let (cx, cy) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+        for (x, y, pixel) in rgba.enumerate_pixels_mut() {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                *pixel = image::Rgba([255, 59, 48, 255]);
+            }
+        }
+    }
+
+    Ok(tauri::image::Image::new_owned(rgba.into_raw(), width, height))
+}
+
 /// Update the tray menu with current workbooks
 pub async fn update_tray_menu(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    // Fetch current workbooks
-    let workbooks = list_workbooks().await.unwrap_or_default();
+    // Fetch current workbooks, most recently opened first
+    let mut workbooks = scan_workbooks(false).await.unwrap_or_default();
+    let recent = window_manager::get_recent_workbooks(app);
+    workbooks.sort_by_key(|w| recent.iter().position(|id| *id == w.id).unwrap_or(usize::MAX));
 
     // Get active workbook ID
     let active_workbook_id = {
@@ -269,8 +609,45 @@ pub async fn update_tray_menu(app: &AppHandle) -> Result<(), Box<dyn std::error:
         }
     };
 
+    // Recent sessions for the active workbook (the agent may not be running yet)
+    let sessions = match (&active_workbook_id, app.try_state::<Arc<Mutex<AppState>>>()) {
+        (Some(workbook_id), Some(state)) => {
+            crate::fetch_workbook_sessions(&state, workbook_id, RECENT_SESSIONS_LIMIT)
+                .await
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+
+    // Runtime, job and session status for each listed workbook
+    let mut statuses = HashMap::new();
+    if let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() {
+        let listed = &workbooks[..workbooks.len().min(MAX_TRAY_WORKBOOKS)];
+        {
+            let state_guard = state.lock().await;
+            for workbook in listed {
+                statuses.insert(workbook.id.clone(), WorkbookMenuStatus {
+                    runtime_running: state_guard.runtime_manager.has_runtime(&workbook.id),
+                    active_jobs: state_guard.job_registry.list_active_for_workbook(&workbook.id).len(),
+                    sessions: Vec::new(),
+                });
+            }
+        }
+
+        // Session lists are cached, so this only hits the agent every SESSION_CACHE_TTL
+        let session_lists = futures_util::future::join_all(listed.iter().map(|workbook| {
+            crate::fetch_workbook_sessions(&state, &workbook.id, WORKBOOK_SESSIONS_LIMIT)
+        }))
+        .await;
+        for (workbook, result) in listed.iter().zip(session_lists) {
+            if let (Some(status), Ok(sessions)) = (statuses.get_mut(&workbook.id), result) {
+                status.sessions = sessions;
+            }
+        }
+    }
+
     // Rebuild menu with active workbook indicator
-    let menu = build_tray_menu(app, &workbooks, active_workbook_id.as_deref())?;
+    let menu = build_tray_menu(app, &workbooks, active_workbook_id.as_deref(), &sessions, &statuses)?;
 
     // Update tray menu
     if let Some(tray) = app.tray_by_id("main") {
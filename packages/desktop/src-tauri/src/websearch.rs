@@ -1,12 +1,367 @@
 //! Web search module using DuckDuckGo as the default provider
 //!
 //! Provides web search capabilities for the agent to gather information
-//! from the internet without requiring API keys.
+//! from the internet without requiring API keys. A Bing or Google provider
+//! can be configured as a fallback for when DuckDuckGo fails.
 
 use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
 use websearch::{providers::DuckDuckGoProvider, web_search, SearchOptions};
 
+use crate::{keychain, AppState};
+
+/// How long fetched robots.txt rules are reused
+const ROBOTS_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// Timeout for fetching robots.txt (crawling is allowed if it can't be fetched)
+const ROBOTS_FETCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Settings key for how long search results are cached
+const SEARCH_CACHE_TTL_KEY: &str = "search_cache_ttl_secs";
+/// Default search result cache lifetime
+const DEFAULT_SEARCH_CACHE_TTL_SECS: u64 = 300;
+
+/// User agent sent when fetching pages (and matched against robots.txt)
+const FETCH_USER_AGENT: &str = "Hands";
+/// Timeout for fetching a page's content
+const PAGE_FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+/// Pages larger than this are rejected
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Default cap on the extracted text length
+const DEFAULT_PAGE_MAX_CHARS: usize = 10_000;
+
+/// Settings key for the configured search provider
+pub(crate) const SEARCH_PROVIDER_KEY: &str = "search_provider";
+/// Keychain account holding the search provider's API key
+const SEARCH_PROVIDER_KEYCHAIN_KEY: &str = "search_provider.api_key";
+/// Timeout for Bing/Google API requests
+const SEARCH_API_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which search backend to use (`settings.json["search_provider"]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SearchProviderKind {
+    #[default]
+    DuckDuckGo,
+    Bing { api_key: String },
+    Google { api_key: String, cx: String },
+}
+
+type SearchFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<WebSearchResult>, String>> + Send + 'a>>;
+
+/// A web search backend
+pub trait SearchProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn search<'a>(&'a self, query: &'a str, max: usize) -> SearchFuture<'a>;
+}
+
+struct DuckDuckGo;
+
+impl SearchProvider for DuckDuckGo {
+    fn name(&self) -> &'static str {
+        "DuckDuckGo"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, max: usize) -> SearchFuture<'a> {
+        Box::pin(async move {
+            let results = web_search(SearchOptions {
+                query: query.to_string(),
+                max_results: Some(max as u32),
+                provider: Box::new(DuckDuckGoProvider::new()),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| format!("Search failed: {}", e))?;
+
+            Ok(results
+                .into_iter()
+                .map(|r| WebSearchResult {
+                    title: r.title,
+                    url: r.url,
+                    snippet: r.snippet,
+                })
+                .collect())
+        })
+    }
+}
+
+/// Bing Web Search API v7
+struct Bing {
+    api_key: String,
+}
+
+impl SearchProvider for Bing {
+    fn name(&self) -> &'static str {
+        "Bing"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, max: usize) -> SearchFuture<'a> {
+        Box::pin(async move {
+            let count = max.min(50).to_string();
+            let body: serde_json::Value = reqwest::Client::new()
+                .get("https://api.bing.microsoft.com/v7.0/search")
+                .query(&[("q", query), ("count", count.as_str())])
+                .header("Ocp-Apim-Subscription-Key", &self.api_key)
+                .timeout(SEARCH_API_TIMEOUT)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map_err(|e| format!("Bing search failed: {}", e))?
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Bing response: {}", e))?;
+
+            let items = body["webPages"]["value"].as_array().cloned().unwrap_or_default();
+            Ok(items
+                .iter()
+                .filter_map(|item| {
+                    Some(WebSearchResult {
+                        title: item["name"].as_str()?.to_string(),
+                        url: item["url"].as_str()?.to_string(),
+                        snippet: item["snippet"].as_str().map(|s| s.to_string()),
+                    })
+                })
+                .take(max)
+                .collect())
+        })
+    }
+}
+
+/// Google Programmable Search (Custom Search JSON API)
+struct Google {
+    api_key: String,
+    cx: String,
+}
+
+impl SearchProvider for Google {
+    fn name(&self) -> &'static str {
+        "Google"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, max: usize) -> SearchFuture<'a> {
+        Box::pin(async move {
+            // The API returns at most 10 results per request
+            let num = max.clamp(1, 10).to_string();
+            let body: serde_json::Value = reqwest::Client::new()
+                .get("https://www.googleapis.com/customsearch/v1")
+                .query(&[
+                    ("key", self.api_key.as_str()),
+                    ("cx", self.cx.as_str()),
+                    ("q", query),
+                    ("num", num.as_str()),
+                ])
+                .timeout(SEARCH_API_TIMEOUT)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map_err(|e| format!("Google search failed: {}", e))?
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Google response: {}", e))?;
+
+            let items = body["items"].as_array().cloned().unwrap_or_default();
+            Ok(items
+                .iter()
+                .filter_map(|item| {
+                    Some(WebSearchResult {
+                        title: item["title"].as_str()?.to_string(),
+                        url: item["link"].as_str()?.to_string(),
+                        snippet: item["snippet"].as_str().map(|s| s.to_string()),
+                    })
+                })
+                .collect())
+        })
+    }
+}
+
+/// Configured provider kind, with its API key resolved from the keychain
+fn load_search_provider_kind(app: &AppHandle) -> SearchProviderKind {
+    let kind: SearchProviderKind = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(SEARCH_PROVIDER_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let resolve = |api_key: String| {
+        if api_key == keychain::SENTINEL {
+            keychain::get_secret(keychain::SERVICE, SEARCH_PROVIDER_KEYCHAIN_KEY).unwrap_or_default()
+        } else {
+            api_key
+        }
+    };
+    match kind {
+        SearchProviderKind::DuckDuckGo => SearchProviderKind::DuckDuckGo,
+        SearchProviderKind::Bing { api_key } => SearchProviderKind::Bing { api_key: resolve(api_key) },
+        SearchProviderKind::Google { api_key, cx } => SearchProviderKind::Google { api_key: resolve(api_key), cx },
+    }
+}
+
+fn build_provider(kind: SearchProviderKind) -> Box<dyn SearchProvider> {
+    match kind {
+        SearchProviderKind::DuckDuckGo => Box::new(DuckDuckGo),
+        SearchProviderKind::Bing { api_key } => Box::new(Bing { api_key }),
+        SearchProviderKind::Google { api_key, cx } => Box::new(Google { api_key, cx }),
+    }
+}
+
+/// The search provider configured in `settings.json` (DuckDuckGo by default)
+pub fn get_search_provider(app: &AppHandle) -> Box<dyn SearchProvider> {
+    build_provider(load_search_provider_kind(app))
+}
+
+/// Providers to try in order: DuckDuckGo first (no API key needed), then the
+/// configured provider if it's a different one
+fn search_providers(app: &AppHandle) -> Vec<Box<dyn SearchProvider>> {
+    let mut providers: Vec<Box<dyn SearchProvider>> = vec![Box::new(DuckDuckGo)];
+    match load_search_provider_kind(app) {
+        SearchProviderKind::DuckDuckGo => {}
+        kind => providers.push(build_provider(kind)),
+    }
+    providers
+}
+
+/// Choose the search provider used when DuckDuckGo fails or finds nothing.
+/// API keys are stored in the keychain where available.
+#[tauri::command]
+pub fn set_search_provider(app: AppHandle, kind: SearchProviderKind) -> Result<(), String> {
+    let store_key = |api_key: &str| -> Result<String, String> {
+        let api_key = api_key.trim();
+        if api_key.is_empty() {
+            return Err("API key is required".to_string());
+        }
+        Ok(keychain::store_secret(SEARCH_PROVIDER_KEYCHAIN_KEY, api_key))
+    };
+
+    let stored = match &kind {
+        SearchProviderKind::DuckDuckGo => {
+            let _ = keychain::delete_secret(keychain::SERVICE, SEARCH_PROVIDER_KEYCHAIN_KEY);
+            SearchProviderKind::DuckDuckGo
+        }
+        SearchProviderKind::Bing { api_key } => SearchProviderKind::Bing { api_key: store_key(api_key)? },
+        SearchProviderKind::Google { api_key, cx } => {
+            if cx.trim().is_empty() {
+                return Err("Google search engine ID (cx) is required".to_string());
+            }
+            SearchProviderKind::Google { api_key: store_key(api_key)?, cx: cx.trim().to_string() }
+        }
+    };
+
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(SEARCH_PROVIDER_KEY, serde_json::json!(stored));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    println!("[websearch] Search provider set to {}", build_provider(kind).name());
+    Ok(())
+}
+
+/// robots.txt `Disallow` path prefixes by lowercased user agent (`*` for all).
+/// An agent with an empty list has its own group that allows everything.
+pub type RobotGroups = HashMap<String, Vec<String>>;
+
+/// robots.txt groups by origin, with the time they were fetched
+pub type RobotsCache = HashMap<String, (Instant, RobotGroups)>;
+
+/// Recent search responses keyed on `(query, max_results)`
+struct SearchCache {
+    entries: HashMap<(String, usize), (WebSearchResponse, Instant)>,
+    ttl: Duration,
+}
+
+impl SearchCache {
+    fn get(&mut self, key: &(String, usize)) -> Option<WebSearchResponse> {
+        let ttl = self.ttl;
+        self.entries.retain(|_, (_, cached_at)| cached_at.elapsed() < ttl);
+        self.entries.get(key).map(|(response, _)| response.clone())
+    }
+}
+
+static SEARCH_CACHE: OnceLock<Mutex<SearchCache>> = OnceLock::new();
+
+fn search_cache() -> &'static Mutex<SearchCache> {
+    SEARCH_CACHE.get_or_init(|| {
+        Mutex::new(SearchCache {
+            entries: HashMap::new(),
+            ttl: Duration::from_secs(DEFAULT_SEARCH_CACHE_TTL_SECS),
+        })
+    })
+}
+
+/// Refresh the cache TTL from `settings.json` (0 disables caching)
+fn load_search_cache_ttl(app: &AppHandle) {
+    let secs = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(SEARCH_CACHE_TTL_KEY))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_SEARCH_CACHE_TTL_SECS);
+    search_cache().lock().unwrap().ttl = Duration::from_secs(secs);
+}
+
+/// Run a search, reusing a cached response for the same query and limit.
+/// Falls back to the next provider when one errors or returns no results.
+/// Results are cached before whitelist filtering.
+async fn cached_web_search(app: &AppHandle, query: String, max: usize) -> Result<Vec<WebSearchResult>, String> {
+    let key = (query, max);
+    if let Some(response) = search_cache().lock().unwrap().get(&key) {
+        println!("[websearch] Cache hit: {}", key.0);
+        return Ok(response.results);
+    }
+
+    let mut results = Vec::new();
+    let mut last_error = None;
+    for provider in search_providers(app) {
+        match provider.search(&key.0, max).await {
+            Ok(found) if !found.is_empty() => {
+                results = found;
+                last_error = None;
+                break;
+            }
+            Ok(_) => println!("[websearch] {} returned no results", provider.name()),
+            Err(e) => {
+                eprintln!("[websearch] {} failed: {}", provider.name(), e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if let Some(e) = last_error {
+        return Err(e);
+    }
+
+    let mut cache = search_cache().lock().unwrap();
+    if !cache.ttl.is_zero() {
+        let response = WebSearchResponse {
+            query: key.0.clone(),
+            results: results.clone(),
+            count: results.len(),
+            context_extracted: false,
+            context_preview: None,
+        };
+        cache.entries.insert(key, (response, Instant::now()));
+    }
+
+    Ok(results)
+}
+
+/// Drop all cached search responses, returning how many were invalidated
+#[tauri::command]
+pub fn clear_search_cache() -> Result<usize, String> {
+    let mut cache = search_cache()
+        .lock()
+        .map_err(|e| format!("Failed to lock search cache: {}", e))?;
+    let count = cache.entries.len();
+    cache.entries.clear();
+    println!("[websearch] Cleared {} cached searches", count);
+    Ok(count)
+}
+
 /// A single search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSearchResult {
@@ -27,6 +382,45 @@ pub struct WebSearchResponse {
     pub results: Vec<WebSearchResult>,
     /// Number of results returned
     pub count: usize,
+    /// Whether text from a screenshot was added to the query
+    pub context_extracted: bool,
+    /// First 100 characters of the extracted screenshot text
+    pub context_preview: Option<String>,
+}
+
+/// Readable content of a fetched web page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageContent {
+    /// Final URL after redirects
+    pub url: String,
+    /// Contents of the page's `<title>` (empty if missing)
+    pub title: String,
+    /// Visible text from `<body>`, whitespace-collapsed and truncated
+    pub text: String,
+    /// Unix timestamp (seconds) of the fetch
+    pub fetched_at: u64,
+}
+
+/// Check that a `site:` filter is a bare domain/path (no spaces or query syntax)
+fn validate_site_filter(site: &str) -> Result<(), String> {
+    let valid = !site.is_empty()
+        && site.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/' | ':'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid site filter: {}", site))
+    }
+}
+
+/// Whether `url`'s host is one of `domains` (or a subdomain of one)
+fn is_whitelisted(url: &str, domains: &[String]) -> bool {
+    let Some(host) = reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase())) else {
+        return false;
+    };
+    domains.iter().any(|d| {
+        let d = d.to_lowercase();
+        host == d || host.ends_with(&format!(".{}", d))
+    })
 }
 
 /// Perform a web search using DuckDuckGo
@@ -34,31 +428,43 @@ pub struct WebSearchResponse {
 /// # Arguments
 /// * `query` - The search query
 /// * `max_results` - Maximum number of results to return (default: 10)
+/// * `site_filter` - Restrict results to a domain (e.g. `docs.rs`) via the `site:` operator
+///
+/// When a domain whitelist is set, results from other domains are dropped.
 #[tauri::command]
 pub async fn websearch_query(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<AppState>>>,
     query: String,
     max_results: Option<usize>,
+    site_filter: Option<String>,
 ) -> Result<WebSearchResponse, String> {
-    let max = max_results.unwrap_or(10);
+    let whitelist = state.lock().await.domain_whitelist.clone();
+    load_search_cache_ttl(&app);
+    run_query(&app, query, max_results, site_filter, &whitelist).await
+}
 
-    let provider = DuckDuckGoProvider::new();
+async fn run_query(
+    app: &AppHandle,
+    query: String,
+    max_results: Option<usize>,
+    site_filter: Option<String>,
+    whitelist: &[String],
+) -> Result<WebSearchResponse, String> {
+    let max = max_results.unwrap_or(10);
 
-    let results = web_search(SearchOptions {
-        query: query.clone(),
-        max_results: Some(max as u32),
-        provider: Box::new(provider),
-        ..Default::default()
-    })
-    .await
-    .map_err(|e| format!("Search failed: {}", e))?;
+    let search_query = match site_filter {
+        Some(site) => {
+            validate_site_filter(&site)?;
+            format!("site:{} {}", site, query)
+        }
+        None => query.clone(),
+    };
 
-    let search_results: Vec<WebSearchResult> = results
+    let search_results: Vec<WebSearchResult> = cached_web_search(app, search_query, max)
+        .await?
         .into_iter()
-        .map(|r| WebSearchResult {
-            title: r.title,
-            url: r.url,
-            snippet: r.snippet,
-        })
+        .filter(|r| whitelist.is_empty() || is_whitelisted(&r.url, whitelist))
         .collect();
 
     let count = search_results.len();
@@ -67,6 +473,8 @@ pub async fn websearch_query(
         query,
         results: search_results,
         count,
+        context_extracted: false,
+        context_preview: None,
     })
 }
 
@@ -77,28 +485,22 @@ pub async fn websearch_query(
 /// * `max_results_per_query` - Maximum results per query (default: 5)
 #[tauri::command]
 pub async fn websearch_batch(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<AppState>>>,
     queries: Vec<String>,
     max_results_per_query: Option<usize>,
 ) -> Result<Vec<WebSearchResponse>, String> {
     let max = max_results_per_query.unwrap_or(5);
+    let whitelist = state.lock().await.domain_whitelist.clone();
+    load_search_cache_ttl(&app);
 
     // Execute searches in parallel
+    let app = &app;
     let futures: Vec<_> = queries
         .into_iter()
-        .map(|query| {
-            let q = query.clone();
-            async move {
-                let provider = DuckDuckGoProvider::new();
-                let results = web_search(SearchOptions {
-                    query: query.clone(),
-                    max_results: Some(max as u32),
-                    provider: Box::new(provider),
-                    ..Default::default()
-                })
-                .await;
-
-                (q, results)
-            }
+        .map(|query| async move {
+            let results = cached_web_search(app, query.clone(), max).await;
+            (query, results)
         })
         .collect();
 
@@ -110,11 +512,7 @@ pub async fn websearch_batch(
             Ok(search_results) => {
                 let search_results: Vec<WebSearchResult> = search_results
                     .into_iter()
-                    .map(|r| WebSearchResult {
-                        title: r.title,
-                        url: r.url,
-                        snippet: r.snippet,
-                    })
+                    .filter(|r| whitelist.is_empty() || is_whitelisted(&r.url, &whitelist))
                     .collect();
 
                 let count = search_results.len();
@@ -122,6 +520,8 @@ pub async fn websearch_batch(
                     query,
                     results: search_results,
                     count,
+                    context_extracted: false,
+                    context_preview: None,
                 });
             }
             Err(e) => {
@@ -130,6 +530,8 @@ pub async fn websearch_batch(
                     query: format!("{} (error: {})", query, e),
                     results: vec![],
                     count: 0,
+                    context_extracted: false,
+                    context_preview: None,
                 });
             }
         }
@@ -137,3 +539,299 @@ pub async fn websearch_batch(
 
     Ok(responses)
 }
+
+/// Perform a web search enriched with text extracted from a screenshot
+///
+/// # Arguments
+/// * `screenshot_path` - Path to a captured screenshot (empty to skip OCR)
+/// * `query` - The search query
+/// * `max_results` - Maximum number of results to return (default: 10)
+///
+/// Falls back to the plain query if OCR fails or yields no text.
+#[tauri::command]
+pub async fn search_with_context(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<AppState>>>,
+    screenshot_path: String,
+    query: String,
+    max_results: Option<usize>,
+) -> Result<WebSearchResponse, String> {
+    let whitelist = state.lock().await.domain_whitelist.clone();
+    load_search_cache_ttl(&app);
+
+    let ocr_text = if screenshot_path.is_empty() {
+        None
+    } else {
+        match crate::capture::extract_text(&screenshot_path, Some(&crate::capture::load_ocr_language(&app))).await {
+            Ok(text) if !text.is_empty() => Some(text),
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("[websearch] OCR failed, using plain query: {}", e);
+                None
+            }
+        }
+    };
+
+    let Some(ocr_text) = ocr_text else {
+        return run_query(&app, query, max_results, None, &whitelist).await;
+    };
+
+    let context = ocr_text.split_whitespace().take(50).collect::<Vec<_>>().join(" ");
+    let enhanced_query = format!("{} {}", query, context);
+
+    let mut response = run_query(&app, enhanced_query, max_results, None, &whitelist).await?;
+    response.context_extracted = true;
+    response.context_preview = Some(ocr_text.chars().take(100).collect());
+
+    Ok(response)
+}
+
+/// Restrict web search results to these domains (and their subdomains).
+/// An empty list removes the restriction.
+#[tauri::command]
+pub async fn set_search_domain_whitelist(
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<AppState>>>,
+    domains: Vec<String>,
+) -> Result<(), String> {
+    for domain in &domains {
+        validate_site_filter(domain)?;
+    }
+    println!("[websearch] Domain whitelist: {:?}", domains);
+    state.lock().await.domain_whitelist = domains;
+    Ok(())
+}
+
+/// Fetch a web page and extract its title and visible text
+///
+/// # Arguments
+/// * `url` - The page to fetch
+/// * `max_chars` - Maximum length of the extracted text (default: 10,000)
+///
+/// Only `text/html` responses up to 5 MB are accepted.
+#[tauri::command]
+pub async fn websearch_fetch_page(
+    state: tauri::State<'_, Arc<tokio::sync::Mutex<AppState>>>,
+    url: String,
+    max_chars: Option<usize>,
+) -> Result<PageContent, String> {
+    let max_chars = max_chars.unwrap_or(DEFAULT_PAGE_MAX_CHARS);
+
+    let parsed = reqwest::Url::parse(&url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+    }
+    if !is_allowed_to_crawl(&state, &url, FETCH_USER_AGENT).await {
+        return Err(format!("Fetching {} is disallowed by robots.txt", url));
+    }
+
+    let mut resp = reqwest::Client::new()
+        .get(parsed)
+        .header(reqwest::header::USER_AGENT, FETCH_USER_AGENT)
+        .timeout(PAGE_FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Failed to fetch {}: HTTP {}", url, resp.status()));
+    }
+
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    if !content_type.starts_with("text/html") {
+        return Err(format!("Unsupported content type for {}: {}", url, content_type));
+    }
+
+    let too_large = || format!("Page {} exceeds {} MB", url, MAX_PAGE_BYTES / (1024 * 1024));
+    if resp.content_length().is_some_and(|len| len as usize > MAX_PAGE_BYTES) {
+        return Err(too_large());
+    }
+
+    // Content-Length may be missing or wrong, so enforce the limit while reading
+    let final_url = resp.url().to_string();
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("Failed to read {}: {}", url, e))? {
+        if body.len() + chunk.len() > MAX_PAGE_BYTES {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let (title, text) = extract_page_text(&String::from_utf8_lossy(&body), max_chars);
+
+    let fetched_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(PageContent {
+        url: final_url,
+        title,
+        text,
+        fetched_at,
+    })
+}
+
+/// Extract the `<title>` and visible `<body>` text from an HTML document,
+/// skipping script/style content and truncating the text to `max_chars`
+fn extract_page_text(html: &str, max_chars: usize) -> (String, String) {
+    let document = scraper::Html::parse_document(html);
+
+    let title_selector = scraper::Selector::parse("title").unwrap();
+    let title = document
+        .select(&title_selector)
+        .next()
+        .map(|t| t.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default();
+
+    let body_selector = scraper::Selector::parse("body").unwrap();
+    let Some(body) = document.select(&body_selector).next() else {
+        return (title, String::new());
+    };
+
+    let mut words = Vec::new();
+    for node in body.descendants() {
+        let Some(text) = node.value().as_text() else {
+            continue;
+        };
+        let hidden = node.ancestors().any(|a| {
+            a.value()
+                .as_element()
+                .is_some_and(|e| matches!(e.name(), "script" | "style" | "noscript" | "template"))
+        });
+        if !hidden {
+            words.extend(text.split_whitespace());
+        }
+    }
+
+    let text = words.join(" ").chars().take(max_chars).collect();
+    (title, text)
+}
+
+/// Parse robots.txt into per-agent groups. Consecutive `User-agent` lines share the
+/// rules that follow them.
+fn parse_robots_txt(content: &str) -> RobotGroups {
+    let mut groups = RobotGroups::new();
+    let mut agents: Vec<String> = Vec::new();
+    let mut in_rules = false;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match field.trim().to_lowercase().as_str() {
+            "user-agent" => {
+                // A user-agent after rules starts a new group
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                let agent = value.to_lowercase();
+                groups.entry(agent.clone()).or_default();
+                agents.push(agent);
+            }
+            "disallow" => {
+                in_rules = true;
+                // An empty Disallow means everything is allowed
+                if value.is_empty() {
+                    continue;
+                }
+                for agent in &agents {
+                    groups.entry(agent.clone()).or_default().push(value.to_string());
+                }
+            }
+            _ => {
+                in_rules = true;
+            }
+        }
+    }
+
+    groups
+}
+
+/// Get robots.txt groups for an origin, from cache or by fetching
+async fn get_robots_groups(state: &Arc<tokio::sync::Mutex<AppState>>, origin: &str) -> RobotGroups {
+    if let Some((fetched_at, groups)) = state.lock().await.robots_cache.get(origin) {
+        if fetched_at.elapsed() < ROBOTS_CACHE_TTL {
+            return groups.clone();
+        }
+    }
+
+    let robots_url = format!("{}/robots.txt", origin);
+    let groups = match reqwest::Client::new()
+        .get(&robots_url)
+        .timeout(ROBOTS_FETCH_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(resp) if resp.status().is_success() => {
+            parse_robots_txt(&resp.text().await.unwrap_or_default())
+        }
+        // Missing robots.txt or fetch errors: fail open
+        _ => RobotGroups::new(),
+    };
+
+    state.lock().await.robots_cache.insert(origin.to_string(), (Instant::now(), groups.clone()));
+    groups
+}
+
+/// Whether `path` may be fetched by `user_agent` (lowercased). The agent's own
+/// group replaces the `*` group entirely, even when it disallows nothing.
+fn is_path_allowed(groups: &RobotGroups, user_agent: &str, path: &str) -> bool {
+    let disallowed = groups
+        .get(user_agent)
+        .or_else(|| groups.get("*"))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    !disallowed.iter().any(|prefix| path.starts_with(prefix.as_str()))
+}
+
+/// Check whether robots.txt allows `user_agent` to fetch `url`.
+/// Rules for the specific user agent take precedence over `*` rules.
+pub async fn is_allowed_to_crawl(state: &Arc<tokio::sync::Mutex<AppState>>, url: &str, user_agent: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return true;
+    };
+    let origin = parsed.origin().ascii_serialization();
+    let path = parsed.path();
+
+    let groups = get_robots_groups(state, &origin).await;
+    is_path_allowed(&groups, &user_agent.to_lowercase(), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specific_group_with_empty_disallow_overrides_wildcard() {
+        let groups = parse_robots_txt("User-agent: *\nDisallow: /\n\nUser-agent: Hands\nDisallow:\n");
+        assert!(is_path_allowed(&groups, "hands", "/docs/page"));
+        assert!(!is_path_allowed(&groups, "otherbot", "/docs/page"));
+    }
+
+    #[test]
+    fn agents_sharing_a_group_get_the_same_rules() {
+        let groups = parse_robots_txt(
+            "User-agent: hands\nUser-agent: otherbot\nDisallow: /private # comment\n\nUser-agent: *\nDisallow: /tmp\n",
+        );
+        for agent in ["hands", "otherbot"] {
+            assert!(!is_path_allowed(&groups, agent, "/private/data"));
+            assert!(is_path_allowed(&groups, agent, "/tmp/file"));
+        }
+        assert!(!is_path_allowed(&groups, "thirdbot", "/tmp/file"));
+        assert!(is_path_allowed(&groups, "thirdbot", "/private/data"));
+    }
+
+    #[test]
+    fn missing_robots_txt_allows_everything() {
+        assert!(is_path_allowed(&RobotGroups::new(), "hands", "/"));
+    }
+}
@@ -1,33 +1,184 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
-use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
 use tauri_plugin_store::StoreExt;
 
-use crate::{get_workbook, list_workbooks, AppState};
+use crate::{get_workbook, scan_workbooks, AppState};
 
 const STORE_NAME: &str = "window-state.json";
 const LAST_WORKBOOK_KEY: &str = "last_opened_workbook";
+const RECENT_WORKBOOKS_KEY: &str = "recent_workbooks";
+const MAX_RECENT_WORKBOOKS: usize = 10;
+const WINDOW_POS_KEY_PREFIX: &str = "window_pos.";
+const DEVELOPER_MODE_KEY: &str = "developer_mode";
+/// How long window geometry must stay unchanged before it is written to disk
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Latest pending geometry save; earlier ones see a newer value and skip writing
+static GEOMETRY_SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Last position and size of a workbook window, in logical pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
 
 pub fn window_label(workbook_id: &str) -> String {
     format!("workbook_{}", workbook_id)
 }
 
 pub fn get_last_workbook(app: &AppHandle) -> Option<String> {
-    if let Ok(store) = app.store(STORE_NAME) {
-        store.get(LAST_WORKBOOK_KEY)
-            .and_then(|v| v.as_str().map(|s| s.to_string()))
-    } else {
-        None
+    get_recent_workbooks(app).into_iter().next()
+}
+
+/// Recently opened workbook IDs, most recent first
+pub fn get_recent_workbooks(app: &AppHandle) -> Vec<String> {
+    let Ok(store) = app.store(STORE_NAME) else {
+        return Vec::new();
+    };
+
+    if let Some(recent) = store.get(RECENT_WORKBOOKS_KEY)
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+    {
+        return recent;
     }
+
+    // Fall back to the single last-opened workbook stored by older versions
+    store.get(LAST_WORKBOOK_KEY)
+        .and_then(|v| v.as_str().map(|s| vec![s.to_string()]))
+        .unwrap_or_default()
 }
 
 pub fn set_last_workbook(app: &AppHandle, workbook_id: &str) {
+    let mut recent = get_recent_workbooks(app);
+    recent.retain(|id| id != workbook_id);
+    recent.insert(0, workbook_id.to_string());
+    recent.truncate(MAX_RECENT_WORKBOOKS);
+
     if let Ok(store) = app.store(STORE_NAME) {
-        let _ = store.set(LAST_WORKBOOK_KEY, serde_json::json!(workbook_id));
+        store.set(RECENT_WORKBOOKS_KEY, serde_json::json!(recent));
         let _ = store.save();
     }
 }
 
+fn load_window_geometry(app: &AppHandle, workbook_id: &str) -> Option<WindowGeometry> {
+    app.store(STORE_NAME)
+        .ok()?
+        .get(format!("{}{}", WINDOW_POS_KEY_PREFIX, workbook_id))
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+fn save_window_geometry(app: &AppHandle, workbook_id: &str, window: &WebviewWindow) {
+    let (Ok(position), Ok(size), Ok(scale)) = (window.outer_position(), window.inner_size(), window.scale_factor()) else {
+        return;
+    };
+    // Minimized windows report bogus geometry
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+
+    let geometry = WindowGeometry {
+        x: position.x as f64 / scale,
+        y: position.y as f64 / scale,
+        width: size.width as f64 / scale,
+        height: size.height as f64 / scale,
+    };
+    // Moved/Resized fire continuously while dragging, so only save once they stop
+    if let Ok(store) = app.store(STORE_NAME) {
+        store.set(format!("{}{}", WINDOW_POS_KEY_PREFIX, workbook_id), serde_json::json!(geometry));
+        let generation = GEOMETRY_SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(GEOMETRY_SAVE_DELAY).await;
+            if GEOMETRY_SAVE_GENERATION.load(Ordering::SeqCst) == generation {
+                let _ = store.save();
+            }
+        });
+    }
+}
+
+/// Whether the window's top-left corner lies on a currently connected monitor
+fn is_on_screen(app: &AppHandle, geometry: &WindowGeometry) -> bool {
+    let Ok(monitors) = app.available_monitors() else {
+        return false;
+    };
+    monitors.iter().any(|m| {
+        let scale = m.scale_factor();
+        let left = m.position().x as f64 / scale;
+        let top = m.position().y as f64 / scale;
+        let right = left + m.size().width as f64 / scale;
+        let bottom = top + m.size().height as f64 / scale;
+        geometry.x >= left && geometry.x < right && geometry.y >= top && geometry.y < bottom
+    })
+}
+
+/// Recently opened workbook IDs for quick-open, most recent first
+#[tauri::command]
+pub fn get_recent_workbook_ids(app: AppHandle) -> Vec<String> {
+    get_recent_workbooks(&app)
+}
+
+fn workbook_window(app: &AppHandle, workbook_id: &str) -> Result<WebviewWindow, String> {
+    app.get_webview_window(&window_label(workbook_id))
+        .ok_or_else(|| format!("No window open for workbook {}", workbook_id))
+}
+
+/// Whether the web inspector is available (`settings.json["developer_mode"]`).
+/// Off by default in release builds; debug builds always allow it.
+pub fn load_developer_mode(app: &AppHandle) -> bool {
+    cfg!(debug_assertions)
+        || app.store("settings.json")
+            .ok()
+            .and_then(|store| store.get(DEVELOPER_MODE_KEY))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+}
+
+fn require_developer_mode(app: &AppHandle) -> Result<(), String> {
+    if load_developer_mode(app) {
+        Ok(())
+    } else {
+        Err("Developer mode is off. Enable it in settings to use the web inspector.".to_string())
+    }
+}
+
+/// Whether developer mode (the workbook web inspector) is enabled
+#[tauri::command]
+pub fn get_developer_mode(app: AppHandle) -> bool {
+    load_developer_mode(&app)
+}
+
+/// Turn developer mode on or off
+#[tauri::command]
+pub fn set_developer_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store("settings.json")
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    store.set(DEVELOPER_MODE_KEY, serde_json::json!(enabled));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
+/// Open the web inspector for a workbook's window. Requires developer mode.
+#[tauri::command]
+pub fn open_workbook_devtools(app: AppHandle, workbook_id: String) -> Result<(), String> {
+    require_developer_mode(&app)?;
+    workbook_window(&app, &workbook_id)?.open_devtools();
+    Ok(())
+}
+
+/// Close the web inspector for a workbook's window. Requires developer mode.
+#[tauri::command]
+pub fn close_workbook_devtools(app: AppHandle, workbook_id: String) -> Result<(), String> {
+    require_developer_mode(&app)?;
+    workbook_window(&app, &workbook_id)?.close_devtools();
+    Ok(())
+}
+
 pub async fn open_workbook(
     app: &AppHandle,
     state: &Arc<Mutex<AppState>>,
@@ -43,21 +194,35 @@ pub async fn open_workbook(
         return Ok(label);
     }
 
-    let workbook = get_workbook(workbook_id.to_string()).await?;
+    let _ = app.emit("workbook:loading", serde_json::json!({ "workbook_id": workbook_id }));
+
+    let workbook = match get_workbook(workbook_id.to_string()).await {
+        Ok(workbook) => workbook,
+        Err(e) => {
+            emit_load_failed(app, workbook_id, &e);
+            return Err(e);
+        }
+    };
     let url = format!("index.html?workbook={}", workbook_id);
 
     let mut builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
         .title(&workbook.name)
-        .inner_size(900.0, 700.0)
         .min_inner_size(600.0, 400.0)
         .decorations(true)
         .transparent(false)
         .resizable(true)
         .shadow(true)
-        .center()
         // Disable Tauri's native drag-drop to allow react-dnd HTML5 backend to work
         .disable_drag_drop_handler();
 
+    // Restore the last position unless that monitor is no longer connected
+    builder = match load_window_geometry(app, workbook_id) {
+        Some(geometry) if is_on_screen(app, &geometry) => builder
+            .position(geometry.x, geometry.y)
+            .inner_size(geometry.width, geometry.height),
+        _ => builder.inner_size(900.0, 700.0).center(),
+    };
+
     #[cfg(target_os = "macos")]
     {
         use tauri::LogicalPosition;
@@ -67,9 +232,31 @@ pub async fn open_workbook(
             .traffic_light_position(LogicalPosition::new(16.0, 18.0));
     }
 
-    builder
-        .build()
-        .map_err(|e| format!("Failed to create workbook window: {}", e))?;
+    let window = match builder.build() {
+        Ok(window) => window,
+        Err(e) => {
+            let error = format!("Failed to create workbook window: {}", e);
+            emit_load_failed(app, workbook_id, &error);
+            return Err(error);
+        }
+    };
+
+    // Remember where the window was left for next time
+    let geometry_app = app.clone();
+    let geometry_label = label.clone();
+    let geometry_workbook_id = workbook_id.to_string();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_)) {
+            if let Some(window) = geometry_app.get_webview_window(&geometry_label) {
+                save_window_geometry(&geometry_app, &geometry_workbook_id, &window);
+            }
+        }
+    });
+
+    let _ = app.emit("workbook:loaded", serde_json::json!({
+        "workbook_id": workbook_id,
+        "label": label,
+    }));
 
     {
         let mut state_guard = state.lock().await;
@@ -82,6 +269,13 @@ pub async fn open_workbook(
     Ok(label)
 }
 
+fn emit_load_failed(app: &AppHandle, workbook_id: &str, error: &str) {
+    let _ = app.emit("workbook:load-failed", serde_json::json!({
+        "workbook_id": workbook_id,
+        "error": error,
+    }));
+}
+
 pub async fn open_startup_workbook(
     app: &AppHandle,
     state: &Arc<Mutex<AppState>>,
@@ -92,7 +286,7 @@ pub async fn open_startup_workbook(
         }
     }
 
-    let workbooks = list_workbooks().await.unwrap_or_default();
+    let workbooks = scan_workbooks(false).await.unwrap_or_default();
     if let Some(first) = workbooks.first() {
         return Ok(Some(open_workbook(app, state, &first.id).await?));
     }
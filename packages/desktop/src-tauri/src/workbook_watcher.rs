@@ -0,0 +1,182 @@
+//! Workbook file watching.
+//!
+//! Watches a workbook directory for external changes (e.g. files edited by the
+//! agent) and emits debounced `workbook:changed` events to that workbook's
+//! windows so the UI can refresh.
+
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+
+use crate::AppState;
+
+/// Quiet period before a batch of changes is emitted
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkbookChanged {
+    pub workbook_id: String,
+    /// Paths relative to the workbook directory
+    pub changed_files: Vec<String>,
+}
+
+/// Start watching a workbook directory for changes. No-op if already watched.
+#[tauri::command]
+pub async fn watch_workbook(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+) -> Result<(), String> {
+    let workbook_dir = crate::get_workbook_dir(&workbook_id)?;
+    if !workbook_dir.exists() {
+        return Err(format!("Workbook {} not found", workbook_id));
+    }
+
+    let mut state_guard = state.lock().await;
+    if state_guard.workbook_watchers.contains_key(&workbook_id) {
+        return Ok(());
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<PathBuf>();
+    let watch_root = workbook_dir.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) => {
+                // Filter here so a busy `node_modules` can't keep resetting the debounce
+                for path in event.paths {
+                    if let Some(relative) = watched_relative_path(&watch_root, &path) {
+                        let _ = tx.send(relative);
+                    }
+                }
+            }
+            Err(e) => eprintln!("[watcher] Watch error: {}", e),
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher
+        .watch(&workbook_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", workbook_dir.display(), e))?;
+    let mut watched_dirs = HashSet::new();
+    let entries = std::fs::read_dir(&workbook_dir)
+        .map_err(|e| format!("Failed to read {}: {}", workbook_dir.display(), e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let relative = PathBuf::from(entry.file_name());
+        if entry.file_type().is_ok_and(|t| t.is_dir()) && !crate::is_excluded_from_copy(&relative) {
+            watch_subdirectory(&mut watcher, &workbook_dir, &relative);
+            watched_dirs.insert(relative);
+        }
+    }
+
+    state_guard.workbook_watchers.insert(workbook_id.clone(), watcher);
+    drop(state_guard);
+
+    // The task ends when the watcher (and with it the sender) is dropped
+    tauri::async_runtime::spawn(forward_changes(app, workbook_id.clone(), workbook_dir, watched_dirs, rx));
+
+    println!("[watcher] Watching workbook {}", workbook_id);
+    Ok(())
+}
+
+/// Stop watching a workbook directory
+#[tauri::command]
+pub async fn unwatch_workbook(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    workbook_id: String,
+) -> Result<(), String> {
+    match state.lock().await.workbook_watchers.remove(&workbook_id) {
+        Some(_) => {
+            println!("[watcher] Stopped watching workbook {}", workbook_id);
+            Ok(())
+        }
+        None => Err(format!("Workbook {} is not being watched", workbook_id)),
+    }
+}
+
+/// Path relative to the workbook directory, unless it's the directory itself or
+/// git internals, dependencies or database files
+fn watched_relative_path(workbook_dir: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(workbook_dir).ok()?;
+    if relative.as_os_str().is_empty() || crate::is_excluded_from_copy(relative) {
+        return None;
+    }
+    Some(relative.to_path_buf())
+}
+
+/// Recursively watch a top-level workbook directory. The workbook root itself is
+/// watched non-recursively so excluded trees (`node_modules`, `postgres`) are never walked.
+fn watch_subdirectory(watcher: &mut notify::RecommendedWatcher, workbook_dir: &Path, relative: &Path) {
+    let path = workbook_dir.join(relative);
+    if let Err(e) = watcher.watch(&path, RecursiveMode::Recursive) {
+        eprintln!("[watcher] Failed to watch {}: {}", path.display(), e);
+    }
+}
+
+/// Collect changed paths until `DEBOUNCE` passes without a change, then emit them.
+/// Top-level directories created after the watch started are watched as they appear.
+async fn forward_changes(
+    app: AppHandle,
+    workbook_id: String,
+    workbook_dir: PathBuf,
+    mut watched_dirs: HashSet<PathBuf>,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<PathBuf>,
+) {
+    let mut pending = BTreeSet::new();
+
+    loop {
+        let next = if pending.is_empty() {
+            rx.recv().await
+        } else {
+            match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    emit_changed(&app, &workbook_id, std::mem::take(&mut pending)).await;
+                    continue;
+                }
+            }
+        };
+
+        let Some(relative) = next else { break };
+
+        if relative.components().count() == 1 {
+            if !workbook_dir.join(&relative).is_dir() {
+                // Removed directories lose their watch; re-add it if they come back
+                watched_dirs.remove(&relative);
+            } else if watched_dirs.insert(relative.clone()) {
+                if let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() {
+                    if let Some(watcher) = state.lock().await.workbook_watchers.get_mut(&workbook_id) {
+                        watch_subdirectory(watcher, &workbook_dir, &relative);
+                    }
+                }
+            }
+        }
+        pending.insert(relative.to_string_lossy().replace('\\', "/"));
+    }
+}
+
+/// Emit `workbook:changed` to the workbook's window and any other windows using its runtime
+async fn emit_changed(app: &AppHandle, workbook_id: &str, changed_files: BTreeSet<String>) {
+    let mut labels = vec![crate::window_manager::window_label(workbook_id)];
+    if let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() {
+        if let Some(runtime) = state.lock().await.runtime_manager.get(workbook_id) {
+            labels.extend(runtime.windows.iter().cloned());
+        }
+    }
+    labels.sort();
+    labels.dedup();
+
+    let payload = WorkbookChanged {
+        workbook_id: workbook_id.to_string(),
+        changed_files: changed_files.into_iter().collect(),
+    };
+    for label in labels {
+        if app.get_webview_window(&label).is_some() {
+            let _ = app.emit_to(label.as_str(), "workbook:changed", &payload);
+        }
+    }
+}
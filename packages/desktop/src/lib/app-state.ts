@@ -15,6 +15,9 @@ export interface JobInfo {
   description: string;
   started_at: number;
   updated_at: number;
+  /** Fraction complete (0-1), if the agent reports it */
+  progress: number | null;
+  current_step: string | null;
 }
 
 export interface RuntimeStatus {
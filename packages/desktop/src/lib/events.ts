@@ -20,6 +20,26 @@ export interface WorkbookEvents {
   };
   /** Emitted when a workbook is opened in a window */
   "workbook-opened": string;
+  /** Emitted when a running workbook gains or loses uncommitted changes */
+  "workbook:dirty-changed": {
+    workbook_id: string;
+    is_dirty: boolean;
+  };
+  /** Emitted to a workbook's windows when its files change on disk (debounced) */
+  "workbook:changed": {
+    workbook_id: string;
+    changed_files: string[];
+  };
+  /** Emitted while a runtime waits for its active jobs before stopping */
+  "runtime:draining": {
+    workbook_id: string;
+    remaining_jobs: number;
+  };
+  /** Emitted for each line a runtime writes to its log (stderr) */
+  "runtime:log-line": {
+    workbook_id: string;
+    line: string;
+  };
 }
 
 /** Floating chat events */
@@ -32,6 +52,10 @@ export interface FloatingChatEvents {
   "floating-chat-collapsed": undefined;
   /** Emitted to send a prompt to floating chat */
   "floating-chat-prompt": string;
+  /** Emitted when the floating chat should switch to another workbook */
+  "floating-chat-context-changed": {
+    workbook_dir: string;
+  };
 }
 
 /** Keyboard/hotkey events */
@@ -46,14 +70,34 @@ export interface KeyboardEvents {
   "option-key-cancelled": undefined;
   /** Option+Space was pressed (toggle expand/collapse) */
   "option-space-pressed": undefined;
+  /** A shortcut registered with register_workbook_hotkey was pressed */
+  "hotkey:triggered": {
+    workbook_id: string;
+    action: string;
+  };
+}
+
+/** STT model download progress */
+export interface SttDownloadProgress {
+  file: string;
+  file_index: number;
+  file_count: number;
+  file_bytes_done: number;
+  file_bytes_total: number;
+  total_bytes_done: number;
+  total_bytes_total: number;
+  elapsed_secs: number;
+  eta_secs: number | null;
 }
 
 /** Speech-to-text events */
 export interface SttEvents {
-  /** Download progress (0.0 to 1.0) */
-  "stt:download-progress": number;
+  /** Download progress */
+  "stt:download-progress": SttDownloadProgress;
   /** Partial transcription result */
   "stt:partial": string;
+  /** Final transcription from the push-to-talk shortcut */
+  "stt:transcription": string;
 }
 
 /** Background job events */
@@ -64,6 +108,14 @@ export interface JobEvents {
   "job:completed": string;
   /** Job failed */
   "job:failed": string;
+  /** Job cancelled by the user */
+  "job:cancelled": string;
+  /** Agent reported progress on a running job */
+  "job:progress": {
+    job_id: string;
+    progress: number;
+    step: string | null;
+  };
 }
 
 /** Navigation events */
@@ -72,6 +124,8 @@ export interface NavigationEvents {
   navigate: string;
   /** Open settings panel */
   "open-settings": undefined;
+  /** Open an agent session (from the tray's Recent Sessions menu) */
+  "open-session": string;
 }
 
 /** All application events */
@@ -5,7 +5,7 @@
  * wrapped in the Tauri platform adapter.
  */
 
-import { App, PlatformProvider } from "@hands/app";
+import { App, initPorts, PlatformProvider } from "@hands/app";
 import { QueryClient, QueryClientProvider } from "@tanstack/react-query";
 import React from "react";
 import ReactDOM from "react-dom/client";
@@ -90,7 +90,10 @@ function getComponent() {
   }
 }
 
-// biome-ignore lint/style/noNonNullAssertion: root element is guaranteed to exist in index.html
-ReactDOM.createRoot(document.getElementById("root")!).render(
-  <React.StrictMode>{getComponent()}</React.StrictMode>,
-);
+// Load the configured port prefix before any component reads PORTS
+initPorts().finally(() => {
+  // biome-ignore lint/style/noNonNullAssertion: root element is guaranteed to exist in index.html
+  ReactDOM.createRoot(document.getElementById("root")!).render(
+    <React.StrictMode>{getComponent()}</React.StrictMode>,
+  );
+});
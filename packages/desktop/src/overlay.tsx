@@ -1,7 +1,8 @@
 /**
  * Overlay Entry Point
  *
- * Separate entry for transparent overlay windows (capture overlay, capture action panel, floating chat).
+ * Separate entry for transparent overlay windows (capture overlay, capture action panel,
+ * capture countdown, floating chat).
  */
 
 import { initTheme, PlatformProvider } from "@hands/app";
@@ -11,6 +12,7 @@ import ReactDOM from "react-dom/client";
 import { TooltipProvider } from "@/components/ui/tooltip";
 import { TauriPlatformAdapter } from "./platform/TauriAdapter";
 import { CaptureActionPanel } from "./windows/CaptureActionPanel";
+import { CaptureCountdown } from "./windows/CaptureCountdown";
 import { CaptureOverlay } from "./windows/CaptureOverlay";
 import { FloatingChat } from "./windows/FloatingChat";
 import "./index.css";
@@ -20,10 +22,11 @@ initTheme();
 
 const queryClient = new QueryClient();
 
-function getWindowType(): "capture-overlay" | "capture-action" | "floating-chat" {
+function getWindowType(): "capture-overlay" | "capture-action" | "capture-countdown" | "floating-chat" {
   const params = new URLSearchParams(window.location.search);
   if (params.has("floating-chat")) return "floating-chat";
   if (params.has("capture-action")) return "capture-action";
+  if (params.has("capture-countdown")) return "capture-countdown";
   return "capture-overlay";
 }
 
@@ -42,6 +45,9 @@ function App() {
   if (windowType === "capture-action") {
     return <CaptureActionPanel />;
   }
+  if (windowType === "capture-countdown") {
+    return <CaptureCountdown />;
+  }
   return <CaptureOverlay />;
 }
 
@@ -134,7 +134,7 @@ export const TauriPlatformAdapter: PlatformAdapter = {
 
     create: async (name: string, template?: string): Promise<Workbook> => {
       return invoke<Workbook>("create_workbook", {
-        request: { name, template },
+        request: { name, template_id: template },
       });
     },
 
@@ -9,7 +9,7 @@
 
 import { useQuery } from "@tanstack/react-query";
 import { convertFileSrc, invoke } from "@tauri-apps/api/core";
-import { emit } from "@tauri-apps/api/event";
+import { emit, listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { motion } from "framer-motion";
 import { ChevronDown, Database, Hand, RefreshCw, Wand2, X } from "lucide-react";
@@ -68,7 +68,9 @@ export function CaptureActionPanel() {
   const [screenshotUrl, setScreenshotUrl] = useState<string | null>(null);
   const [imgWidth, setImgWidth] = useState<number>(0);
   const [imgHeight, setImgHeight] = useState<number>(0);
-  const [_panelId, setPanelId] = useState<string>("");
+  const [panelId, setPanelId] = useState<string>("");
+  const [ocrEnabled, setOcrEnabled] = useState(false);
+  const [ocrText, setOcrText] = useState<string | null>(null);
   const [workbooks, setWorkbooks] = useState<Workbook[]>([]);
   const [selectedWorkbook, setSelectedWorkbook] = useState<string>("");
   const [showDropdown, setShowDropdown] = useState(false);
@@ -100,6 +102,8 @@ export function CaptureActionPanel() {
       setPanelId(id);
     }
 
+    setOcrEnabled(params.get("ocr") === "true");
+
     if (width) {
       setImgWidth(parseInt(width, 10));
     }
@@ -114,8 +118,11 @@ export function CaptureActionPanel() {
       try {
         const list = await invoke<Workbook[]>("list_workbooks");
         setWorkbooks(list);
-        if (list.length > 0) {
-          setSelectedWorkbook(list[0].id);
+        // Default to the workbook that was active when the capture started
+        const activeId = new URLSearchParams(window.location.search).get("workbook-id");
+        const initial = list.find((w) => w.id === activeId) ?? list[0];
+        if (initial) {
+          setSelectedWorkbook(initial.id);
         }
       } catch (err) {
         console.error("Failed to load workbooks:", err);
@@ -155,6 +162,41 @@ export function CaptureActionPanel() {
     staleTime: Infinity,
   });
 
+  // Text recognized in the screenshot (when OCR is enabled) to include in the prompt
+  useEffect(() => {
+    if (!ocrEnabled || !panelId) return;
+
+    let cancelled = false;
+    const unlistenComplete = listen<{ panel_id: string; text: string }>(
+      "capture:ocr-complete",
+      (event) => {
+        if (event.payload.panel_id === panelId) setOcrText(event.payload.text);
+      },
+    );
+    const unlistenFailed = listen<{ panel_id: string; error: string }>(
+      "capture:ocr-failed",
+      (event) => {
+        if (event.payload.panel_id === panelId) {
+          console.error("[CapturePanel] OCR failed:", event.payload.error);
+        }
+      },
+    );
+
+    // OCR may have finished before we started listening
+    Promise.all([unlistenComplete, unlistenFailed])
+      .then(() => invoke<string | null>("capture_ocr_result", { panelId }))
+      .then((text) => {
+        if (!cancelled && text != null) setOcrText(text);
+      })
+      .catch((err) => console.error("[CapturePanel] OCR failed:", err));
+
+    return () => {
+      cancelled = true;
+      unlistenComplete.then((unlisten) => unlisten());
+      unlistenFailed.then((unlisten) => unlisten());
+    };
+  }, [ocrEnabled, panelId]);
+
   // Log errors
   useEffect(() => {
     if (error) {
@@ -203,10 +245,14 @@ export function CaptureActionPanel() {
       }
 
       try {
+        const prompt = ocrText
+          ? `${action.prompt}\n\nText in the screenshot:\n${ocrText}`
+          : action.prompt;
+
         // Emit event with the action prompt for the workbook window to pick up
         await emit("capture-action-prompt", {
           workbookId: selectedWorkbook,
-          prompt: action.prompt,
+          prompt,
           actionType: action.type,
           label: action.label,
         });
@@ -221,7 +267,7 @@ export function CaptureActionPanel() {
         console.error("[CapturePanel] Failed to execute action:", err);
       }
     },
-    [selectedWorkbook, handleClose],
+    [selectedWorkbook, handleClose, ocrText],
   );
 
   const selectedWorkbookName =
@@ -0,0 +1,57 @@
+/**
+ * Capture Countdown
+ *
+ * Transient always-on-top window shown during a delayed capture.
+ * Click or press Escape to cancel.
+ */
+
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+
+function initialRemaining(): number {
+  const params = new URLSearchParams(window.location.search);
+  return Number(params.get("remaining")) || 0;
+}
+
+async function cancelCountdown() {
+  try {
+    await invoke("cancel_capture_countdown");
+  } catch (err) {
+    console.error("Failed to cancel capture countdown:", err);
+  }
+}
+
+export function CaptureCountdown() {
+  const [remaining, setRemaining] = useState(initialRemaining);
+
+  useEffect(() => {
+    const unlisten = listen<{ remaining: number }>("capture:countdown", (event) => {
+      setRemaining(event.payload.remaining);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key === "Escape") cancelCountdown();
+    };
+    window.addEventListener("keydown", handleKeyDown);
+    return () => window.removeEventListener("keydown", handleKeyDown);
+  }, []);
+
+  return (
+    <div className="flex h-screen w-screen items-center justify-center">
+      <button
+        type="button"
+        onClick={cancelCountdown}
+        title="Cancel capture"
+        className="flex h-24 w-24 items-center justify-center rounded-full bg-black/70 text-5xl font-semibold text-white tabular-nums"
+      >
+        {remaining}
+      </button>
+    </div>
+  );
+}
@@ -55,6 +55,7 @@ import {
 } from "@/hooks/useSession";
 // Use shared api and hooks - same as workbook editor
 import type { Session } from "@/lib/api";
+import type { SttDownloadProgress } from "@/lib/events";
 import { startSSESync } from "@/lib/sse";
 
 // ============================================================================
@@ -286,10 +287,12 @@ export function FloatingChat() {
 
       // STT download progress
       unlisteners.push(
-        await listen<number>("stt:download-progress", (event) => {
+        await listen<SttDownloadProgress>("stt:download-progress", (event) => {
           if (isWorkbookOpenRef.current) return;
-          setSttDownloadProgress(event.payload);
-          if (event.payload >= 1) {
+          const { total_bytes_done, total_bytes_total } = event.payload;
+          const progress = total_bytes_total > 0 ? total_bytes_done / total_bytes_total : 0;
+          setSttDownloadProgress(progress);
+          if (total_bytes_total > 0 && total_bytes_done >= total_bytes_total) {
             // Download complete, reset after a moment
             setTimeout(() => {
               setSttDownloading(false);
@@ -392,27 +395,35 @@ export function FloatingChat() {
     return dir;
   });
 
-  // Listen for active workbook changes - invalidate caches to refetch from new workbook
+  // Listen for active workbook / context changes - invalidate caches to refetch from new workbook
   useEffect(() => {
-    const unlisten = listen<{ workbook_id: string; workbook_dir: string }>(
-      "active-workbook-changed",
-      (event) => {
+    const switchWorkbook = (newDir: string) => {
+      if (newDir && newDir !== workbookDir) {
+        setWorkbookDir(newDir);
+        setActiveSessionId(null); // Clear active session when switching workbooks
+        // Invalidate runtime cache so it re-fetches from Tauri
+        queryClient.invalidateQueries({ queryKey: ["active-runtime"] });
+        // Invalidate all session-related queries to refetch from new workbook
+        queryClient.invalidateQueries({ queryKey: ["sessions"] });
+        queryClient.invalidateQueries({ queryKey: ["messages"] });
+        queryClient.invalidateQueries({ queryKey: ["session-statuses"] });
+      }
+    };
+
+    const unlisteners = [
+      listen<{ workbook_id: string; workbook_dir: string }>("active-workbook-changed", (event) => {
         console.log("[FloatingChat] Active workbook changed:", event.payload);
-        const newDir = event.payload.workbook_dir;
-        if (newDir && newDir !== workbookDir) {
-          setWorkbookDir(newDir);
-          setActiveSessionId(null); // Clear active session when switching workbooks
-          // Invalidate runtime cache so it re-fetches from Tauri
-          queryClient.invalidateQueries({ queryKey: ["active-runtime"] });
-          // Invalidate all session-related queries to refetch from new workbook
-          queryClient.invalidateQueries({ queryKey: ["sessions"] });
-          queryClient.invalidateQueries({ queryKey: ["messages"] });
-          queryClient.invalidateQueries({ queryKey: ["session-statuses"] });
-        }
-      },
-    );
+        switchWorkbook(event.payload.workbook_dir);
+      }),
+      listen<{ workbook_dir: string }>("floating-chat-context-changed", (event) => {
+        console.log("[FloatingChat] Context changed:", event.payload);
+        switchWorkbook(event.payload.workbook_dir);
+      }),
+    ];
     return () => {
-      unlisten.then((fn) => fn());
+      for (const unlisten of unlisteners) {
+        unlisten.then((fn) => fn());
+      }
     };
   }, [workbookDir, queryClient, setActiveSessionId]);
 
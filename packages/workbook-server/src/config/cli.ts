@@ -4,12 +4,12 @@
  * Called by Tauri and can be used standalone.
  *
  * Usage:
- *   bun run packages/runtime/src/config/cli.ts init --name="My Workbook" --dir="/path/to/workbook"
+ *   bun run packages/runtime/src/config/cli.ts init --name="My Workbook" --dir="/path/to/workbook" [--template=<id>]
  */
 
 import { initWorkbook } from "./index.js";
 
-function parseArgs(): { command: string; name?: string; dir?: string } {
+function parseArgs(): { command: string; name?: string; dir?: string; template?: string } {
   const args: Record<string, string> = {};
   let command = "";
 
@@ -26,20 +26,21 @@ function parseArgs(): { command: string; name?: string; dir?: string } {
     command,
     name: args.name,
     dir: args.dir,
+    template: args.template,
   };
 }
 
 async function main() {
-  const { command, name, dir } = parseArgs();
+  const { command, name, dir, template } = parseArgs();
 
   if (command === "init") {
     if (!name || !dir) {
-      console.error("Usage: init --name=<name> --dir=<directory>");
+      console.error("Usage: init --name=<name> --dir=<directory> [--template=<id>]");
       process.exit(1);
     }
 
     try {
-      await initWorkbook({ name, directory: dir });
+      await initWorkbook({ name, directory: dir, template });
       console.log(JSON.stringify({ success: true }));
     } catch (err) {
       console.error(
@@ -310,6 +310,8 @@ export interface InitWorkbookOptions {
   description?: string;
   /** Target directory (must exist or will be created) */
   directory: string;
+  /** Starter template ID (see WORKBOOK_TEMPLATES); omitted for an empty workbook */
+  template?: string;
 }
 
 /**
@@ -384,14 +386,88 @@ This is your new workbook. Here's how to get started:
 Happy analyzing!
 `;
 
+// Data Analysis template: a starter page for exploring imported data
+const DATA_ANALYSIS_PAGE_TEMPLATE = `---
+title: "Analysis"
+---
+
+# Analysis
+
+Use this page to explore the data in your workbook.
+
+## Import
+
+Drag CSV, JSON, or Parquet files into the chat to load them into tables.
+Ask the agent to clean up column names and types as they come in.
+
+## Explore
+
+- Ask for summaries: row counts, distributions, missing values
+- Reference tables with \`@\` to query them directly
+- Ask for a chart of any query result to add it to this page
+
+## Findings
+
+Record what you learn here as you go.
+`;
+
+// Web Scraper template: a starter page describing the scrape-and-store loop
+const WEB_SCRAPER_PAGE_TEMPLATE = `---
+title: "Scraper"
+---
+
+# Scraper
+
+Use this workbook to fetch web pages on a schedule and keep the extracted data in tables.
+
+## Sources
+
+List the URLs to fetch and what to extract from each:
+
+| URL | What to extract |
+| --- | --- |
+| https://example.com | Page title and links |
+
+## Schedule
+
+Ask the agent to create an action that fetches the sources above, extracts the
+fields into a table, and runs on a cron schedule (for example every hour).
+
+## Results
+
+Ask for a table or chart of the scraped data to add it to this page.
+`;
+
+/**
+ * Starter templates for new workbooks: template ID -> extra files (relative path -> content)
+ * written on top of the standard workbook structure.
+ */
+export const WORKBOOK_TEMPLATES: Record<string, Record<string, string>> = {
+  empty: {},
+  "data-analysis": {
+    "pages/analysis.mdx": DATA_ANALYSIS_PAGE_TEMPLATE,
+  },
+  "web-scraper": {
+    "pages/scraper.mdx": WEB_SCRAPER_PAGE_TEMPLATE,
+  },
+};
+
 /**
  * Initialize a new workbook with standard structure and starter files.
  * This is the single source of truth for workbook creation - used by CLI and desktop app.
  */
 export async function initWorkbook(options: InitWorkbookOptions): Promise<void> {
-  const { name, directory } = options;
+  const { name, directory, template } = options;
   const { mkdirSync, writeFileSync } = await import("node:fs");
-  const { join } = await import("node:path");
+  const { dirname, join } = await import("node:path");
+
+  // Validate the template before writing anything
+  const templateFiles = template ? WORKBOOK_TEMPLATES[template] : {};
+  if (!templateFiles) {
+    throw new Error(
+      `Unknown template: ${template} (available: ${Object.keys(WORKBOOK_TEMPLATES).join(", ")})`,
+    );
+  }
 
   const slug = slugify(name);
 
@@ -445,4 +521,10 @@ db/
 
   // Create welcome page
   writeFileSync(join(directory, "pages", "welcome.mdx"), WELCOME_PAGE_TEMPLATE);
+
+  // Add the template's starter files
+  for (const [path, content] of Object.entries(templateFiles)) {
+    mkdirSync(dirname(join(directory, path)), { recursive: true });
+    writeFileSync(join(directory, path), content);
+  }
 }